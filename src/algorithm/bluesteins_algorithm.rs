@@ -69,22 +69,12 @@ impl<T: FftNum> BluesteinsAlgorithm<T> {
         assert!(len * 2 - 1 <= inner_fft_len, "Bluestein's algorithm requires inner_fft.len() >= self.len() * 2 - 1. Expected >= {}, got {}", len * 2 - 1, inner_fft_len);
 
         // when computing FFTs, we're going to run our inner multiply pairise by some precomputed data, then run an inverse inner FFT. We need to precompute that inner data here
-        let inner_len_float = T::from_usize(inner_fft_len).unwrap();
         let direction = inner_fft.fft_direction();
-
-        // Compute twiddle factors that we'll run our inner FFT on
-        let mut inner_fft_input = vec![Complex::zero(); inner_fft_len];
-        for i in 0..len {
-            inner_fft_input[i] =
-                Self::compute_bluesteins_twiddle(i, len, direction) / inner_len_float;
-        }
-        for i in 1..len {
-            inner_fft_input[inner_fft_len - i] = inner_fft_input[i];
-        }
-
-        //Compute the inner fft
-        let mut inner_fft_scratch = vec![Complex::zero(); inner_fft.get_inplace_scratch_len()];
-        inner_fft.process_with_scratch(&mut inner_fft_input, &mut inner_fft_scratch);
+        let inner_fft_multiplier =
+            compute_convolution_multiplier(&*inner_fft, len, len, |i: isize| {
+                let index = if i < 0 { -i } else { i };
+                Self::compute_bluesteins_twiddle(index as usize, len, direction)
+            });
 
         // also compute some more mundane twiddle factors to start and end with
         let twiddles: Vec<_> = (0..len)
@@ -94,7 +84,7 @@ impl<T: FftNum> BluesteinsAlgorithm<T> {
         Self {
             inner_fft: inner_fft,
 
-            inner_fft_multiplier: inner_fft_input.into_boxed_slice(),
+            inner_fft_multiplier,
             twiddles: twiddles.into_boxed_slice(),
 
             len,
@@ -106,38 +96,17 @@ impl<T: FftNum> BluesteinsAlgorithm<T> {
         let (inner_input, inner_scratch) = scratch.split_at_mut(self.inner_fft_multiplier.len());
 
         // Copy the buffer into our inner FFT input. the buffer will only fill part of the FFT input, so zero fill the rest
-        for ((buffer_entry, inner_entry), twiddle) in input
-            .iter()
-            .zip(inner_input.iter_mut())
-            .zip(self.twiddles.iter())
-        {
-            *inner_entry = *buffer_entry * *twiddle;
-        }
-        for inner in inner_input.iter_mut().skip(input.len()) {
-            *inner = Complex::zero();
-        }
+        prepare_chirp_convolution(input, &self.twiddles, inner_input);
 
-        // run our inner forward FFT
-        self.inner_fft
-            .process_with_scratch(inner_input, inner_scratch);
-
-        // Multiply our inner FFT output by our precomputed data. Then, conjugate the result to set up for an inverse FFT
-        for (inner, multiplier) in inner_input.iter_mut().zip(self.inner_fft_multiplier.iter()) {
-            *inner = (*inner * *multiplier).conj();
-        }
+        convolve_with_multiplier(
+            &*self.inner_fft,
+            &self.inner_fft_multiplier,
+            inner_input,
+            inner_scratch,
+        );
 
-        // inverse FFT. we're computing a forward but we're massaging it into an inverse by conjugating the inputs and outputs
-        self.inner_fft
-            .process_with_scratch(inner_input, inner_scratch);
-
-        // copy our data back to the buffer, applying twiddle factors again as we go. Also conjugate inner_input to complete the inverse FFT
-        for ((buffer_entry, inner_entry), twiddle) in input
-            .iter_mut()
-            .zip(inner_input.iter())
-            .zip(self.twiddles.iter())
-        {
-            *buffer_entry = inner_entry.conj() * twiddle;
-        }
+        // copy our data back to the buffer, applying twiddle factors again as we go
+        finish_chirp_convolution(inner_input, &self.twiddles, input);
     }
 
     fn perform_fft_out_of_place(
@@ -149,38 +118,17 @@ impl<T: FftNum> BluesteinsAlgorithm<T> {
         let (inner_input, inner_scratch) = scratch.split_at_mut(self.inner_fft_multiplier.len());
 
         // Copy the buffer into our inner FFT input. the buffer will only fill part of the FFT input, so zero fill the rest
-        for ((buffer_entry, inner_entry), twiddle) in input
-            .iter()
-            .zip(inner_input.iter_mut())
-            .zip(self.twiddles.iter())
-        {
-            *inner_entry = *buffer_entry * *twiddle;
-        }
-        for inner in inner_input.iter_mut().skip(input.len()) {
-            *inner = Complex::zero();
-        }
-
-        // run our inner forward FFT
-        self.inner_fft
-            .process_with_scratch(inner_input, inner_scratch);
+        prepare_chirp_convolution(input, &self.twiddles, inner_input);
 
-        // Multiply our inner FFT output by our precomputed data. Then, conjugate the result to set up for an inverse FFT
-        for (inner, multiplier) in inner_input.iter_mut().zip(self.inner_fft_multiplier.iter()) {
-            *inner = (*inner * *multiplier).conj();
-        }
+        convolve_with_multiplier(
+            &*self.inner_fft,
+            &self.inner_fft_multiplier,
+            inner_input,
+            inner_scratch,
+        );
 
-        // inverse FFT. we're computing a forward but we're massaging it into an inverse by conjugating the inputs and outputs
-        self.inner_fft
-            .process_with_scratch(inner_input, inner_scratch);
-
-        // copy our data back to the buffer, applying twiddle factors again as we go. Also conjugate inner_input to complete the inverse FFT
-        for ((buffer_entry, inner_entry), twiddle) in output
-            .iter_mut()
-            .zip(inner_input.iter())
-            .zip(self.twiddles.iter())
-        {
-            *buffer_entry = inner_entry.conj() * twiddle;
-        }
+        // copy our data to the output, applying twiddle factors again as we go
+        finish_chirp_convolution(inner_input, &self.twiddles, output);
     }
 }
 boilerplate_fft!(
@@ -189,9 +137,101 @@ boilerplate_fft!(
     |this: &BluesteinsAlgorithm<_>| this.inner_fft_multiplier.len()
         + this.inner_fft.get_inplace_scratch_len(), // in-place scratch len
     |this: &BluesteinsAlgorithm<_>| this.inner_fft_multiplier.len()
-        + this.inner_fft.get_inplace_scratch_len()  // out of place scratch len
+        + this.inner_fft.get_inplace_scratch_len(), // out of place scratch len
+    |this: &BluesteinsAlgorithm<_>| std::mem::size_of_val(&*this.inner_fft_multiplier)
+        + std::mem::size_of_val(&*this.twiddles), // own heap bytes
+    |this: &BluesteinsAlgorithm<_>| vec![Arc::clone(&this.inner_fft)]  // inner ffts
 );
 
+// The pieces of Bluestein's Algorithm below are shared with the Chirp-Z transform, which is the same computation with
+// different chirps, and with input and output lengths that don't have to match.
+
+// Computes the data that `convolve_with_multiplier` multiplies by: A scaled FFT of the convolution kernel.
+// `kernel` is evaluated at every index in `-(negative_len - 1)..positive_len`, and the negative indexes wrap around to the end of the inner FFT.
+pub(crate) fn compute_convolution_multiplier<T: FftNum>(
+    inner_fft: &dyn Fft<T>,
+    positive_len: usize,
+    negative_len: usize,
+    kernel: impl Fn(isize) -> Complex<T>,
+) -> Box<[Complex<T>]> {
+    let inner_fft_len = inner_fft.len();
+    assert!(
+        positive_len + negative_len - 1 <= inner_fft_len,
+        "The convolution kernel doesn't fit in the inner FFT. Expected inner_fft.len() >= {}, got {}",
+        positive_len + negative_len - 1,
+        inner_fft_len
+    );
+    let inner_len_float = T::from_usize(inner_fft_len).unwrap();
+
+    // Compute twiddle factors that we'll run our inner FFT on
+    let mut inner_fft_input = vec![Complex::zero(); inner_fft_len];
+    for (i, element) in inner_fft_input[..positive_len].iter_mut().enumerate() {
+        *element = kernel(i as isize) / inner_len_float;
+    }
+    for (i, element) in inner_fft_input[inner_fft_len + 1 - negative_len..]
+        .iter_mut()
+        .rev()
+        .enumerate()
+    {
+        *element = kernel(-(i as isize) - 1) / inner_len_float;
+    }
+
+    //Compute the inner fft
+    let mut inner_fft_scratch = vec![Complex::zero(); inner_fft.get_inplace_scratch_len()];
+    inner_fft.process_with_scratch(&mut inner_fft_input, &mut inner_fft_scratch);
+
+    inner_fft_input.into_boxed_slice()
+}
+
+// Multiplies `input` by `chirp` and writes it to the start of `inner_input`, then zero-fills the rest of `inner_input`
+pub(crate) fn prepare_chirp_convolution<T: FftNum>(
+    input: &[Complex<T>],
+    chirp: &[Complex<T>],
+    inner_input: &mut [Complex<T>],
+) {
+    for ((buffer_entry, inner_entry), twiddle) in
+        input.iter().zip(inner_input.iter_mut()).zip(chirp.iter())
+    {
+        *inner_entry = *buffer_entry * *twiddle;
+    }
+    for inner in inner_input.iter_mut().skip(input.len()) {
+        *inner = Complex::zero();
+    }
+}
+
+// Convolves `inner_input` with the kernel that `multiplier` was computed from.
+// To save a pass over the data, the result is left conjugated: `finish_chirp_convolution` undoes the conjugation.
+pub(crate) fn convolve_with_multiplier<T: FftNum>(
+    inner_fft: &dyn Fft<T>,
+    multiplier: &[Complex<T>],
+    inner_input: &mut [Complex<T>],
+    inner_scratch: &mut [Complex<T>],
+) {
+    // run our inner forward FFT
+    inner_fft.process_with_scratch(inner_input, inner_scratch);
+
+    // Multiply our inner FFT output by our precomputed data. Then, conjugate the result to set up for an inverse FFT
+    for (inner, multiplier) in inner_input.iter_mut().zip(multiplier.iter()) {
+        *inner = (*inner * *multiplier).conj();
+    }
+
+    // inverse FFT. we're computing a forward but we're massaging it into an inverse by conjugating the inputs and outputs
+    inner_fft.process_with_scratch(inner_input, inner_scratch);
+}
+
+// Conjugates the first `output.len()` elements of `inner_input` to complete the convolution, multiplies them by `chirp`, and writes them to `output`
+pub(crate) fn finish_chirp_convolution<T: FftNum>(
+    inner_input: &[Complex<T>],
+    chirp: &[Complex<T>],
+    output: &mut [Complex<T>],
+) {
+    for ((buffer_entry, inner_entry), twiddle) in
+        output.iter_mut().zip(inner_input.iter()).zip(chirp.iter())
+    {
+        *buffer_entry = inner_entry.conj() * twiddle;
+    }
+}
+
 #[cfg(test)]
 mod unit_tests {
     use super::*;
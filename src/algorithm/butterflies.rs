@@ -3,8 +3,8 @@ use num_complex::Complex;
 use crate::{common::FftNum, FftDirection};
 
 use crate::array_utils;
-use crate::array_utils::{RawSlice, RawSliceMut};
-use crate::common::{fft_error_inplace, fft_error_outofplace};
+use crate::array_utils::{LoadComplex, RawSlice, RawSliceMut, RawSplitSliceMut, StoreComplex};
+use crate::common::{fft_error_inplace, fft_error_outofplace, fft_error_split};
 use crate::twiddles;
 use crate::{Direction, Fft, Length};
 
@@ -67,6 +67,34 @@ macro_rules! boilerplate_fft_butterfly {
                     fft_error_inplace(self.len(), buffer.len(), 0, 0);
                 }
             }
+            fn process_split_with_scratch(
+                &self,
+                real: &mut [T],
+                imag: &mut [T],
+                _scratch: &mut [Complex<T>],
+            ) {
+                if real.len() != imag.len() || real.len() < self.len() {
+                    // We want to trigger a panic, but we want to avoid doing it in this function to reduce code size, so call a function marked cold and inline(never) that will do it for us
+                    fft_error_split(self.len(), real.len(), imag.len(), 0, 0);
+                    return; // Unreachable, because fft_error_split asserts, but it helps codegen to put it here
+                }
+
+                let result = array_utils::iter_chunks_zipped(
+                    real,
+                    imag,
+                    self.len(),
+                    |real_chunk, imag_chunk| unsafe {
+                        let chunk = RawSplitSliceMut::new(real_chunk, imag_chunk);
+                        self.perform_fft_contiguous(chunk.as_slice(), chunk)
+                    },
+                );
+
+                if result.is_err() {
+                    // We want to trigger a panic, because the buffer sizes weren't cleanly divisible by the FFT size,
+                    // but we want to avoid doing it in this function to reduce code size, so call a function marked cold and inline(never) that will do it for us
+                    fft_error_split(self.len(), real.len(), imag.len(), 0, 0);
+                }
+            }
             #[inline(always)]
             fn get_inplace_scratch_len(&self) -> usize {
                 0
@@ -75,6 +103,10 @@ macro_rules! boilerplate_fft_butterfly {
             fn get_outofplace_scratch_len(&self) -> usize {
                 0
             }
+            #[inline(always)]
+            fn get_split_scratch_len(&self) -> usize {
+                0
+            }
         }
         impl<T> Length for $struct_name<T> {
             #[inline(always)]
@@ -116,6 +148,17 @@ impl<T: FftNum> Fft<T> for Butterfly1<T> {
 
     fn process_with_scratch(&self, _buffer: &mut [Complex<T>], _scratch: &mut [Complex<T>]) {}
 
+    fn process_split_with_scratch(
+        &self,
+        real: &mut [T],
+        imag: &mut [T],
+        _scratch: &mut [Complex<T>],
+    ) {
+        if real.len() != imag.len() || real.is_empty() {
+            fft_error_split(1, real.len(), imag.len(), 0, 0);
+        }
+    }
+
     fn get_inplace_scratch_len(&self) -> usize {
         0
     }
@@ -123,6 +166,10 @@ impl<T: FftNum> Fft<T> for Butterfly1<T> {
     fn get_outofplace_scratch_len(&self) -> usize {
         0
     }
+
+    fn get_split_scratch_len(&self) -> usize {
+        0
+    }
 }
 impl<T> Length for Butterfly1<T> {
     fn len(&self) -> usize {
@@ -156,10 +203,10 @@ impl<T: FftNum> Butterfly2<T> {
         *left = temp;
     }
     #[inline(always)]
-    unsafe fn perform_fft_contiguous(
+    pub(crate) unsafe fn perform_fft_contiguous(
         &self,
-        input: RawSlice<Complex<T>>,
-        output: RawSliceMut<Complex<T>>,
+        input: impl LoadComplex<T>,
+        output: impl StoreComplex<T>,
     ) {
         let value0 = input.load(0);
         let value1 = input.load(1);
@@ -189,10 +236,10 @@ impl<T: FftNum> Butterfly3<T> {
         }
     }
     #[inline(always)]
-    unsafe fn perform_fft_contiguous(
+    pub(crate) unsafe fn perform_fft_contiguous(
         &self,
-        input: RawSlice<Complex<T>>,
-        output: RawSliceMut<Complex<T>>,
+        input: impl LoadComplex<T>,
+        output: impl StoreComplex<T>,
     ) {
         let xp = input.load(1) + input.load(2);
         let xn = input.load(1) - input.load(2);
@@ -230,8 +277,8 @@ impl<T: FftNum> Butterfly4<T> {
     #[inline(always)]
     pub(crate) unsafe fn perform_fft_contiguous(
         &self,
-        input: RawSlice<Complex<T>>,
-        output: RawSliceMut<Complex<T>>,
+        input: impl LoadComplex<T>,
+        output: impl StoreComplex<T>,
     ) {
         //we're going to hardcode a step of mixed radix
         //aka we're going to do the six step algorithm
@@ -279,10 +326,10 @@ impl<T: FftNum> Butterfly5<T> {
     }
 
     #[inline(never)] // refusing to inline this code reduces code size, and doesn't hurt performance
-    unsafe fn perform_fft_contiguous(
+    pub(crate) unsafe fn perform_fft_contiguous(
         &self,
-        input: RawSlice<Complex<T>>,
-        output: RawSliceMut<Complex<T>>,
+        input: impl LoadComplex<T>,
+        output: impl StoreComplex<T>,
     ) {
         // let mut outer = Butterfly2::perform_fft_array([input.load(1), input.load(4)]);
         // let mut inner = Butterfly2::perform_fft_array([input.load(2), input.load(3)]);
@@ -438,10 +485,10 @@ impl<T: FftNum> Butterfly6<T> {
         }
     }
     #[inline(always)]
-    unsafe fn perform_fft_contiguous(
+    pub(crate) unsafe fn perform_fft_contiguous(
         &self,
-        input: RawSlice<Complex<T>>,
-        output: RawSliceMut<Complex<T>>,
+        input: impl LoadComplex<T>,
+        output: impl StoreComplex<T>,
     ) {
         //since GCD(2,3) == 1 we're going to hardcode a step of the Good-Thomas algorithm to avoid twiddle factors
 
@@ -493,10 +540,10 @@ impl<T: FftNum> Butterfly7<T> {
         }
     }
     #[inline(never)]
-    unsafe fn perform_fft_contiguous(
+    pub(crate) unsafe fn perform_fft_contiguous(
         &self,
-        input: RawSlice<Complex<T>>,
-        output: RawSliceMut<Complex<T>>,
+        input: impl LoadComplex<T>,
+        output: impl StoreComplex<T>,
     ) {
         // let mut outer = Butterfly2::perform_fft_array([input.load(1), input.load(6)]);
         // let mut mid   = Butterfly2::perform_fft_array([input.load(2), input.load(5)]);
@@ -686,10 +733,10 @@ impl<T: FftNum> Butterfly8<T> {
     }
 
     #[inline(always)]
-    unsafe fn perform_fft_contiguous(
+    pub(crate) unsafe fn perform_fft_contiguous(
         &self,
-        input: RawSlice<Complex<T>>,
-        output: RawSliceMut<Complex<T>>,
+        input: impl LoadComplex<T>,
+        output: impl StoreComplex<T>,
     ) {
         let butterfly4 = Butterfly4::new(self.direction);
 
@@ -755,8 +802,8 @@ impl<T: FftNum> Butterfly11<T> {
     #[inline(never)]
     unsafe fn perform_fft_contiguous(
         &self,
-        input: RawSlice<Complex<T>>,
-        output: RawSliceMut<Complex<T>>,
+        input: impl LoadComplex<T>,
+        output: impl StoreComplex<T>,
     ) {
         // This function was derived in the same manner as the butterflies for length 3, 5 and 7.
         // However, instead of doing it by hand the actual code is autogenerated
@@ -1011,8 +1058,8 @@ impl<T: FftNum> Butterfly13<T> {
     #[inline(never)]
     unsafe fn perform_fft_contiguous(
         &self,
-        input: RawSlice<Complex<T>>,
-        output: RawSliceMut<Complex<T>>,
+        input: impl LoadComplex<T>,
+        output: impl StoreComplex<T>,
     ) {
         // This function was derived in the same manner as the butterflies for length 3, 5 and 7.
         // However, instead of doing it by hand the actual code is autogenerated
@@ -1321,10 +1368,10 @@ impl<T: FftNum> Butterfly16<T> {
     }
 
     #[inline(never)]
-    unsafe fn perform_fft_contiguous(
+    pub(crate) unsafe fn perform_fft_contiguous(
         &self,
-        input: RawSlice<Complex<T>>,
-        output: RawSliceMut<Complex<T>>,
+        input: impl LoadComplex<T>,
+        output: impl StoreComplex<T>,
     ) {
         let butterfly4 = Butterfly4::new(self.fft_direction());
 
@@ -1429,8 +1476,8 @@ impl<T: FftNum> Butterfly17<T> {
     #[inline(never)]
     unsafe fn perform_fft_contiguous(
         &self,
-        input: RawSlice<Complex<T>>,
-        output: RawSliceMut<Complex<T>>,
+        input: impl LoadComplex<T>,
+        output: impl StoreComplex<T>,
     ) {
         // This function was derived in the same manner as the butterflies for length 3, 5 and 7.
         // However, instead of doing it by hand the actual code is autogenerated
@@ -1915,8 +1962,8 @@ impl<T: FftNum> Butterfly19<T> {
     #[inline(never)]
     unsafe fn perform_fft_contiguous(
         &self,
-        input: RawSlice<Complex<T>>,
-        output: RawSliceMut<Complex<T>>,
+        input: impl LoadComplex<T>,
+        output: impl StoreComplex<T>,
     ) {
         // This function was derived in the same manner as the butterflies for length 3, 5 and 7.
         // However, instead of doing it by hand the actual code is autogenerated
@@ -2498,8 +2545,8 @@ impl<T: FftNum> Butterfly23<T> {
     #[inline(never)]
     unsafe fn perform_fft_contiguous(
         &self,
-        input: RawSlice<Complex<T>>,
-        output: RawSliceMut<Complex<T>>,
+        input: impl LoadComplex<T>,
+        output: impl StoreComplex<T>,
     ) {
         // This function was derived in the same manner as the butterflies for length 3, 5 and 7.
         // However, instead of doing it by hand the actual code is autogenerated
@@ -3304,8 +3351,8 @@ impl<T: FftNum> Butterfly29<T> {
     #[inline(never)]
     unsafe fn perform_fft_contiguous(
         &self,
-        input: RawSlice<Complex<T>>,
-        output: RawSliceMut<Complex<T>>,
+        input: impl LoadComplex<T>,
+        output: impl StoreComplex<T>,
     ) {
         // This function was derived in the same manner as the butterflies for length 3, 5 and 7.
         // However, instead of doing it by hand the actual code is autogenerated
@@ -4481,8 +4528,8 @@ impl<T: FftNum> Butterfly31<T> {
     #[inline(never)]
     unsafe fn perform_fft_contiguous(
         &self,
-        input: RawSlice<Complex<T>>,
-        output: RawSliceMut<Complex<T>>,
+        input: impl LoadComplex<T>,
+        output: impl StoreComplex<T>,
     ) {
         // This function was derived in the same manner as the butterflies for length 3, 5 and 7.
         // However, instead of doing it by hand the actual code is autogenerated
@@ -5766,8 +5813,8 @@ impl<T: FftNum> Butterfly32<T> {
     #[inline(never)]
     unsafe fn perform_fft_contiguous(
         &self,
-        input: RawSlice<Complex<T>>,
-        output: RawSliceMut<Complex<T>>,
+        input: impl LoadComplex<T>,
+        output: impl StoreComplex<T>,
     ) {
         // we're going to hardcode a step of split radix
         // step 1: copy and reorder the  input into the scratch
@@ -5897,7 +5944,7 @@ impl<T: FftNum> Butterfly32<T> {
 #[cfg(test)]
 mod unit_tests {
     use super::*;
-    use crate::test_utils::check_fft_algorithm;
+    use crate::test_utils::{check_fft_algorithm, split_mismatched_lengths, split_partial_chunk};
 
     //the tests for all butterflies will be identical except for the identifiers used and size
     //so it's ideal for a macro
@@ -5929,4 +5976,16 @@ mod unit_tests {
     test_butterfly_func!(test_butterfly29, Butterfly29, 29);
     test_butterfly_func!(test_butterfly31, Butterfly31, 31);
     test_butterfly_func!(test_butterfly32, Butterfly32, 32);
+
+    #[test]
+    #[should_panic(expected = "Real and imaginary buffers must have the same length")]
+    fn test_butterfly_split_mismatched_lengths() {
+        split_mismatched_lengths::<f32>(&Butterfly8::new(FftDirection::Forward));
+    }
+
+    #[test]
+    #[should_panic(expected = "Input FFT buffer must be a multiple of FFT length")]
+    fn test_butterfly_split_partial_chunk() {
+        split_partial_chunk::<f32>(&Butterfly8::new(FftDirection::Forward));
+    }
 }
@@ -2,7 +2,7 @@ use num_complex::Complex;
 use num_traits::Zero;
 
 use crate::array_utils;
-use crate::common::{fft_error_inplace, fft_error_outofplace};
+use crate::common::{fft_error_inplace, fft_error_outofplace, fft_error_split};
 use crate::{twiddles, FftDirection};
 use crate::{Direction, Fft, FftNum, Length};
 
@@ -61,8 +61,39 @@ impl<T: FftNum> Dft<T> {
             }
         }
     }
+
+    fn perform_split_fft_out_of_place(
+        &self,
+        signal_real: &[T],
+        signal_imag: &[T],
+        spectrum_real: &mut [T],
+        spectrum_imag: &mut [T],
+    ) {
+        for k in 0..spectrum_real.len() {
+            let mut sum: Complex<T> = Zero::zero();
+            let mut twiddle_index = 0;
+
+            for (&re, &im) in signal_real.iter().zip(signal_imag.iter()) {
+                let twiddle = self.twiddles[twiddle_index];
+                sum = sum + twiddle * Complex::new(re, im);
+
+                twiddle_index += k;
+                if twiddle_index >= self.twiddles.len() {
+                    twiddle_index -= self.twiddles.len();
+                }
+            }
+
+            spectrum_real[k] = sum.re;
+            spectrum_imag[k] = sum.im;
+        }
+    }
 }
-boilerplate_fft_oop!(Dft, |this: &Dft<_>| this.twiddles.len());
+boilerplate_fft_oop!(
+    Dft,
+    |this: &Dft<_>| this.twiddles.len(),
+    |this: &Dft<_>| std::mem::size_of_val(&this.twiddles[..]),
+    |_| Vec::new()
+);
 
 #[cfg(test)]
 mod unit_tests {
@@ -183,6 +214,8 @@ mod unit_tests {
             &mut zero_output,
             &mut zero_scratch,
         );
+        zero_dft.process_split(&mut [], &mut []);
+        zero_dft.process_split_with_scratch(&mut [], &mut [], &mut zero_scratch);
     }
 
     /// Returns true if our `dft` function calculates the given output from the
@@ -0,0 +1,57 @@
+use std::sync::Arc;
+
+use num_complex::Complex;
+
+use crate::{common::FftNum, FftDirection};
+use crate::{Direction, Fft, Length, OutputPrunedFft};
+
+/// Adapts a [`Fft`](crate::Fft) into an [`OutputPrunedFft`](crate::OutputPrunedFft) that computes every output
+///
+/// This is useful as an inner FFT of [`OutputPrunedMixedRadix`](crate::algorithm::OutputPrunedMixedRadix), when every output of that inner FFT is needed,
+/// or when the inner FFT's algorithm can't be pruned.
+///
+/// ~~~
+/// // Wrap a forward FFT of size 40, so that it can be used as an output-pruned FFT
+/// use std::sync::Arc;
+/// use rustfft::algorithm::FullOutput;
+/// use rustfft::{FftPlanner, OutputPrunedFft};
+/// use rustfft::num_complex::Complex;
+///
+/// let mut buffer = vec![Complex{ re: 0.0f32, im: 0.0f32 }; 40];
+///
+/// let mut planner = FftPlanner::new();
+/// let fft: Arc<dyn OutputPrunedFft<f32>> = Arc::new(FullOutput::new(planner.plan_fft_forward(40)));
+/// fft.process(&mut buffer);
+/// ~~~
+pub struct FullOutput<T> {
+    fft: Arc<dyn Fft<T>>,
+}
+
+impl<T: FftNum> FullOutput<T> {
+    /// Creates an output-pruned FFT instance which computes every output of `fft`
+    pub fn new(fft: Arc<dyn Fft<T>>) -> Self {
+        Self { fft }
+    }
+}
+impl<T: FftNum> OutputPrunedFft<T> for FullOutput<T> {
+    #[inline(always)]
+    fn computes_output(&self, _index: usize) -> bool {
+        true
+    }
+    fn process_with_scratch(&self, buffer: &mut [Complex<T>], scratch: &mut [Complex<T>]) {
+        self.fft.process_with_scratch(buffer, scratch);
+    }
+    fn get_inplace_scratch_len(&self) -> usize {
+        self.fft.get_inplace_scratch_len()
+    }
+}
+impl<T: FftNum> Length for FullOutput<T> {
+    fn len(&self) -> usize {
+        self.fft.len()
+    }
+}
+impl<T: FftNum> Direction for FullOutput<T> {
+    fn fft_direction(&self) -> FftDirection {
+        self.fft.fft_direction()
+    }
+}
@@ -279,7 +279,12 @@ boilerplate_fft!(
     GoodThomasAlgorithm,
     |this: &GoodThomasAlgorithm<_>| this.len,
     |this: &GoodThomasAlgorithm<_>| this.inplace_scratch_len,
-    |this: &GoodThomasAlgorithm<_>| this.outofplace_scratch_len
+    |this: &GoodThomasAlgorithm<_>| this.outofplace_scratch_len,
+    |_| 0,
+    |this: &GoodThomasAlgorithm<_>| vec![
+        Arc::clone(&this.width_size_fft),
+        Arc::clone(&this.height_size_fft)
+    ]
 );
 
 /// Implementation of the Good-Thomas Algorithm, specialized for smaller input sizes
@@ -448,7 +453,12 @@ boilerplate_fft!(
     GoodThomasAlgorithmSmall,
     |this: &GoodThomasAlgorithmSmall<_>| this.width * this.height,
     |this: &GoodThomasAlgorithmSmall<_>| this.len(),
-    |_| 0
+    |_| 0,
+    |this: &GoodThomasAlgorithmSmall<_>| std::mem::size_of_val(&*this.input_output_map),
+    |this: &GoodThomasAlgorithmSmall<_>| vec![
+        Arc::clone(&this.width_size_fft),
+        Arc::clone(&this.height_size_fft)
+    ]
 );
 
 #[cfg(test)]
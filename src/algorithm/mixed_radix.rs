@@ -1,13 +1,15 @@
-use std::cmp::max;
+use std::cmp::{max, min};
+use std::ops::Range;
 use std::sync::Arc;
 
 use num_complex::Complex;
+use num_traits::Zero;
 use transpose;
 
 use crate::array_utils;
 use crate::common::{fft_error_inplace, fft_error_outofplace};
 use crate::{common::FftNum, twiddles, FftDirection};
-use crate::{Direction, Fft, Length};
+use crate::{Direction, Fft, Length, OutputPrunedFft};
 
 /// Implementation of the Mixed-Radix FFT algorithm
 ///
@@ -195,7 +197,392 @@ boilerplate_fft!(
     MixedRadix,
     |this: &MixedRadix<_>| this.twiddles.len(),
     |this: &MixedRadix<_>| this.inplace_scratch_len,
-    |this: &MixedRadix<_>| this.outofplace_scratch_len
+    |this: &MixedRadix<_>| this.outofplace_scratch_len,
+    |this: &MixedRadix<_>| std::mem::size_of_val(&*this.twiddles),
+    |this: &MixedRadix<_>| vec![
+        Arc::clone(&this.width_size_fft),
+        Arc::clone(&this.height_size_fft)
+    ]
+);
+
+/// Input-pruned implementation of the Mixed-Radix FFT algorithm
+///
+/// Computes the same FFT as [`MixedRadix`](crate::algorithm::MixedRadix), for inputs where only the first `nonzero_len` elements are non-zero,
+/// IE heavily zero-padded signals. Elements after the first `nonzero_len` are treated as zero, and never read.
+///
+/// Input `x + y * n1` is element `y` of the size-n2 FFT in column `x`, so only the first `min(nonzero_len, n1)` columns
+/// contain non-zero inputs, and only the first `ceil(nonzero_len / n1)` inputs of each column can be non-zero. The size-n2 FFTs
+/// of the other columns are skipped, and `height_fft` may itself be input-pruned to `ceil(nonzero_len / n1)`.
+///
+/// ~~~
+/// // Computes a forward FFT of size 1200, where only the first 100 inputs are non-zero
+/// use rustfft::algorithm::InputPrunedMixedRadix;
+/// use rustfft::{Fft, FftPlanner};
+/// use rustfft::num_complex::Complex;
+///
+/// let mut buffer = vec![Complex{ re: 0.0f32, im: 0.0f32 }; 1200];
+///
+/// // Each of the 30 columns has at most ceil(100 / 30) = 4 non-zero inputs
+/// let mut planner = FftPlanner::new();
+/// let inner_fft_n1 = planner.plan_fft_forward(30);
+/// let inner_fft_n2 = planner.plan_fft_input_pruned(40, rustfft::FftDirection::Forward, 4);
+///
+/// let fft = InputPrunedMixedRadix::new(inner_fft_n1, inner_fft_n2, 100);
+/// fft.process(&mut buffer);
+/// ~~~
+pub struct InputPrunedMixedRadix<T> {
+    twiddles: Box<[Complex<T>]>,
+
+    width_size_fft: Arc<dyn Fft<T>>,
+    width: usize,
+
+    height_size_fft: Arc<dyn Fft<T>>,
+    height: usize,
+
+    nonzero_len: usize,
+
+    inplace_scratch_len: usize,
+    outofplace_scratch_len: usize,
+
+    direction: FftDirection,
+}
+
+impl<T: FftNum> InputPrunedMixedRadix<T> {
+    /// Creates a FFT instance which will process inputs/outputs of size `width_fft.len() * height_fft.len()`, whose first `nonzero_len` inputs are the only non-zero inputs.
+    ///
+    /// `height_fft` only needs to be correct for inputs whose first `ceil(nonzero_len / width_fft.len())` elements are the only non-zero elements.
+    ///
+    /// # Panics
+    /// Panics if `nonzero_len > width_fft.len() * height_fft.len()`.
+    pub fn new(
+        width_fft: Arc<dyn Fft<T>>,
+        height_fft: Arc<dyn Fft<T>>,
+        nonzero_len: usize,
+    ) -> Self {
+        assert_eq!(
+            width_fft.fft_direction(), height_fft.fft_direction(),
+            "width_fft and height_fft must have the same direction. got width direction={}, height direction={}",
+            width_fft.fft_direction(), height_fft.fft_direction());
+
+        let direction = width_fft.fft_direction();
+
+        let width = width_fft.len();
+        let height = height_fft.len();
+
+        let len = width * height;
+        assert!(
+            nonzero_len <= len,
+            "The number of non-zero inputs can't exceed the FFT length. Expected nonzero_len <= {}, got {}",
+            len,
+            nonzero_len
+        );
+
+        // Only the columns with non-zero inputs need twiddle factors
+        let nonzero_columns = min(nonzero_len, width);
+        let mut twiddles = Vec::with_capacity(nonzero_columns * height);
+        for x in 0..nonzero_columns {
+            for y in 0..height {
+                twiddles.push(twiddles::compute_twiddle(x * y, len, direction));
+            }
+        }
+
+        // The scratch requirements are the same as MixedRadix, because the inner FFTs are used the same way
+        let height_inplace_scratch = height_fft.get_inplace_scratch_len();
+        let width_inplace_scratch = width_fft.get_inplace_scratch_len();
+        let width_outofplace_scratch = width_fft.get_outofplace_scratch_len();
+
+        let max_inner_inplace_scratch = max(height_inplace_scratch, width_inplace_scratch);
+        let outofplace_scratch_len = if max_inner_inplace_scratch > len {
+            max_inner_inplace_scratch
+        } else {
+            0
+        };
+
+        let inplace_scratch_len = len
+            + max(
+                if height_inplace_scratch > len {
+                    height_inplace_scratch
+                } else {
+                    0
+                },
+                width_outofplace_scratch,
+            );
+
+        Self {
+            twiddles: twiddles.into_boxed_slice(),
+
+            width_size_fft: width_fft,
+            width,
+
+            height_size_fft: height_fft,
+            height,
+
+            nonzero_len,
+
+            inplace_scratch_len,
+            outofplace_scratch_len,
+
+            direction,
+        }
+    }
+
+    /// Returns the number of leading inputs that may be non-zero
+    pub fn nonzero_len(&self) -> usize {
+        self.nonzero_len
+    }
+
+    // STEP 1 of the six step FFT: transpose the input, reading only its first `nonzero_len` elements.
+    // Only the columns with non-zero inputs are written
+    fn transpose_nonzero_inputs(&self, input: &[Complex<T>], output: &mut [Complex<T>]) {
+        let nonzero_columns = min(self.nonzero_len, self.width);
+        for element in output[..nonzero_columns * self.height].iter_mut() {
+            *element = Zero::zero();
+        }
+        for (i, element) in input[..self.nonzero_len].iter().enumerate() {
+            output[(i % self.width) * self.height + i / self.width] = *element;
+        }
+    }
+
+    // STEPS 2 and 3 of the six step FFT, skipping the columns whose inputs are all zero
+    fn perform_column_ffts(&self, buffer: &mut [Complex<T>], height_scratch: &mut [Complex<T>]) {
+        let nonzero_columns = min(self.nonzero_len, self.width);
+        let (nonzero_buffer, zero_buffer) = buffer.split_at_mut(nonzero_columns * self.height);
+
+        // STEP 2: perform FFTs of size `height` on the columns with non-zero inputs. The FFT of the other columns is zero
+        if nonzero_columns > 0 {
+            self.height_size_fft
+                .process_with_scratch(nonzero_buffer, height_scratch);
+        }
+        for element in zero_buffer.iter_mut() {
+            *element = Zero::zero();
+        }
+
+        // STEP 3: Apply twiddle factors
+        for (element, twiddle) in nonzero_buffer.iter_mut().zip(self.twiddles.iter()) {
+            *element = *element * twiddle;
+        }
+    }
+
+    fn perform_fft_inplace(&self, buffer: &mut [Complex<T>], scratch: &mut [Complex<T>]) {
+        // SIX STEP FFT:
+        let (scratch, inner_scratch) = scratch.split_at_mut(self.len());
+
+        // STEP 1: transpose
+        self.transpose_nonzero_inputs(buffer, scratch);
+
+        // STEPS 2 and 3: perform FFTs of size `height`, and apply twiddle factors
+        let height_scratch = if inner_scratch.len() > buffer.len() {
+            &mut inner_scratch[..]
+        } else {
+            &mut buffer[..]
+        };
+        self.perform_column_ffts(scratch, height_scratch);
+
+        // STEP 4: transpose again
+        transpose::transpose(scratch, buffer, self.height, self.width);
+
+        // STEP 5: perform FFTs of size `width`
+        self.width_size_fft
+            .process_outofplace_with_scratch(buffer, scratch, inner_scratch);
+
+        // STEP 6: transpose again
+        transpose::transpose(scratch, buffer, self.width, self.height);
+    }
+
+    fn perform_fft_out_of_place(
+        &self,
+        input: &mut [Complex<T>],
+        output: &mut [Complex<T>],
+        scratch: &mut [Complex<T>],
+    ) {
+        // SIX STEP FFT:
+
+        // STEP 1: transpose
+        self.transpose_nonzero_inputs(input, output);
+
+        // STEPS 2 and 3: perform FFTs of size `height`, and apply twiddle factors
+        let height_scratch = if scratch.len() > input.len() {
+            &mut scratch[..]
+        } else {
+            &mut input[..]
+        };
+        self.perform_column_ffts(output, height_scratch);
+
+        // STEP 4: transpose again
+        transpose::transpose(output, input, self.height, self.width);
+
+        // STEP 5: perform FFTs of size `width`
+        let width_scratch = if scratch.len() > output.len() {
+            &mut scratch[..]
+        } else {
+            &mut output[..]
+        };
+        self.width_size_fft
+            .process_with_scratch(input, width_scratch);
+
+        // STEP 6: transpose again
+        transpose::transpose(input, output, self.width, self.height);
+    }
+}
+boilerplate_fft!(
+    InputPrunedMixedRadix,
+    |this: &InputPrunedMixedRadix<_>| this.width * this.height,
+    |this: &InputPrunedMixedRadix<_>| this.inplace_scratch_len,
+    |this: &InputPrunedMixedRadix<_>| this.outofplace_scratch_len,
+    |this: &InputPrunedMixedRadix<_>| std::mem::size_of_val(&*this.twiddles),
+    |this: &InputPrunedMixedRadix<_>| vec![
+        Arc::clone(&this.width_size_fft),
+        Arc::clone(&this.height_size_fft)
+    ]
+);
+
+/// Output-pruned implementation of the Mixed-Radix FFT algorithm
+///
+/// Computes the same FFT as [`MixedRadix`](crate::algorithm::MixedRadix), but the inner FFTs are themselves output-pruned.
+/// Output `x * n2 + y` depends on output `y` of every size-n2 FFT, and on output `x` of the size-n1 FFT that runs on row `y`,
+/// so this computes every output whose `x` and `y` are both computed by the respective inner FFTs. Size-n1 FFTs only run
+/// on the rows whose `y` is computed.
+///
+/// ~~~
+/// // Computes outputs 100..200 of a forward FFT of size 1200
+/// use std::sync::Arc;
+/// use rustfft::algorithm::{FullOutput, OutputPrunedMixedRadix};
+/// use rustfft::{FftPlanner, OutputPrunedFft};
+/// use rustfft::num_complex::Complex;
+///
+/// let mut buffer = vec![Complex{ re: 0.0f32, im: 0.0f32 }; 1200];
+///
+/// // Output k is at x = k / 40, y = k % 40. So outputs 100..200 need x in 2..5, and every y
+/// let mut planner = FftPlanner::new();
+/// let width_mask: Vec<bool> = (0..30).map(|x| x >= 2 && x < 5).collect();
+/// let inner_fft_n1 = planner.plan_fft_output_pruned(30, rustfft::FftDirection::Forward, &width_mask);
+/// let inner_fft_n2 = Arc::new(FullOutput::new(planner.plan_fft_forward(40)));
+///
+/// let fft = OutputPrunedMixedRadix::new(inner_fft_n1, inner_fft_n2);
+/// fft.process(&mut buffer);
+/// ~~~
+pub struct OutputPrunedMixedRadix<T> {
+    twiddles: Box<[Complex<T>]>,
+
+    width_size_fft: Arc<dyn OutputPrunedFft<T>>,
+    width: usize,
+
+    height_size_fft: Arc<dyn OutputPrunedFft<T>>,
+    height: usize,
+
+    // Runs of consecutive rows whose height output is computed. The width FFTs only run on these rows
+    computed_rows: Box<[Range<usize>]>,
+
+    inplace_scratch_len: usize,
+
+    direction: FftDirection,
+}
+
+impl<T: FftNum> OutputPrunedMixedRadix<T> {
+    /// Creates a FFT instance which will process inputs/outputs of size `width_fft.len() * height_fft.len()`
+    pub fn new(
+        width_fft: Arc<dyn OutputPrunedFft<T>>,
+        height_fft: Arc<dyn OutputPrunedFft<T>>,
+    ) -> Self {
+        assert_eq!(
+            width_fft.fft_direction(), height_fft.fft_direction(),
+            "width_fft and height_fft must have the same direction. got width direction={}, height direction={}",
+            width_fft.fft_direction(), height_fft.fft_direction());
+
+        let direction = width_fft.fft_direction();
+
+        let width = width_fft.len();
+        let height = height_fft.len();
+
+        let len = width * height;
+
+        let mut twiddles = Vec::with_capacity(len);
+        for x in 0..width {
+            for y in 0..height {
+                twiddles.push(twiddles::compute_twiddle(x * y, len, direction));
+            }
+        }
+
+        let mut computed_rows: Vec<Range<usize>> = Vec::new();
+        for y in (0..height).filter(|&y| height_fft.computes_output(y)) {
+            match computed_rows.last_mut() {
+                Some(run) if run.end == y => run.end += 1,
+                _ => computed_rows.push(y..y + 1),
+            }
+        }
+
+        // Both inner FFTs are in-place, and they run on our own scratch or on the buffer, so we need self.len() scratch, plus whatever they need
+        let inplace_scratch_len = len
+            + max(
+                width_fft.get_inplace_scratch_len(),
+                height_fft.get_inplace_scratch_len(),
+            );
+
+        Self {
+            twiddles: twiddles.into_boxed_slice(),
+
+            width_size_fft: width_fft,
+            width,
+
+            height_size_fft: height_fft,
+            height,
+
+            computed_rows: computed_rows.into_boxed_slice(),
+
+            inplace_scratch_len,
+
+            direction,
+        }
+    }
+
+    fn perform_fft_inplace(&self, buffer: &mut [Complex<T>], scratch: &mut [Complex<T>]) {
+        // SIX STEP FFT, skipping the rows that don't contribute to any computed output:
+        let (scratch, inner_scratch) = scratch.split_at_mut(self.len());
+
+        // STEP 1: transpose
+        transpose::transpose(buffer, scratch, self.width, self.height);
+
+        // STEP 2: perform FFTs of size `height`
+        self.height_size_fft
+            .process_with_scratch(scratch, inner_scratch);
+
+        // STEP 3: Apply twiddle factors to the rows that the width FFTs will use
+        for (column, twiddles) in scratch
+            .chunks_exact_mut(self.height)
+            .zip(self.twiddles.chunks_exact(self.height))
+        {
+            for run in self.computed_rows.iter() {
+                for (element, twiddle) in column[run.clone()].iter_mut().zip(&twiddles[run.clone()])
+                {
+                    *element = *element * twiddle;
+                }
+            }
+        }
+
+        // STEP 4: transpose again
+        transpose::transpose(scratch, buffer, self.height, self.width);
+
+        // STEP 5: perform FFTs of size `width` on the computed rows
+        for run in self.computed_rows.iter() {
+            self.width_size_fft.process_with_scratch(
+                &mut buffer[run.start * self.width..run.end * self.width],
+                inner_scratch,
+            );
+        }
+
+        // STEP 6: transpose again
+        transpose::transpose(buffer, scratch, self.width, self.height);
+        buffer.copy_from_slice(scratch);
+    }
+}
+boilerplate_output_pruned_fft!(
+    OutputPrunedMixedRadix,
+    |this: &OutputPrunedMixedRadix<_>| this.twiddles.len(),
+    |this: &OutputPrunedMixedRadix<_>| this.inplace_scratch_len,
+    |this: &OutputPrunedMixedRadix<_>, index: usize| {
+        this.width_size_fft.computes_output(index / this.height)
+            && this.height_size_fft.computes_output(index % this.height)
+    }
 );
 
 /// Implementation of the Mixed-Radix FFT algorithm, specialized for smaller input sizes
@@ -333,14 +720,24 @@ boilerplate_fft!(
     MixedRadixSmall,
     |this: &MixedRadixSmall<_>| this.twiddles.len(),
     |this: &MixedRadixSmall<_>| this.len(),
-    |_| 0
+    |_| 0,
+    |this: &MixedRadixSmall<_>| std::mem::size_of_val(&*this.twiddles),
+    |this: &MixedRadixSmall<_>| vec![
+        Arc::clone(&this.width_size_fft),
+        Arc::clone(&this.height_size_fft)
+    ]
 );
 
 #[cfg(test)]
 mod unit_tests {
     use super::*;
-    use crate::test_utils::check_fft_algorithm;
+    use crate::algorithm::{FullOutput, InputPrunedRadix4, OutputPrunedRadix4};
+    use crate::test_utils::{
+        check_fft_algorithm, check_input_pruned_fft_algorithm, check_output_pruned_fft_algorithm,
+        split_mismatched_lengths, split_partial_chunk, split_small_scratch,
+    };
     use crate::{algorithm::Dft, test_utils::BigScratchAlgorithm};
+    use num_integer::div_ceil;
     use num_traits::Zero;
     use std::sync::Arc;
 
@@ -354,6 +751,33 @@ mod unit_tests {
         }
     }
 
+    #[test]
+    #[should_panic(expected = "Real and imaginary buffers must have the same length")]
+    fn test_mixed_radix_split_mismatched_lengths() {
+        split_mismatched_lengths::<f32>(&MixedRadix::new(
+            Arc::new(Dft::new(3, FftDirection::Forward)),
+            Arc::new(Dft::new(4, FftDirection::Forward)),
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "Input FFT buffer must be a multiple of FFT length")]
+    fn test_mixed_radix_split_partial_chunk() {
+        split_partial_chunk::<f32>(&MixedRadix::new(
+            Arc::new(Dft::new(3, FftDirection::Forward)),
+            Arc::new(Dft::new(4, FftDirection::Forward)),
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "Not enough scratch space was provided")]
+    fn test_mixed_radix_split_small_scratch() {
+        split_small_scratch::<f32>(&MixedRadix::new(
+            Arc::new(Dft::new(3, FftDirection::Forward)),
+            Arc::new(Dft::new(4, FftDirection::Forward)),
+        ));
+    }
+
     #[test]
     fn test_mixed_radix_small() {
         for width in 2..7 {
@@ -364,6 +788,90 @@ mod unit_tests {
         }
     }
 
+    #[test]
+    fn test_output_pruned_mixed_radix() {
+        for &width in &[1, 2, 4, 16] {
+            for &height in &[1, 2, 8, 32] {
+                for &direction in &[FftDirection::Forward, FftDirection::Inverse] {
+                    test_output_pruned_mixed_radix_with_lengths(width, height, direction);
+                }
+            }
+        }
+    }
+
+    fn test_output_pruned_mixed_radix_with_lengths(
+        width: usize,
+        height: usize,
+        direction: FftDirection,
+    ) {
+        let len = width * height;
+        let band_start = len / 5;
+        let band_end = band_start + len / 7 + 1;
+        let output_mask: Vec<bool> = (0..len).map(|k| k >= band_start && k < band_end).collect();
+
+        // Output k is at x = k / height and y = k % height, so prune each inner FFT to the x's and y's of the selected outputs
+        let mut width_mask = vec![false; width];
+        let mut height_mask = vec![false; height];
+        for k in band_start..band_end {
+            width_mask[k / height] = true;
+            height_mask[k % height] = true;
+        }
+
+        let width_fft = Arc::new(OutputPrunedRadix4::new(width, direction, &width_mask))
+            as Arc<dyn OutputPrunedFft<f32>>;
+        let height_fft = Arc::new(OutputPrunedRadix4::new(height, direction, &height_mask))
+            as Arc<dyn OutputPrunedFft<f32>>;
+        let fft = OutputPrunedMixedRadix::new(width_fft, height_fft);
+        check_output_pruned_fft_algorithm(&fft, &output_mask, direction);
+
+        // Unpruned inner FFTs should compute everything
+        let width_fft = Arc::new(FullOutput::new(Arc::new(Dft::new(width, direction))))
+            as Arc<dyn OutputPrunedFft<f32>>;
+        let height_fft = Arc::new(FullOutput::new(Arc::new(Dft::new(height, direction))))
+            as Arc<dyn OutputPrunedFft<f32>>;
+        let fft = OutputPrunedMixedRadix::new(width_fft, height_fft);
+        check_output_pruned_fft_algorithm(&fft, &vec![true; len], direction);
+    }
+
+    #[test]
+    fn test_input_pruned_mixed_radix() {
+        for &width in &[1, 3, 4, 16] {
+            for &height in &[1, 2, 8, 32] {
+                let len = width * height;
+                for &nonzero_len in &[0, 1, width / 2 + 1, len / 5 + 1, len] {
+                    let nonzero_len = std::cmp::min(nonzero_len, len);
+                    for &direction in &[FftDirection::Forward, FftDirection::Inverse] {
+                        test_input_pruned_mixed_radix_with_lengths(
+                            width,
+                            height,
+                            nonzero_len,
+                            direction,
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn test_input_pruned_mixed_radix_with_lengths(
+        width: usize,
+        height: usize,
+        nonzero_len: usize,
+        direction: FftDirection,
+    ) {
+        let len = width * height;
+        let height_nonzero_len = div_ceil(nonzero_len, width);
+
+        let width_fft = Arc::new(Dft::new(width, direction)) as Arc<dyn Fft<f32>>;
+        let height_fft = Arc::new(InputPrunedRadix4::new(
+            height,
+            direction,
+            height_nonzero_len,
+        )) as Arc<dyn Fft<f32>>;
+        let fft = InputPrunedMixedRadix::new(width_fft, height_fft, nonzero_len);
+        check_input_pruned_fft_algorithm(&fft, len, nonzero_len, direction, true);
+    }
+
     fn test_mixed_radix_with_lengths(width: usize, height: usize, direction: FftDirection) {
         let width_fft = Arc::new(Dft::new(width, direction)) as Arc<dyn Fft<f32>>;
         let height_fft = Arc::new(Dft::new(height, direction)) as Arc<dyn Fft<f32>>;
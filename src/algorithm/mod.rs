@@ -1,16 +1,20 @@
-mod bluesteins_algorithm;
+pub(crate) mod bluesteins_algorithm;
 mod dft;
+mod full_output;
 mod good_thomas_algorithm;
 mod mixed_radix;
 mod raders_algorithm;
-mod radix4;
+pub(crate) mod radix4;
 
 /// Hardcoded size-specfic FFT algorithms
 pub mod butterflies;
 
 pub use self::bluesteins_algorithm::BluesteinsAlgorithm;
 pub use self::dft::Dft;
+pub use self::full_output::FullOutput;
 pub use self::good_thomas_algorithm::{GoodThomasAlgorithm, GoodThomasAlgorithmSmall};
-pub use self::mixed_radix::{MixedRadix, MixedRadixSmall};
+pub use self::mixed_radix::{
+    InputPrunedMixedRadix, MixedRadix, MixedRadixSmall, OutputPrunedMixedRadix,
+};
 pub use self::raders_algorithm::RadersAlgorithm;
-pub use self::radix4::Radix4;
+pub use self::radix4::{InputPrunedRadix4, OutputPrunedRadix4, Radix4};
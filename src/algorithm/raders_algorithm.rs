@@ -222,7 +222,9 @@ boilerplate_fft!(
     RadersAlgorithm,
     |this: &RadersAlgorithm<_>| this.len.get(),
     |this: &RadersAlgorithm<_>| this.inplace_scratch_len,
-    |this: &RadersAlgorithm<_>| this.outofplace_scratch_len
+    |this: &RadersAlgorithm<_>| this.outofplace_scratch_len,
+    |this: &RadersAlgorithm<_>| std::mem::size_of_val(&*this.inner_fft_data),
+    |this: &RadersAlgorithm<_>| vec![Arc::clone(&this.inner_fft)]
 );
 
 #[cfg(test)]
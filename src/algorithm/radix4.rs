@@ -1,3 +1,4 @@
+use std::ops::Range;
 use std::sync::Arc;
 
 use num_complex::Complex;
@@ -5,13 +6,13 @@ use num_traits::Zero;
 
 use crate::algorithm::butterflies::{Butterfly1, Butterfly16, Butterfly2, Butterfly4, Butterfly8};
 use crate::array_utils;
-use crate::common::{fft_error_inplace, fft_error_outofplace};
+use crate::common::{fft_error_inplace, fft_error_outofplace, fft_error_split};
 use crate::{
-    array_utils::{RawSlice, RawSliceMut},
+    array_utils::{LoadComplex, RawSlice, RawSliceMut, RawSplitSliceMut, StoreComplex},
     common::FftNum,
     twiddles, FftDirection,
 };
-use crate::{Direction, Fft, Length};
+use crate::{Direction, Fft, Length, OutputPrunedFft};
 
 /// FFT algorithm optimized for power-of-two sizes
 ///
@@ -111,7 +112,7 @@ impl<T: FftNum> Radix4<T> {
             for i in 0..num_rows {
                 unsafe {
                     butterfly_4(
-                        &mut spectrum[i * current_size..],
+                        RawSliceMut::new(&mut spectrum[i * current_size..]),
                         layer_twiddles,
                         current_size / 4,
                         self.direction,
@@ -126,16 +127,452 @@ impl<T: FftNum> Radix4<T> {
             current_size *= 4;
         }
     }
+
+    fn perform_split_fft_out_of_place(
+        &self,
+        signal_real: &[T],
+        signal_imag: &[T],
+        spectrum_real: &mut [T],
+        spectrum_imag: &mut [T],
+    ) {
+        // copy the real and imaginary parts into the spectrum vectors separately, so they never need to be interleaved
+        prepare_radix4(
+            signal_real.len(),
+            self.base_len,
+            signal_real,
+            spectrum_real,
+            1,
+        );
+        prepare_radix4(
+            signal_imag.len(),
+            self.base_len,
+            signal_imag,
+            spectrum_imag,
+            1,
+        );
+
+        // Base-level FFTs
+        self.base_fft
+            .process_split_with_scratch(spectrum_real, spectrum_imag, &mut []);
+
+        // cross-FFTs
+        let mut current_size = self.base_len * 4;
+        let mut layer_twiddles: &[Complex<T>] = &self.twiddles;
+
+        while current_size <= signal_real.len() {
+            let num_rows = signal_real.len() / current_size;
+
+            for i in 0..num_rows {
+                unsafe {
+                    butterfly_4(
+                        RawSplitSliceMut::new(
+                            &mut spectrum_real[i * current_size..],
+                            &mut spectrum_imag[i * current_size..],
+                        ),
+                        layer_twiddles,
+                        current_size / 4,
+                        self.direction,
+                    )
+                }
+            }
+
+            //skip past all the twiddle factors used in this layer
+            let twiddle_offset = (current_size * 3) / 4;
+            layer_twiddles = &layer_twiddles[twiddle_offset..];
+
+            current_size *= 4;
+        }
+    }
+}
+boilerplate_fft_oop!(
+    Radix4,
+    |this: &Radix4<_>| this.len,
+    |this: &Radix4<_>| std::mem::size_of_val(&*this.twiddles),
+    |this: &Radix4<_>| vec![Arc::clone(&this.base_fft)]
+);
+
+/// Output-pruned FFT algorithm for power-of-two sizes
+///
+/// Computes the same FFT as [`Radix4`](crate::algorithm::Radix4), but only computes the outputs selected by `output_mask`.
+/// Each radix-4 butterfly in the final layer produces 4 outputs, so any butterfly whose outputs are all unselected is skipped.
+/// That in turn leaves outputs of the previous layer unused, so the pruning propagates down through the layers until every butterfly is needed.
+///
+/// ~~~
+/// // Computes the first 64 outputs of a forward FFT of size 4096
+/// use rustfft::algorithm::OutputPrunedRadix4;
+/// use rustfft::{FftDirection, OutputPrunedFft};
+/// use rustfft::num_complex::Complex;
+///
+/// let mut buffer = vec![Complex{ re: 0.0f32, im: 0.0f32 }; 4096];
+///
+/// let output_mask: Vec<bool> = (0..4096).map(|i| i < 64).collect();
+/// let fft = OutputPrunedRadix4::new(4096, FftDirection::Forward, &output_mask);
+/// fft.process(&mut buffer);
+/// ~~~
+pub struct OutputPrunedRadix4<T> {
+    radix4: Radix4<T>,
+
+    // For each layer of cross-FFTs, starting with the bottom layer, the butterflies that contribute to a selected output
+    layer_butterflies: Box<[Box<[usize]>]>,
+    output_mask: Box<[bool]>,
+
+    direction: FftDirection,
+}
+
+impl<T: FftNum> OutputPrunedRadix4<T> {
+    /// Preallocates necessary arrays and precomputes necessary data to efficiently compute the selected outputs of the power-of-two FFT
+    ///
+    /// # Panics
+    /// Panics if `len` isn't a power of two, or if `output_mask.len() != len`.
+    pub fn new(len: usize, direction: FftDirection, output_mask: &[bool]) -> Self {
+        assert_eq!(
+            output_mask.len(),
+            len,
+            "The output mask must contain one element per FFT output. Expected {}, got {}",
+            len,
+            output_mask.len()
+        );
+        let radix4 = Radix4::new(len, direction);
+
+        // Walk down from the top layer: Butterfly i of a cross-FFT of size `current_size` writes to outputs i, i + current_size/4, etc,
+        // so it's needed if any of those outputs are. The needed butterflies become the needed outputs of the layer below
+        let mut needed_outputs = output_mask.to_vec();
+        let mut layer_butterflies = Vec::new();
+        let mut current_size = len;
+        while current_size >= radix4.base_len * 4 {
+            let num_ffts = current_size / 4;
+            needed_outputs = (0..num_ffts)
+                .map(|i| (0..4).any(|k| needed_outputs[i + k * num_ffts]))
+                .collect();
+            layer_butterflies.push(
+                needed_outputs
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, &needed)| needed)
+                    .map(|(i, _)| i)
+                    .collect(),
+            );
+            current_size = num_ffts;
+        }
+        layer_butterflies.reverse();
+
+        Self {
+            radix4,
+
+            layer_butterflies: layer_butterflies.into_boxed_slice(),
+            output_mask: output_mask.to_vec().into_boxed_slice(),
+
+            direction,
+        }
+    }
+
+    fn perform_fft_inplace(&self, buffer: &mut [Complex<T>], scratch: &mut [Complex<T>]) {
+        // copy the data into the scratch vector
+        prepare_radix4(buffer.len(), self.radix4.base_len, buffer, scratch, 1);
+
+        // Base-level FFTs
+        self.radix4.base_fft.process_with_scratch(scratch, &mut []);
+
+        // cross-FFTs, skipping the butterflies that don't contribute to any selected output
+        let mut current_size = self.radix4.base_len * 4;
+        let mut layer_twiddles: &[Complex<T>] = &self.radix4.twiddles;
+
+        for butterflies in self.layer_butterflies.iter() {
+            let num_rows = buffer.len() / current_size;
+
+            for i in 0..num_rows {
+                unsafe {
+                    butterfly_4_pruned(
+                        RawSliceMut::new(&mut scratch[i * current_size..]),
+                        layer_twiddles,
+                        current_size / 4,
+                        butterflies,
+                        self.direction,
+                    )
+                }
+            }
+
+            //skip past all the twiddle factors used in this layer
+            let twiddle_offset = (current_size * 3) / 4;
+            layer_twiddles = &layer_twiddles[twiddle_offset..];
+
+            current_size *= 4;
+        }
+
+        // copy the selected outputs back into the buffer
+        for ((output, result), &selected) in buffer
+            .iter_mut()
+            .zip(scratch.iter())
+            .zip(self.output_mask.iter())
+        {
+            if selected {
+                *output = *result;
+            }
+        }
+    }
+}
+boilerplate_output_pruned_fft!(
+    OutputPrunedRadix4,
+    |this: &OutputPrunedRadix4<_>| this.radix4.len,
+    |this: &OutputPrunedRadix4<_>| this.radix4.len,
+    |this: &OutputPrunedRadix4<_>, index: usize| this.output_mask[index]
+);
+
+/// Input-pruned FFT algorithm for power-of-two sizes
+///
+/// Computes the same FFT as [`Radix4`](crate::algorithm::Radix4), for inputs where only the first `nonzero_len` elements are non-zero,
+/// IE heavily zero-padded signals. Elements after the first `nonzero_len` are treated as zero, and never read.
+///
+/// Radix4 starts by computing many small FFTs, each on inputs spaced `len / base_len` apart. When most of the input is zero padding,
+/// most of these small FFTs only see zeros, so they're skipped, along with every butterfly in the following layers whose inputs are all zero.
+///
+/// ~~~
+/// // Computes a forward FFT of size 4096, where only the first 100 inputs are non-zero
+/// use rustfft::algorithm::InputPrunedRadix4;
+/// use rustfft::{Fft, FftDirection};
+/// use rustfft::num_complex::Complex;
+///
+/// let mut buffer = vec![Complex{ re: 0.0f32, im: 0.0f32 }; 4096];
+///
+/// let fft = InputPrunedRadix4::new(4096, FftDirection::Forward, 100);
+/// fft.process(&mut buffer);
+/// ~~~
+pub struct InputPrunedRadix4<T> {
+    radix4: Radix4<T>,
+    nonzero_len: usize,
+
+    // For each base-level FFT, the index of its first input. Its other inputs follow at a stride of len / base_len
+    base_offsets: Box<[usize]>,
+
+    // Runs of consecutive base-level FFTs that have at least one non-zero input
+    nonzero_base_rows: Box<[Range<usize>]>,
+
+    // For each layer of cross-FFTs, starting with the bottom layer, the cross-FFTs that have at least one non-zero input
+    layer_rows: Box<[Box<[usize]>]>,
+
+    direction: FftDirection,
+}
+
+impl<T: FftNum> InputPrunedRadix4<T> {
+    /// Preallocates necessary arrays and precomputes necessary data to efficiently compute the power-of-two FFT of an input
+    /// whose first `nonzero_len` elements are the only non-zero elements
+    ///
+    /// # Panics
+    /// Panics if `len` isn't a power of two, or if `nonzero_len > len`.
+    pub fn new(len: usize, direction: FftDirection, nonzero_len: usize) -> Self {
+        assert!(
+            nonzero_len <= len,
+            "The number of non-zero inputs can't exceed the FFT length. Expected nonzero_len <= {}, got {}",
+            len,
+            nonzero_len
+        );
+        let radix4 = Radix4::new(len, direction);
+
+        // prepare_radix4 sends the inputs at index `offset + stride * i` to base-level FFT `row`, where `offset` is `row` with its base-4 digits reversed.
+        // The smallest input index is the offset, so a base-level FFT has a non-zero input if its offset is less than nonzero_len
+        let base_rows = len / radix4.base_len;
+        let digit_count = base_rows.trailing_zeros() / 2;
+        let base_offsets: Vec<usize> = (0..base_rows)
+            .map(|row| reverse_base4_digits(row, digit_count))
+            .collect();
+
+        let mut nonzero_rows: Vec<bool> = base_offsets
+            .iter()
+            .map(|&offset| offset < nonzero_len)
+            .collect();
+        let mut nonzero_base_rows: Vec<Range<usize>> = Vec::new();
+        for row in (0..base_rows).filter(|&row| nonzero_rows[row]) {
+            match nonzero_base_rows.last_mut() {
+                Some(run) if run.end == row => run.end += 1,
+                _ => nonzero_base_rows.push(row..row + 1),
+            }
+        }
+
+        // Each cross-FFT combines 4 consecutive FFTs from the layer below, so it has a non-zero input if any of them do
+        let mut layer_rows = Vec::new();
+        let mut current_size = radix4.base_len * 4;
+        while current_size <= len {
+            nonzero_rows = nonzero_rows
+                .chunks_exact(4)
+                .map(|rows| rows.iter().any(|&nonzero| nonzero))
+                .collect();
+            layer_rows.push(
+                nonzero_rows
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, &nonzero)| nonzero)
+                    .map(|(i, _)| i)
+                    .collect(),
+            );
+            current_size *= 4;
+        }
+
+        Self {
+            radix4,
+            nonzero_len,
+
+            base_offsets: base_offsets.into_boxed_slice(),
+            nonzero_base_rows: nonzero_base_rows.into_boxed_slice(),
+            layer_rows: layer_rows.into_boxed_slice(),
+
+            direction,
+        }
+    }
+
+    /// Returns the number of leading inputs that may be non-zero
+    pub fn nonzero_len(&self) -> usize {
+        self.nonzero_len
+    }
+
+    fn perform_fft_out_of_place(
+        &self,
+        signal: &[Complex<T>],
+        spectrum: &mut [Complex<T>],
+        _scratch: &mut [Complex<T>],
+    ) {
+        let base_len = self.radix4.base_len;
+
+        // Start from all zeros, then copy the non-zero inputs into the base-level FFTs that use them
+        for element in spectrum.iter_mut() {
+            *element = Zero::zero();
+        }
+        for run in self.nonzero_base_rows.iter() {
+            self.gather_base_inputs(run.clone(), signal, spectrum);
+
+            // Base-level FFTs
+            self.radix4.base_fft.process_with_scratch(
+                &mut spectrum[run.start * base_len..run.end * base_len],
+                &mut [],
+            );
+        }
+
+        // cross-FFTs, skipping the ones whose inputs are all zero
+        let mut current_size = base_len * 4;
+        let mut layer_twiddles: &[Complex<T>] = &self.radix4.twiddles;
+
+        for rows in self.layer_rows.iter() {
+            for &i in rows.iter() {
+                unsafe {
+                    butterfly_4(
+                        RawSliceMut::new(&mut spectrum[i * current_size..]),
+                        layer_twiddles,
+                        current_size / 4,
+                        self.direction,
+                    )
+                }
+            }
+
+            //skip past all the twiddle factors used in this layer
+            let twiddle_offset = (current_size * 3) / 4;
+            layer_twiddles = &layer_twiddles[twiddle_offset..];
+
+            current_size *= 4;
+        }
+    }
+
+    fn perform_split_fft_out_of_place(
+        &self,
+        signal_real: &[T],
+        signal_imag: &[T],
+        spectrum_real: &mut [T],
+        spectrum_imag: &mut [T],
+    ) {
+        let base_len = self.radix4.base_len;
+
+        // Start from all zeros, then copy the non-zero real and imaginary parts into the base-level FFTs that use them
+        for (re, im) in spectrum_real.iter_mut().zip(spectrum_imag.iter_mut()) {
+            *re = Zero::zero();
+            *im = Zero::zero();
+        }
+        for run in self.nonzero_base_rows.iter() {
+            self.gather_base_inputs(run.clone(), signal_real, spectrum_real);
+            self.gather_base_inputs(run.clone(), signal_imag, spectrum_imag);
+
+            // Base-level FFTs
+            let run_range = run.start * base_len..run.end * base_len;
+            self.radix4.base_fft.process_split_with_scratch(
+                &mut spectrum_real[run_range.clone()],
+                &mut spectrum_imag[run_range],
+                &mut [],
+            );
+        }
+
+        // cross-FFTs, skipping the ones whose inputs are all zero
+        let mut current_size = base_len * 4;
+        let mut layer_twiddles: &[Complex<T>] = &self.radix4.twiddles;
+
+        for rows in self.layer_rows.iter() {
+            for &i in rows.iter() {
+                unsafe {
+                    butterfly_4(
+                        RawSplitSliceMut::new(
+                            &mut spectrum_real[i * current_size..],
+                            &mut spectrum_imag[i * current_size..],
+                        ),
+                        layer_twiddles,
+                        current_size / 4,
+                        self.direction,
+                    )
+                }
+            }
+
+            //skip past all the twiddle factors used in this layer
+            let twiddle_offset = (current_size * 3) / 4;
+            layer_twiddles = &layer_twiddles[twiddle_offset..];
+
+            current_size *= 4;
+        }
+    }
+
+    // Copies the non-zero inputs of the base-level FFTs in `rows` from `signal` to `spectrum`. Works on complex numbers, or on real or imaginary parts alone
+    fn gather_base_inputs<U: Copy>(&self, rows: Range<usize>, signal: &[U], spectrum: &mut [U]) {
+        let base_len = self.radix4.base_len;
+        let stride = spectrum.len() / base_len;
+
+        for row in rows {
+            let offset = self.base_offsets[row];
+            for (i, element) in spectrum[row * base_len..(row + 1) * base_len]
+                .iter_mut()
+                .enumerate()
+                .take_while(|(i, _)| offset + i * stride < self.nonzero_len)
+            {
+                *element = signal[offset + i * stride];
+            }
+        }
+    }
+}
+boilerplate_fft_oop!(
+    InputPrunedRadix4,
+    |this: &InputPrunedRadix4<_>| this.radix4.len,
+    |this: &InputPrunedRadix4<_>| this.radix4.own_heap_bytes()
+        + std::mem::size_of_val(&*this.base_offsets)
+        + std::mem::size_of_val(&*this.nonzero_base_rows)
+        + std::mem::size_of_val(&*this.layer_rows)
+        + this
+            .layer_rows
+            .iter()
+            .map(|row| std::mem::size_of_val(&**row))
+            .sum::<usize>(),
+    |this: &InputPrunedRadix4<_>| this.radix4.inner_ffts()
+);
+
+fn reverse_base4_digits(value: usize, digit_count: u32) -> usize {
+    let mut value = value;
+    let mut result = 0;
+    for _ in 0..digit_count {
+        result = (result << 2) | (value & 3);
+        value >>= 2;
+    }
+    result
 }
-boilerplate_fft_oop!(Radix4, |this: &Radix4<_>| this.len);
 
 // after testing an iterative bit reversal algorithm, this recursive algorithm
 // was almost an order of magnitude faster at setting up
-fn prepare_radix4<T: FftNum>(
+pub(crate) fn prepare_radix4<T: Copy>(
     size: usize,
     base_len: usize,
-    signal: &[Complex<T>],
-    spectrum: &mut [Complex<T>],
+    signal: &[T],
+    spectrum: &mut [T],
     stride: usize,
 ) {
     if size == base_len {
@@ -158,7 +595,7 @@ fn prepare_radix4<T: FftNum>(
 }
 
 unsafe fn butterfly_4<T: FftNum>(
-    data: &mut [Complex<T>],
+    data: impl LoadComplex<T> + StoreComplex<T>,
     twiddles: &[Complex<T>],
     num_ffts: usize,
     direction: FftDirection,
@@ -167,29 +604,59 @@ unsafe fn butterfly_4<T: FftNum>(
 
     let mut idx = 0usize;
     let mut tw_idx = 0usize;
-    let mut scratch = [Zero::zero(); 4];
     for _ in 0..num_ffts {
-        scratch[0] = *data.get_unchecked(idx);
-        scratch[1] = *data.get_unchecked(idx + 1 * num_ffts) * twiddles[tw_idx];
-        scratch[2] = *data.get_unchecked(idx + 2 * num_ffts) * twiddles[tw_idx + 1];
-        scratch[3] = *data.get_unchecked(idx + 3 * num_ffts) * twiddles[tw_idx + 2];
-
-        butterfly4.perform_fft_contiguous(RawSlice::new(&scratch), RawSliceMut::new(&mut scratch));
-
-        *data.get_unchecked_mut(idx) = scratch[0];
-        *data.get_unchecked_mut(idx + 1 * num_ffts) = scratch[1];
-        *data.get_unchecked_mut(idx + 2 * num_ffts) = scratch[2];
-        *data.get_unchecked_mut(idx + 3 * num_ffts) = scratch[3];
+        single_butterfly_4(&butterfly4, data, twiddles, idx, tw_idx, num_ffts);
 
         tw_idx += 3;
         idx += 1;
     }
 }
 
+// Like butterfly_4, but only computes the butterflies listed in `butterflies`
+unsafe fn butterfly_4_pruned<T: FftNum>(
+    data: impl LoadComplex<T> + StoreComplex<T>,
+    twiddles: &[Complex<T>],
+    num_ffts: usize,
+    butterflies: &[usize],
+    direction: FftDirection,
+) {
+    let butterfly4 = Butterfly4::new(direction);
+
+    for &idx in butterflies {
+        single_butterfly_4(&butterfly4, data, twiddles, idx, idx * 3, num_ffts);
+    }
+}
+
+#[inline(always)]
+unsafe fn single_butterfly_4<T: FftNum>(
+    butterfly4: &Butterfly4<T>,
+    data: impl LoadComplex<T> + StoreComplex<T>,
+    twiddles: &[Complex<T>],
+    idx: usize,
+    tw_idx: usize,
+    num_ffts: usize,
+) {
+    let mut scratch = [Zero::zero(); 4];
+    scratch[0] = data.load(idx);
+    scratch[1] = data.load(idx + 1 * num_ffts) * twiddles[tw_idx];
+    scratch[2] = data.load(idx + 2 * num_ffts) * twiddles[tw_idx + 1];
+    scratch[3] = data.load(idx + 3 * num_ffts) * twiddles[tw_idx + 2];
+
+    butterfly4.perform_fft_contiguous(RawSlice::new(&scratch), RawSliceMut::new(&mut scratch));
+
+    data.store(scratch[0], idx);
+    data.store(scratch[1], idx + 1 * num_ffts);
+    data.store(scratch[2], idx + 2 * num_ffts);
+    data.store(scratch[3], idx + 3 * num_ffts);
+}
+
 #[cfg(test)]
 mod unit_tests {
     use super::*;
-    use crate::test_utils::check_fft_algorithm;
+    use crate::test_utils::{
+        check_fft_algorithm, check_input_pruned_fft_algorithm, check_output_pruned_fft_algorithm,
+        split_mismatched_lengths, split_partial_chunk, split_small_scratch,
+    };
 
     #[test]
     fn test_radix4() {
@@ -200,6 +667,65 @@ mod unit_tests {
         }
     }
 
+    #[test]
+    fn test_output_pruned_radix4() {
+        for pow in 0..9 {
+            let len = 1 << pow;
+            for &direction in &[FftDirection::Forward, FftDirection::Inverse] {
+                // A contiguous band, a single bin, and a scattered mask
+                let masks: Vec<Vec<bool>> = vec![
+                    (0..len)
+                        .map(|i| i >= len / 3 && i < len / 3 + len / 8 + 1)
+                        .collect(),
+                    (0..len).map(|i| i == len - 1).collect(),
+                    (0..len).map(|i| i % 7 == 3).collect(),
+                ];
+                for mask in &masks {
+                    let fft = OutputPrunedRadix4::new(len, direction, mask);
+                    check_output_pruned_fft_algorithm::<f32>(&fft, mask, direction);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_input_pruned_radix4() {
+        for pow in 0..9 {
+            let len = 1 << pow;
+            for &nonzero_len in &[0, 1, len / 8 + 1, len / 3, len] {
+                let nonzero_len = std::cmp::min(nonzero_len, len);
+                for &direction in &[FftDirection::Forward, FftDirection::Inverse] {
+                    let fft = InputPrunedRadix4::new(len, direction, nonzero_len);
+                    check_input_pruned_fft_algorithm::<f32>(
+                        &fft,
+                        len,
+                        nonzero_len,
+                        direction,
+                        true,
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Real and imaginary buffers must have the same length")]
+    fn test_radix4_split_mismatched_lengths() {
+        split_mismatched_lengths::<f32>(&Radix4::new(64, FftDirection::Forward));
+    }
+
+    #[test]
+    #[should_panic(expected = "Input FFT buffer must be a multiple of FFT length")]
+    fn test_radix4_split_partial_chunk() {
+        split_partial_chunk::<f32>(&Radix4::new(64, FftDirection::Forward));
+    }
+
+    #[test]
+    #[should_panic(expected = "Not enough scratch space was provided")]
+    fn test_radix4_split_small_scratch() {
+        split_small_scratch::<f32>(&Radix4::new(64, FftDirection::Forward));
+    }
+
     fn test_radix4_with_length(len: usize, direction: FftDirection) {
         let fft = Radix4::new(len, direction);
 
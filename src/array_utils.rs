@@ -1,3 +1,5 @@
+use num_complex::Complex;
+
 /// Given an array of size width * height, representing a flattened 2D array,
 /// transpose the rows and columns of that 2D array into the output
 /// benchmarking shows that loop tiling isn't effective for small arrays (in the range of 50x50 or smaller)
@@ -25,6 +27,54 @@ pub unsafe fn workaround_transmute_mut<T, U>(slice: &mut [T]) -> &mut [U] {
     std::slice::from_raw_parts_mut(ptr, len)
 }
 
+#[cold]
+#[inline(never)]
+fn interleaved_error(len: usize, is_aligned: bool) {
+    assert!(
+        len & 1 == 0,
+        "Interleaved buffers must have an even length, so that each real part is followed by an imaginary part. Got len = {}",
+        len
+    );
+    assert!(
+        is_aligned,
+        "Interleaved buffer is not aligned to the alignment of Complex<T>"
+    );
+}
+
+/// Reinterprets a slice of interleaved scalars, `[re0, im0, re1, im1, ...]`, as a slice of complex numbers with half the length.
+///
+/// This is a safe wrapper around the reinterpretation that `Complex<T>`'s `#[repr(C)]` layout allows.
+///
+/// # Panics
+///
+/// This function panics if `buffer.len()` is odd, or if `buffer` isn't aligned to the alignment of `Complex<T>`.
+pub fn interleaved_as_complex<T>(buffer: &[T]) -> &[Complex<T>] {
+    // Alignments are always powers of two, so a misaligned pointer has some of its low bits set
+    let is_aligned = buffer.as_ptr() as usize & (std::mem::align_of::<Complex<T>>() - 1) == 0;
+    if buffer.len() & 1 > 0 || !is_aligned {
+        interleaved_error(buffer.len(), is_aligned);
+    }
+    unsafe { std::slice::from_raw_parts(buffer.as_ptr() as *const Complex<T>, buffer.len() / 2) }
+}
+
+/// Reinterprets a mutable slice of interleaved scalars, `[re0, im0, re1, im1, ...]`, as a mutable slice of complex numbers with half the length.
+///
+/// This is a safe wrapper around the reinterpretation that `Complex<T>`'s `#[repr(C)]` layout allows.
+///
+/// # Panics
+///
+/// This function panics if `buffer.len()` is odd, or if `buffer` isn't aligned to the alignment of `Complex<T>`.
+pub fn interleaved_as_complex_mut<T>(buffer: &mut [T]) -> &mut [Complex<T>] {
+    // Alignments are always powers of two, so a misaligned pointer has some of its low bits set
+    let is_aligned = buffer.as_ptr() as usize & (std::mem::align_of::<Complex<T>>() - 1) == 0;
+    if buffer.len() & 1 > 0 || !is_aligned {
+        interleaved_error(buffer.len(), is_aligned);
+    }
+    unsafe {
+        std::slice::from_raw_parts_mut(buffer.as_mut_ptr() as *mut Complex<T>, buffer.len() / 2)
+    }
+}
+
 #[derive(Copy, Clone)]
 pub struct RawSlice<T> {
     ptr: *const T,
@@ -104,6 +154,146 @@ impl<T> RawSliceMut<T> {
     }
 }
 
+/// Loads complex numbers from a buffer, regardless of whether the buffer is interleaved or split into real and imaginary parts.
+/// Lets FFT kernels be generic over the data layout.
+pub trait LoadComplex<T>: Copy {
+    unsafe fn load(&self, index: usize) -> Complex<T>;
+}
+
+/// Stores complex numbers to a buffer, regardless of whether the buffer is interleaved or split into real and imaginary parts.
+/// Lets FFT kernels be generic over the data layout.
+pub trait StoreComplex<T>: Copy {
+    unsafe fn store(&self, value: Complex<T>, index: usize);
+}
+
+impl<T: Copy> LoadComplex<T> for RawSlice<Complex<T>> {
+    #[inline(always)]
+    unsafe fn load(&self, index: usize) -> Complex<T> {
+        RawSlice::load(self, index)
+    }
+}
+impl<T: Copy> LoadComplex<T> for RawSliceMut<Complex<T>> {
+    #[inline(always)]
+    unsafe fn load(&self, index: usize) -> Complex<T> {
+        debug_assert!(index < self.slice_len);
+        *self.ptr.add(index)
+    }
+}
+impl<T: Copy> StoreComplex<T> for RawSliceMut<Complex<T>> {
+    #[inline(always)]
+    unsafe fn store(&self, value: Complex<T>, index: usize) {
+        RawSliceMut::store(self, value, index)
+    }
+}
+
+/// A RawSplitSlice is a pair of normal slices holding the real and imaginary parts of complex numbers, but aliasable. Its functionality is severely limited.
+#[derive(Copy, Clone)]
+pub struct RawSplitSlice<T> {
+    real: *const T,
+    imag: *const T,
+    slice_len: usize,
+}
+impl<T> RawSplitSlice<T> {
+    #[allow(unused)]
+    #[inline(always)]
+    pub fn new(real: &[T], imag: &[T]) -> Self {
+        debug_assert_eq!(real.len(), imag.len());
+        Self {
+            real: real.as_ptr(),
+            imag: imag.as_ptr(),
+            slice_len: real.len(),
+        }
+    }
+    #[allow(unused)]
+    #[inline(always)]
+    pub fn real_ptr(&self) -> *const T {
+        self.real
+    }
+    #[allow(unused)]
+    #[inline(always)]
+    pub fn imag_ptr(&self) -> *const T {
+        self.imag
+    }
+    #[allow(unused)]
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.slice_len
+    }
+}
+impl<T: Copy> LoadComplex<T> for RawSplitSlice<T> {
+    #[inline(always)]
+    unsafe fn load(&self, index: usize) -> Complex<T> {
+        debug_assert!(index < self.slice_len);
+        Complex::new(*self.real.add(index), *self.imag.add(index))
+    }
+}
+
+/// A RawSplitSliceMut is a pair of normal mutable slices holding the real and imaginary parts of complex numbers, but aliasable. Its functionality is severely limited.
+#[derive(Copy, Clone)]
+pub struct RawSplitSliceMut<T> {
+    real: *mut T,
+    imag: *mut T,
+    slice_len: usize,
+}
+impl<T> RawSplitSliceMut<T> {
+    #[inline(always)]
+    pub fn new(real: &mut [T], imag: &mut [T]) -> Self {
+        debug_assert_eq!(real.len(), imag.len());
+        Self {
+            real: real.as_mut_ptr(),
+            imag: imag.as_mut_ptr(),
+            slice_len: real.len(),
+        }
+    }
+    /// Returns a read-only view of the same data, for kernels that read and write the same buffer
+    #[inline(always)]
+    pub fn as_slice(&self) -> RawSplitSlice<T> {
+        RawSplitSlice {
+            real: self.real,
+            imag: self.imag,
+            slice_len: self.slice_len,
+        }
+    }
+    #[allow(unused)]
+    #[inline(always)]
+    pub fn real_ptr(&self) -> *mut T {
+        self.real
+    }
+    #[allow(unused)]
+    #[inline(always)]
+    pub fn imag_ptr(&self) -> *mut T {
+        self.imag
+    }
+    #[allow(unused)]
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.slice_len
+    }
+}
+impl<T: Copy> LoadComplex<T> for RawSplitSliceMut<T> {
+    #[inline(always)]
+    unsafe fn load(&self, index: usize) -> Complex<T> {
+        self.as_slice().load(index)
+    }
+}
+impl<T: Copy> StoreComplex<T> for RawSplitSliceMut<T> {
+    #[inline(always)]
+    unsafe fn store(&self, value: Complex<T>, index: usize) {
+        debug_assert!(index < self.slice_len);
+        *self.real.add(index) = value.re;
+        *self.imag.add(index) = value.im;
+    }
+}
+
+/// Reinterprets a scratch buffer of `len` complex numbers as two scalar buffers of length `len`, to hold real and imaginary parts.
+/// This is sound because `Complex<T>` is `#[repr(C)]`, so the scratch buffer is `2 * len` consecutive scalars.
+pub fn split_scratch<T>(scratch: &mut [Complex<T>]) -> (&mut [T], &mut [T]) {
+    let len = scratch.len();
+    let scalars =
+        unsafe { std::slice::from_raw_parts_mut(scratch.as_mut_ptr() as *mut T, len * 2) };
+    scalars.split_at_mut(len)
+}
+
 #[cfg(test)]
 mod unit_tests {
     use super::*;
@@ -138,6 +328,29 @@ mod unit_tests {
             }
         }
     }
+
+    #[test]
+    fn test_interleaved_as_complex() {
+        let mut buffer = vec![1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0];
+        assert_eq!(
+            interleaved_as_complex(&buffer),
+            &[
+                Complex::new(1.0, 2.0),
+                Complex::new(3.0, 4.0),
+                Complex::new(5.0, 6.0)
+            ]
+        );
+
+        interleaved_as_complex_mut(&mut buffer)[1] = Complex::new(7.0, 8.0);
+        assert_eq!(buffer, vec![1.0, 2.0, 7.0, 8.0, 5.0, 6.0]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_interleaved_as_complex_odd_len() {
+        let buffer = vec![1.0f64, 2.0, 3.0];
+        interleaved_as_complex(&buffer);
+    }
 }
 
 // Loop over exact chunks of the provided buffer. Very similar in semantics to ChunksExactMut, but generates smaller code and requires no modulo operations
@@ -5,14 +5,14 @@ use std::mem::MaybeUninit;
 use num_complex::Complex;
 
 use crate::array_utils;
-use crate::common::{fft_error_inplace, fft_error_outofplace};
+use crate::common::{fft_error_inplace, fft_error_outofplace, fft_error_split};
 use crate::{common::FftNum, twiddles};
 use crate::{Direction, Fft, FftDirection, Length};
 
 use super::avx32_utils;
 use super::avx_vector;
 use super::avx_vector::{AvxArray, AvxArrayMut, AvxVector, AvxVector128, AvxVector256, Rotation90};
-use crate::array_utils::{RawSlice, RawSliceMut};
+use crate::array_utils::{RawSlice, RawSliceMut, RawSplitSliceMut};
 
 // Safety: This macro will call `self::perform_fft_f32()` which probably has a #[target_feature(enable = "...")] annotation on it.
 // Calling functions with that annotation is unsafe, because it doesn't actually check if the CPU has the required features.
@@ -91,6 +91,40 @@ macro_rules! boilerplate_fft_simd_butterfly {
                     fft_error_inplace(self.len(), buffer.len(), 0, 0);
                 }
             }
+            fn process_split_with_scratch(
+                &self,
+                real: &mut [T],
+                imag: &mut [T],
+                _scratch: &mut [Complex<T>],
+            ) {
+                if real.len() != imag.len() || real.len() < self.len() {
+                    // We want to trigger a panic, but we want to avoid doing it in this function to reduce code size, so call a function marked cold and inline(never) that will do it for us
+                    fft_error_split(self.len(), real.len(), imag.len(), 0, 0);
+                    return; // Unreachable, because fft_error_split asserts, but it helps codegen to put it here
+                }
+
+                let result = array_utils::iter_chunks_zipped(
+                    real,
+                    imag,
+                    self.len(),
+                    |real_chunk, imag_chunk| {
+                        unsafe {
+                            // Specialization workaround: See the comments in FftPlannerAvx::new() for why we have to transmute these slices
+                            let chunk = RawSplitSliceMut::<f32>::new(
+                                array_utils::workaround_transmute_mut(real_chunk),
+                                array_utils::workaround_transmute_mut(imag_chunk),
+                            );
+                            self.perform_fft_f32(chunk.as_slice(), chunk);
+                        }
+                    },
+                );
+
+                if result.is_err() {
+                    // We want to trigger a panic, because the buffer sizes weren't cleanly divisible by the FFT size,
+                    // but we want to avoid doing it in this function to reduce code size, so call a function marked cold and inline(never) that will do it for us
+                    fft_error_split(self.len(), real.len(), imag.len(), 0, 0);
+                }
+            }
             #[inline(always)]
             fn get_inplace_scratch_len(&self) -> usize {
                 0
@@ -99,6 +133,10 @@ macro_rules! boilerplate_fft_simd_butterfly {
             fn get_outofplace_scratch_len(&self) -> usize {
                 0
             }
+            #[inline(always)]
+            fn get_split_scratch_len(&self) -> usize {
+                0
+            }
         }
         impl<T> Length for $struct_name<T> {
             #[inline(always)]
@@ -304,12 +342,9 @@ impl Butterfly5Avx<f32> {
 }
 impl<T> Butterfly5Avx<T> {
     #[target_feature(enable = "avx", enable = "fma")]
-    unsafe fn perform_fft_f32(
-        &self,
-        input: RawSlice<Complex<f32>>,
-        mut output: RawSliceMut<Complex<f32>>,
-    ) {
-        let input0 = _mm_castpd_ps(_mm_load1_pd(input.as_ptr() as *const f64)); // load the first element of the input, and duplicate it into both complex number slots of input0
+    unsafe fn perform_fft_f32(&self, input: impl AvxArray<f32>, mut output: impl AvxArrayMut<f32>) {
+        let input0 = input.load_partial1_complex(0);
+        let input0 = _mm_movelh_ps(input0, input0); // duplicate the first element of the input into both complex number slots of input0
         let input12 = input.load_partial2_complex(1);
         let input34 = input.load_partial2_complex(3);
 
@@ -376,15 +411,13 @@ impl Butterfly7Avx<f32> {
 }
 impl<T> Butterfly7Avx<T> {
     #[target_feature(enable = "avx", enable = "fma")]
-    unsafe fn perform_fft_f32(
-        &self,
-        input: RawSlice<Complex<f32>>,
-        mut output: RawSliceMut<Complex<f32>>,
-    ) {
-        let input0 = _mm_castpd_ps(_mm_load1_pd(input.as_ptr() as *const f64)); // load the first element of the input, and duplicate it into both complex number slots of input0
-                                                                                // we want to load 3 elements into 123 and 3 elements into 456, but we can only load 4, so we're going to do slightly overlapping reads here
-                                                                                // we have to reverse 456 immediately after loading, and that'll be easiest if we load the 456 into the latter 3 slots of the register, rather than the front 3 slots
-                                                                                // as a bonus, that also means we don't need masked reads or anything
+    unsafe fn perform_fft_f32(&self, input: impl AvxArray<f32>, mut output: impl AvxArrayMut<f32>) {
+        let input0 = input.load_partial1_complex(0);
+        let input0 = _mm_movelh_ps(input0, input0); // duplicate the first element of the input into both complex number slots of input0
+
+        // we want to load 3 elements into 123 and 3 elements into 456, but we can only load 4, so we're going to do slightly overlapping reads here
+        // we have to reverse 456 immediately after loading, and that'll be easiest if we load the 456 into the latter 3 slots of the register, rather than the front 3 slots
+        // as a bonus, that also means we don't need masked reads or anything
         let input123 = input.load_complex(1);
         let input456 = input.load_complex(3);
 
@@ -502,12 +535,9 @@ impl Butterfly11Avx<f32> {
 }
 impl<T> Butterfly11Avx<T> {
     #[target_feature(enable = "avx", enable = "fma")]
-    unsafe fn perform_fft_f32(
-        &self,
-        input: RawSlice<Complex<f32>>,
-        mut output: RawSliceMut<Complex<f32>>,
-    ) {
-        let input0 = _mm_castpd_ps(_mm_load1_pd(input.as_ptr() as *const f64)); // load the first element of the input, and duplicate it into both complex number slots of input0
+    unsafe fn perform_fft_f32(&self, input: impl AvxArray<f32>, mut output: impl AvxArrayMut<f32>) {
+        let input0 = input.load_partial1_complex(0);
+        let input0 = _mm_movelh_ps(input0, input0); // duplicate the first element of the input into both complex number slots of input0
         let input1234 = input.load_complex(1);
         let input56 = input.load_partial2_complex(5);
         let input78910 = input.load_complex(7);
@@ -620,11 +650,7 @@ impl Butterfly8Avx<f32> {
 }
 impl<T> Butterfly8Avx<T> {
     #[target_feature(enable = "avx", enable = "fma")]
-    unsafe fn perform_fft_f32(
-        &self,
-        input: RawSlice<Complex<f32>>,
-        mut output: RawSliceMut<Complex<f32>>,
-    ) {
+    unsafe fn perform_fft_f32(&self, input: impl AvxArray<f32>, mut output: impl AvxArrayMut<f32>) {
         let row0 = input.load_complex(0);
         let row1 = input.load_complex(4);
 
@@ -689,15 +715,12 @@ impl Butterfly9Avx<f32> {
 }
 impl<T> Butterfly9Avx<T> {
     #[target_feature(enable = "avx", enable = "fma")]
-    unsafe fn perform_fft_f32(
-        &self,
-        input: RawSlice<Complex<f32>>,
-        mut output: RawSliceMut<Complex<f32>>,
-    ) {
+    unsafe fn perform_fft_f32(&self, input: impl AvxArray<f32>, mut output: impl AvxArrayMut<f32>) {
         // we're going to load these elements in a peculiar way. instead of loading a row into the first 3 element of each register and leaving the last element empty
         // we're leaving the first element empty and putting the data in the last 3 elements. this will let us do 3 total complex multiplies instead of 4.
 
-        let input0_lo = _mm_castpd_ps(_mm_load1_pd(input.as_ptr() as *const f64));
+        let input0_lo = input.load_partial1_complex(0);
+        let input0_lo = _mm_movelh_ps(input0_lo, input0_lo);
         let input0_hi = input.load_partial2_complex(1);
         let input0 = AvxVector256::merge(input0_lo, input0_hi);
         let input1 = input.load_complex(2);
@@ -779,16 +802,13 @@ impl Butterfly12Avx<f32> {
 }
 impl<T> Butterfly12Avx<T> {
     #[target_feature(enable = "avx", enable = "fma")]
-    unsafe fn perform_fft_f32(
-        &self,
-        input: RawSlice<Complex<f32>>,
-        mut output: RawSliceMut<Complex<f32>>,
-    ) {
+    unsafe fn perform_fft_f32(&self, input: impl AvxArray<f32>, mut output: impl AvxArrayMut<f32>) {
         // we're going to load these elements in a peculiar way. instead of loading a row into the first 3 element of each register and leaving the last element empty
         // we're leaving the first element empty and putting the data in the last 3 elements. this will save us a complex multiply.
 
         // for everything but the first element, we can do overlapping reads. for the first element, an "overlapping read" would have us reading from index -1, so instead we have to shuffle some data around
-        let input0_lo = _mm_castpd_ps(_mm_load1_pd(input.as_ptr() as *const f64));
+        let input0_lo = input.load_partial1_complex(0);
+        let input0_lo = _mm_movelh_ps(input0_lo, input0_lo);
         let input0_hi = input.load_partial2_complex(1);
         let input_rows = [
             AvxVector256::merge(input0_lo, input0_hi),
@@ -849,11 +869,7 @@ impl Butterfly16Avx<f32> {
 }
 impl<T> Butterfly16Avx<T> {
     #[target_feature(enable = "avx", enable = "fma")]
-    unsafe fn perform_fft_f32(
-        &self,
-        input: RawSlice<Complex<f32>>,
-        mut output: RawSliceMut<Complex<f32>>,
-    ) {
+    unsafe fn perform_fft_f32(&self, input: impl AvxArray<f32>, mut output: impl AvxArrayMut<f32>) {
         // Manually unrolling this loop because writing a "for r in 0..4" loop results in slow codegen that makes the whole thing take 1.5x longer :(
         let rows = [
             input.load_complex(0),
@@ -906,11 +922,7 @@ impl Butterfly24Avx<f32> {
 }
 impl<T> Butterfly24Avx<T> {
     #[target_feature(enable = "avx", enable = "fma")]
-    unsafe fn perform_fft_f32(
-        &self,
-        input: RawSlice<Complex<f32>>,
-        mut output: RawSliceMut<Complex<f32>>,
-    ) {
+    unsafe fn perform_fft_f32(&self, input: impl AvxArray<f32>, mut output: impl AvxArrayMut<f32>) {
         // Manually unrolling this loop because writing a "for r in 0..6" loop results in slow codegen that makes the whole thing take 1.5x longer :(
         let rows = [
             input.load_complex(0),
@@ -970,11 +982,7 @@ impl Butterfly27Avx<f32> {
 }
 impl<T> Butterfly27Avx<T> {
     #[target_feature(enable = "avx", enable = "fma")]
-    unsafe fn perform_fft_f32(
-        &self,
-        input: RawSlice<Complex<f32>>,
-        mut output: RawSliceMut<Complex<f32>>,
-    ) {
+    unsafe fn perform_fft_f32(&self, input: impl AvxArray<f32>, mut output: impl AvxArrayMut<f32>) {
         // we're going to load our data in a peculiar way. we're going to load the first column on its own as a column of __m128.
         // it's faster to just load the first 2 columns into these m128s than trying to worry about masks, etc, so the second column will piggyback along and we just won't use it
         let mut rows0 = [AvxVector::zero(); 3];
@@ -1040,11 +1048,7 @@ impl Butterfly32Avx<f32> {
 }
 impl<T> Butterfly32Avx<T> {
     #[target_feature(enable = "avx", enable = "fma")]
-    unsafe fn perform_fft_f32(
-        &self,
-        input: RawSlice<Complex<f32>>,
-        mut output: RawSliceMut<Complex<f32>>,
-    ) {
+    unsafe fn perform_fft_f32(&self, input: impl AvxArray<f32>, mut output: impl AvxArrayMut<f32>) {
         let mut rows0 = [AvxVector::zero(); 4];
         let mut rows1 = [AvxVector::zero(); 4];
         for r in 0..4 {
@@ -1108,11 +1112,7 @@ impl Butterfly36Avx<f32> {
 }
 impl<T> Butterfly36Avx<T> {
     #[target_feature(enable = "avx", enable = "fma")]
-    unsafe fn perform_fft_f32(
-        &self,
-        input: RawSlice<Complex<f32>>,
-        mut output: RawSliceMut<Complex<f32>>,
-    ) {
+    unsafe fn perform_fft_f32(&self, input: impl AvxArray<f32>, mut output: impl AvxArrayMut<f32>) {
         // we're going to load our data in a peculiar way. we're going to load the first column on its own as a column of __m128.
         // it's faster to just load the first 2 columns into these m128s than trying to worry about masks, etc, so the second column will piggyback along and we just won't use it
         let mut rows0 = [AvxVector::zero(); 4];
@@ -1175,11 +1175,7 @@ impl Butterfly48Avx<f32> {
 }
 impl<T> Butterfly48Avx<T> {
     #[target_feature(enable = "avx", enable = "fma")]
-    unsafe fn perform_fft_f32(
-        &self,
-        input: RawSlice<Complex<f32>>,
-        mut output: RawSliceMut<Complex<f32>>,
-    ) {
+    unsafe fn perform_fft_f32(&self, input: impl AvxArray<f32>, mut output: impl AvxArrayMut<f32>) {
         let mut rows0 = [AvxVector::zero(); 4];
         let mut rows1 = [AvxVector::zero(); 4];
         let mut rows2 = [AvxVector::zero(); 4];
@@ -1262,11 +1258,7 @@ impl Butterfly54Avx<f32> {
 }
 impl<T> Butterfly54Avx<T> {
     #[target_feature(enable = "avx", enable = "fma")]
-    unsafe fn perform_fft_f32(
-        &self,
-        input: RawSlice<Complex<f32>>,
-        mut output: RawSliceMut<Complex<f32>>,
-    ) {
+    unsafe fn perform_fft_f32(&self, input: impl AvxArray<f32>, mut output: impl AvxArrayMut<f32>) {
         // we're going to load our data in a peculiar way. we're going to load the first column on its own as a column of __m128.
         // it's faster to just load the first 2 columns into these m128s than trying to worry about masks, etc, so the second column will piggyback along and we just won't use it
         //
@@ -1347,11 +1339,7 @@ impl Butterfly64Avx<f32> {
 }
 impl<T> Butterfly64Avx<T> {
     #[target_feature(enable = "avx", enable = "fma")]
-    unsafe fn perform_fft_f32(
-        &self,
-        input: RawSlice<Complex<f32>>,
-        mut output: RawSliceMut<Complex<f32>>,
-    ) {
+    unsafe fn perform_fft_f32(&self, input: impl AvxArray<f32>, mut output: impl AvxArrayMut<f32>) {
         // We're going to treat our input as a 8x8 2d array. First, do 8 butterfly 8's down the columns of that array.
         // We can't fit the whole problem into AVX registers at once, so we'll have to spill some things.
         // By computing a sizeable chunk and not referencing any of it for a while, we're making it easy for the compiler to decide what to spill
@@ -1413,11 +1401,7 @@ impl Butterfly72Avx<f32> {
 }
 impl<T> Butterfly72Avx<T> {
     #[target_feature(enable = "avx", enable = "fma")]
-    unsafe fn perform_fft_f32(
-        &self,
-        input: RawSlice<Complex<f32>>,
-        mut output: RawSliceMut<Complex<f32>>,
-    ) {
+    unsafe fn perform_fft_f32(&self, input: impl AvxArray<f32>, mut output: impl AvxArrayMut<f32>) {
         // We're going to treat our input as a 12x6 2d array. First, do butterfly 6's down the columns of that array.
         // We can't fit the whole problem into AVX registers at once, so we'll have to spill some things.
         // By computing a sizeable chunk and not referencing any of it for a while, we're making it easy for the compiler to decide what to spill
@@ -1536,11 +1520,7 @@ impl<T> Butterfly128Avx<T> {
     }
 
     #[target_feature(enable = "avx", enable = "fma")]
-    unsafe fn row_butterflies(
-        &self,
-        input: RawSlice<Complex<f32>>,
-        mut output: RawSliceMut<Complex<f32>>,
-    ) {
+    unsafe fn row_butterflies(&self, input: impl AvxArray<f32>, mut output: impl AvxArrayMut<f32>) {
         // Second phase: Butterfly 16's down the columns of our transposed array.
         // Thankfully, during the first phase, we set everything up so that all we have to do here is compute the size-16 FFT columns and write them back out where we got them
         // We're also using a customized butterfly16 function that is smarter about when it loads/stores data, to reduce register spilling
@@ -1617,11 +1597,7 @@ impl<T> Butterfly256Avx<T> {
     }
 
     #[target_feature(enable = "avx", enable = "fma")]
-    unsafe fn row_butterflies(
-        &self,
-        input: RawSlice<Complex<f32>>,
-        mut output: RawSliceMut<Complex<f32>>,
-    ) {
+    unsafe fn row_butterflies(&self, input: impl AvxArray<f32>, mut output: impl AvxArrayMut<f32>) {
         // Second phase: Butterfly 32's down the columns of our transposed array.
         // Thankfully, during the first phase, we set everything up so that all we have to do here is compute the size-32 FFT columns and write them back out where we got them
         // We're also using a customized butterfly32 function that is smarter about when it loads/stores data, to reduce register spilling
@@ -1736,11 +1712,7 @@ impl<T> Butterfly512Avx<T> {
     }
 
     #[target_feature(enable = "avx", enable = "fma")]
-    unsafe fn row_butterflies(
-        &self,
-        input: RawSlice<Complex<f32>>,
-        mut output: RawSliceMut<Complex<f32>>,
-    ) {
+    unsafe fn row_butterflies(&self, input: impl AvxArray<f32>, mut output: impl AvxArrayMut<f32>) {
         // Second phase: Butterfly 32's down the columns of our transposed array.
         // Thankfully, during the first phase, we set everything up so that all we have to do here is compute the size-32 FFT columns and write them back out where we got them
         // We're also using a customized butterfly32 function that is smarter about when it loads/stores data, to reduce register spilling
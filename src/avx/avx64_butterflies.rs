@@ -5,14 +5,14 @@ use std::mem::MaybeUninit;
 use num_complex::Complex;
 
 use crate::array_utils;
-use crate::common::{fft_error_inplace, fft_error_outofplace};
+use crate::common::{fft_error_inplace, fft_error_outofplace, fft_error_split};
 use crate::{common::FftNum, twiddles};
 use crate::{Direction, Fft, FftDirection, Length};
 
 use super::avx64_utils;
 use super::avx_vector;
 use super::avx_vector::{AvxArray, AvxArrayMut, AvxVector, AvxVector128, AvxVector256, Rotation90};
-use crate::array_utils::{RawSlice, RawSliceMut};
+use crate::array_utils::{RawSlice, RawSliceMut, RawSplitSliceMut};
 
 // Safety: This macro will call `self::perform_fft_f32()` which probably has a #[target_feature(enable = "...")] annotation on it.
 // Calling functions with that annotation is unsafe, because it doesn't actually check if the CPU has the required features.
@@ -89,6 +89,40 @@ macro_rules! boilerplate_fft_simd_butterfly {
                     fft_error_inplace(self.len(), buffer.len(), 0, 0);
                 }
             }
+            fn process_split_with_scratch(
+                &self,
+                real: &mut [T],
+                imag: &mut [T],
+                _scratch: &mut [Complex<T>],
+            ) {
+                if real.len() != imag.len() || real.len() < self.len() {
+                    // We want to trigger a panic, but we want to avoid doing it in this function to reduce code size, so call a function marked cold and inline(never) that will do it for us
+                    fft_error_split(self.len(), real.len(), imag.len(), 0, 0);
+                    return; // Unreachable, because fft_error_split asserts, but it helps codegen to put it here
+                }
+
+                let result = array_utils::iter_chunks_zipped(
+                    real,
+                    imag,
+                    self.len(),
+                    |real_chunk, imag_chunk| {
+                        unsafe {
+                            // Specialization workaround: See the comments in FftPlannerAvx::new() for why we have to transmute these slices
+                            let chunk = RawSplitSliceMut::<f64>::new(
+                                array_utils::workaround_transmute_mut(real_chunk),
+                                array_utils::workaround_transmute_mut(imag_chunk),
+                            );
+                            self.perform_fft_f64(chunk.as_slice(), chunk);
+                        }
+                    },
+                );
+
+                if result.is_err() {
+                    // We want to trigger a panic, because the buffer sizes weren't cleanly divisible by the FFT size,
+                    // but we want to avoid doing it in this function to reduce code size, so call a function marked cold and inline(never) that will do it for us
+                    fft_error_split(self.len(), real.len(), imag.len(), 0, 0);
+                }
+            }
             #[inline(always)]
             fn get_inplace_scratch_len(&self) -> usize {
                 0
@@ -97,6 +131,10 @@ macro_rules! boilerplate_fft_simd_butterfly {
             fn get_outofplace_scratch_len(&self) -> usize {
                 0
             }
+            #[inline(always)]
+            fn get_split_scratch_len(&self) -> usize {
+                0
+            }
         }
         impl<T> Length for $struct_name<T> {
             #[inline(always)]
@@ -304,13 +342,9 @@ impl Butterfly5Avx64<f64> {
 }
 impl<T> Butterfly5Avx64<T> {
     #[target_feature(enable = "avx", enable = "fma")]
-    unsafe fn perform_fft_f64(
-        &self,
-        input: RawSlice<Complex<f64>>,
-        mut output: RawSliceMut<Complex<f64>>,
-    ) {
-        let input0 =
-            _mm256_loadu2_m128d(input.as_ptr() as *const f64, input.as_ptr() as *const f64);
+    unsafe fn perform_fft_f64(&self, input: impl AvxArray<f64>, mut output: impl AvxArrayMut<f64>) {
+        let input0 = input.load_partial1_complex(0);
+        let input0 = _mm256_set_m128d(input0, input0); // duplicate the first element of the input into both complex number slots of input0
         let input12 = input.load_complex(1);
         let input34 = input.load_complex(3);
 
@@ -377,13 +411,9 @@ impl Butterfly7Avx64<f64> {
 }
 impl<T> Butterfly7Avx64<T> {
     #[target_feature(enable = "avx", enable = "fma")]
-    unsafe fn perform_fft_f64(
-        &self,
-        input: RawSlice<Complex<f64>>,
-        mut output: RawSliceMut<Complex<f64>>,
-    ) {
-        let input0 =
-            _mm256_loadu2_m128d(input.as_ptr() as *const f64, input.as_ptr() as *const f64);
+    unsafe fn perform_fft_f64(&self, input: impl AvxArray<f64>, mut output: impl AvxArrayMut<f64>) {
+        let input0 = input.load_partial1_complex(0);
+        let input0 = _mm256_set_m128d(input0, input0); // duplicate the first element of the input into both complex number slots of input0
         let input12 = input.load_complex(1);
         let input3 = input.load_partial1_complex(3);
         let input4 = input.load_partial1_complex(4);
@@ -482,11 +512,7 @@ impl Butterfly11Avx64<f64> {
 }
 impl<T> Butterfly11Avx64<T> {
     #[target_feature(enable = "avx", enable = "fma")]
-    unsafe fn perform_fft_f64(
-        &self,
-        input: RawSlice<Complex<f64>>,
-        mut output: RawSliceMut<Complex<f64>>,
-    ) {
+    unsafe fn perform_fft_f64(&self, input: impl AvxArray<f64>, mut output: impl AvxArrayMut<f64>) {
         let input0 = input.load_partial1_complex(0);
         let input12 = input.load_complex(1);
         let input34 = input.load_complex(3);
@@ -597,11 +623,7 @@ impl Butterfly8Avx64<f64> {
 }
 impl<T> Butterfly8Avx64<T> {
     #[target_feature(enable = "avx", enable = "fma")]
-    unsafe fn perform_fft_f64(
-        &self,
-        input: RawSlice<Complex<f64>>,
-        mut output: RawSliceMut<Complex<f64>>,
-    ) {
+    unsafe fn perform_fft_f64(&self, input: impl AvxArray<f64>, mut output: impl AvxArrayMut<f64>) {
         let row0 = input.load_complex(0);
         let row1 = input.load_complex(2);
         let row2 = input.load_complex(4);
@@ -648,11 +670,7 @@ impl Butterfly9Avx64<f64> {
 }
 impl<T> Butterfly9Avx64<T> {
     #[target_feature(enable = "avx", enable = "fma")]
-    unsafe fn perform_fft_f64(
-        &self,
-        input: RawSlice<Complex<f64>>,
-        mut output: RawSliceMut<Complex<f64>>,
-    ) {
+    unsafe fn perform_fft_f64(&self, input: impl AvxArray<f64>, mut output: impl AvxArrayMut<f64>) {
         // we're going to load our input as a 3x3 array. We have to load 3 columns, which is a little awkward
         // We can reduce the number of multiplies we do if we load the first column as half-width and the second column as full.
         let mut rows0 = [AvxVector::zero(); 3];
@@ -708,11 +726,7 @@ impl Butterfly12Avx64<f64> {
 }
 impl<T> Butterfly12Avx64<T> {
     #[target_feature(enable = "avx", enable = "fma")]
-    unsafe fn perform_fft_f64(
-        &self,
-        input: RawSlice<Complex<f64>>,
-        mut output: RawSliceMut<Complex<f64>>,
-    ) {
+    unsafe fn perform_fft_f64(&self, input: impl AvxArray<f64>, mut output: impl AvxArrayMut<f64>) {
         // we're going to load our input as a 3x4 array. We have to load 3 columns, which is a little awkward
         // We can reduce the number of multiplies we do if we load the first column as half-width and the second column as full.
         let mut rows0 = [AvxVector::zero(); 4];
@@ -766,11 +780,7 @@ impl Butterfly16Avx64<f64> {
 }
 impl<T> Butterfly16Avx64<T> {
     #[target_feature(enable = "avx", enable = "fma")]
-    unsafe fn perform_fft_f64(
-        &self,
-        input: RawSlice<Complex<f64>>,
-        mut output: RawSliceMut<Complex<f64>>,
-    ) {
+    unsafe fn perform_fft_f64(&self, input: impl AvxArray<f64>, mut output: impl AvxArrayMut<f64>) {
         let mut rows0 = [AvxVector::zero(); 4];
         let mut rows1 = [AvxVector::zero(); 4];
         for r in 0..4 {
@@ -822,11 +832,7 @@ impl Butterfly18Avx64<f64> {
 }
 impl<T> Butterfly18Avx64<T> {
     #[target_feature(enable = "avx", enable = "fma")]
-    unsafe fn perform_fft_f64(
-        &self,
-        input: RawSlice<Complex<f64>>,
-        mut output: RawSliceMut<Complex<f64>>,
-    ) {
+    unsafe fn perform_fft_f64(&self, input: impl AvxArray<f64>, mut output: impl AvxArrayMut<f64>) {
         // we're going to load our input as a 3x6 array. We have to load 3 columns, which is a little awkward
         // We can reduce the number of multiplies we do if we load the first column as half-width and the second column as full.
         let mut rows0 = [AvxVector::zero(); 6];
@@ -884,11 +890,7 @@ impl Butterfly24Avx64<f64> {
 }
 impl<T> Butterfly24Avx64<T> {
     #[target_feature(enable = "avx", enable = "fma")]
-    unsafe fn perform_fft_f64(
-        &self,
-        input: RawSlice<Complex<f64>>,
-        mut output: RawSliceMut<Complex<f64>>,
-    ) {
+    unsafe fn perform_fft_f64(&self, input: impl AvxArray<f64>, mut output: impl AvxArrayMut<f64>) {
         let mut rows0 = [AvxVector::zero(); 4];
         let mut rows1 = [AvxVector::zero(); 4];
         let mut rows2 = [AvxVector::zero(); 4];
@@ -959,11 +961,7 @@ impl Butterfly27Avx64<f64> {
 }
 impl<T> Butterfly27Avx64<T> {
     #[target_feature(enable = "avx", enable = "fma")]
-    unsafe fn perform_fft_f64(
-        &self,
-        input: RawSlice<Complex<f64>>,
-        mut output: RawSliceMut<Complex<f64>>,
-    ) {
+    unsafe fn perform_fft_f64(&self, input: impl AvxArray<f64>, mut output: impl AvxArrayMut<f64>) {
         // we're going to load our input as a 9x3 array. We have to load 9 columns, which is a little awkward
         // We can reduce the number of multiplies we do if we load the first column as half-width and the remaining 4 sets of vectors as full.
         // We can't fit the whole problem into AVX registers at once, so we'll have to spill some things.
@@ -1051,11 +1049,7 @@ impl Butterfly32Avx64<f64> {
 }
 impl<T> Butterfly32Avx64<T> {
     #[target_feature(enable = "avx", enable = "fma")]
-    unsafe fn perform_fft_f64(
-        &self,
-        input: RawSlice<Complex<f64>>,
-        mut output: RawSliceMut<Complex<f64>>,
-    ) {
+    unsafe fn perform_fft_f64(&self, input: impl AvxArray<f64>, mut output: impl AvxArrayMut<f64>) {
         // We're going to treat our input as a 8x4 2d array. First, do 8 butterfly 4's down the columns of that array.
         // We can't fit the whole problem into AVX registers at once, so we'll have to spill some things.
         // By computing half of the problem and then not referencing any of it for a while, we're making it easy for the compiler to decide what to spill
@@ -1123,11 +1117,7 @@ impl Butterfly36Avx64<f64> {
 }
 impl<T> Butterfly36Avx64<T> {
     #[target_feature(enable = "avx", enable = "fma")]
-    unsafe fn perform_fft_f64(
-        &self,
-        input: RawSlice<Complex<f64>>,
-        mut output: RawSliceMut<Complex<f64>>,
-    ) {
+    unsafe fn perform_fft_f64(&self, input: impl AvxArray<f64>, mut output: impl AvxArrayMut<f64>) {
         // we're going to load our input as a 6x6 array
         // We can't fit the whole problem into AVX registers at once, so we'll have to spill some things.
         // By computing chunks of the problem and then not referencing any of it for a while, we're making it easy for the compiler to decide what to spill
@@ -1241,11 +1231,7 @@ impl<T> Butterfly64Avx64<T> {
     }
 
     #[target_feature(enable = "avx", enable = "fma")]
-    unsafe fn row_butterflies(
-        &self,
-        input: RawSlice<Complex<f64>>,
-        mut output: RawSliceMut<Complex<f64>>,
-    ) {
+    unsafe fn row_butterflies(&self, input: impl AvxArray<f64>, mut output: impl AvxArrayMut<f64>) {
         // Second phase: Butterfly 8's down the columns of our transposed array.
         // Thankfully, during the first phase, we set everything up so that all we have to do here is compute the size-8 FFT columns and write them back out where we got them
         for columnset in 0usize..4 {
@@ -1322,11 +1308,7 @@ impl<T> Butterfly128Avx64<T> {
     }
 
     #[target_feature(enable = "avx", enable = "fma")]
-    unsafe fn row_butterflies(
-        &self,
-        input: RawSlice<Complex<f64>>,
-        mut output: RawSliceMut<Complex<f64>>,
-    ) {
+    unsafe fn row_butterflies(&self, input: impl AvxArray<f64>, mut output: impl AvxArrayMut<f64>) {
         // Second phase: Butterfly 16's down the columns of our transposed array.
         // Thankfully, during the first phase, we set everything up so that all we have to do here is compute the size-16 FFT columns and write them back out where we got them
         // We're also using a customized butterfly16 function that is smarter about when it loads/stores data, to reduce register spilling
@@ -1406,11 +1388,7 @@ impl<T> Butterfly256Avx64<T> {
     }
 
     #[target_feature(enable = "avx", enable = "fma")]
-    unsafe fn row_butterflies(
-        &self,
-        input: RawSlice<Complex<f64>>,
-        mut output: RawSliceMut<Complex<f64>>,
-    ) {
+    unsafe fn row_butterflies(&self, input: impl AvxArray<f64>, mut output: impl AvxArrayMut<f64>) {
         // Second phase: Butterfly 32's down the columns of our transposed array.
         // Thankfully, during the first phase, we set everything up so that all we have to do here is compute the size-32 FFT columns and write them back out where we got them
         // We're also using a customized butterfly32 function that is smarter about when it loads/stores data, to reduce register spilling
@@ -1525,11 +1503,7 @@ impl<T> Butterfly512Avx64<T> {
     }
 
     #[target_feature(enable = "avx", enable = "fma")]
-    unsafe fn row_butterflies(
-        &self,
-        input: RawSlice<Complex<f64>>,
-        mut output: RawSliceMut<Complex<f64>>,
-    ) {
+    unsafe fn row_butterflies(&self, input: impl AvxArray<f64>, mut output: impl AvxArrayMut<f64>) {
         // Second phase: Butterfly 32's down the columns of our transposed array.
         // Thankfully, during the first phase, we set everything up so that all we have to do here is compute the size-32 FFT columns and write them back out where we got them
         // We're also using a customized butterfly32 function that is smarter about when it loads/stores data, to reduce register spilling
@@ -30,7 +30,9 @@ pub struct BluesteinsAvx<A: AvxNum, T> {
     common_data: CommonSimdData<T, A::VectorType>,
     _phantom: std::marker::PhantomData<T>,
 }
-boilerplate_avx_fft_commondata!(BluesteinsAvx);
+boilerplate_avx_fft_commondata!(BluesteinsAvx, |this: &BluesteinsAvx<_, _>| {
+    std::mem::size_of_val(&*this.inner_fft_multiplier)
+});
 
 impl<A: AvxNum, T: FftNum> BluesteinsAvx<A, T> {
     fn compute_bluesteins_twiddle(index: usize, len: usize, direction: FftDirection) -> Complex<A> {
@@ -3,18 +3,64 @@ use std::{any::TypeId, cmp::min};
 
 use primal_check::miller_rabin;
 
+use crate::algorithm::butterflies::*;
 use crate::algorithm::*;
 use crate::common::FftNum;
-use crate::math_utils::PartialFactors;
-use crate::Fft;
-use crate::{algorithm::butterflies::*, fft_cache::FftCache};
+use crate::cost_model::FftEstimate;
+use crate::fft_cache::{CachePolicy, FftCache};
+use crate::math_utils::{next_smooth_number, prev_smooth_number, PartialFactors};
+use crate::plan::Recipe;
+use crate::{Fft, PlannerAlgorithm, PlannerOptions};
 
 use super::*;
 
+// The AVX planner is fastest for sizes whose only odd prime factor is 3
+const FAST_ODD_PRIMES: [usize; 1] = [3];
+
 fn wrap_fft<T: FftNum>(butterfly: impl Fft<T> + 'static) -> Arc<dyn Fft<T>> {
     Arc::new(butterfly) as Arc<dyn Fft<T>>
 }
 
+// Describes a butterfly as the scalar algorithm with the same structure, so that the cost model can estimate it
+fn butterfly_recipe(len: usize) -> Arc<Recipe> {
+    Arc::new(match len {
+        0 | 1 => Recipe::Dft(len),
+        2 => Recipe::Butterfly2,
+        3 => Recipe::Butterfly3,
+        4 => Recipe::Butterfly4,
+        5 => Recipe::Butterfly5,
+        6 => Recipe::Butterfly6,
+        7 => Recipe::Butterfly7,
+        8 => Recipe::Butterfly8,
+        11 => Recipe::Butterfly11,
+        13 => Recipe::Butterfly13,
+        16 => Recipe::Butterfly16,
+        17 => Recipe::Butterfly17,
+        19 => Recipe::Butterfly19,
+        23 => Recipe::Butterfly23,
+        29 => Recipe::Butterfly29,
+        31 => Recipe::Butterfly31,
+        32 => Recipe::Butterfly32,
+        _ if len.is_power_of_two() => Recipe::Radix4(len),
+        _ => {
+            // The remaining butterflies are products of 2^n and 3^m, which compute a small power of two or a 3 first
+            let power2 = len.trailing_zeros();
+            let left_len = if power2 > 0 { 1 << min(power2, 4) } else { 3 };
+            Recipe::MixedRadixSmall {
+                left_fft: butterfly_recipe(left_len),
+                right_fft: butterfly_recipe(len / left_len),
+            }
+        }
+    })
+}
+
+// A FFT design used for estimates: the scalar recipe with the same structure, and the scratch lengths of the AVX instances
+struct AvxDesign {
+    recipe: Arc<Recipe>,
+    inplace_scratch_len: usize,
+    outofplace_scratch_len: usize,
+}
+
 #[derive(Debug)]
 enum MixedRadixBase {
     // The base will be a butterfly algorithm
@@ -27,6 +73,9 @@ enum MixedRadixBase {
     // First usize is the base length, second usize is the inner FFT length
     BluesteinsBase(usize, usize),
 
+    // The base will be a naive DFT, because the options deny every algorithm that could compute it faster
+    DftBase(usize),
+
     // The "base" is a FFT instance we already have cached
     CacheBase(usize),
 }
@@ -36,6 +85,7 @@ impl MixedRadixBase {
             Self::ButterflyBase(len) => *len,
             Self::RadersBase(len) => *len,
             Self::BluesteinsBase(len, _) => *len,
+            Self::DftBase(len) => *len,
             Self::CacheBase(len) => *len,
         }
     }
@@ -119,6 +169,20 @@ impl<T: FftNum> FftPlannerAvx<T> {
     /// Returns `Ok(planner_instance)` if this machine has the required instruction sets and the `avx` feature flag is set.
     /// Returns `Err(())` if some instruction sets are missing, or if the `avx` feature flag is not set.
     pub fn new() -> Result<Self, ()> {
+        Self::with_options(PlannerOptions::new())
+    }
+
+    /// Constructs a new `FftPlannerAvx` instance, which chooses algorithms according to `options`.
+    ///
+    /// The AVX planner only takes the allow and deny lists for Rader's and Bluestein's algorithms into account. See [`PlannerOptions`](crate::PlannerOptions) for details.
+    ///
+    /// Returns `Ok(planner_instance)` if this machine has the required instruction sets and the `avx` feature flag is set.
+    /// Returns `Err(())` if some instruction sets are missing, or if the `avx` feature flag is not set.
+    ///
+    /// # Panics
+    /// Panics if the options set `min_radix4_bits` larger than `max_radix4_bits`.
+    pub fn with_options(options: PlannerOptions) -> Result<Self, ()> {
+        options.validate();
         // Eventually we might make AVX algorithms that don't also require FMA.
         // If that happens, we can only check for AVX here? seems like a pretty low-priority addition
         let has_avx = is_x86_feature_detected!("avx");
@@ -152,11 +216,11 @@ impl<T: FftNum> FftPlannerAvx<T> {
 
             if id_t == id_f32 {
                 return Ok(Self {
-                    internal_planner: Box::new(AvxPlannerInternal::<f32, T>::new()),
+                    internal_planner: Box::new(AvxPlannerInternal::<f32, T>::new(options)),
                 });
             } else if id_t == id_f64 {
                 return Ok(Self {
-                    internal_planner: Box::new(AvxPlannerInternal::<f64, T>::new()),
+                    internal_planner: Box::new(AvxPlannerInternal::<f64, T>::new(options)),
                 });
             }
         }
@@ -184,6 +248,84 @@ impl<T: FftNum> FftPlannerAvx<T> {
         self.plan_fft(len, FftDirection::Inverse)
     }
 
+    /// Sets the policy that limits which FFT instances this planner keeps in its cache. If the cache doesn't fit the new policy,
+    /// the least recently used instances are discarded immediately.
+    ///
+    /// FFT instances that have already been returned by the planner stay valid when they're discarded from the cache.
+    pub fn set_cache_policy(&mut self, policy: CachePolicy) {
+        self.internal_planner.cache_mut().set_policy(policy);
+    }
+
+    /// Returns the policy that limits which FFT instances this planner keeps in its cache
+    pub fn cache_policy(&self) -> CachePolicy {
+        self.internal_planner.cache().policy()
+    }
+
+    /// Discards every FFT instance from this planner's cache.
+    ///
+    /// FFT instances that have already been returned by the planner stay valid.
+    pub fn clear(&mut self) {
+        self.internal_planner.cache_mut().clear();
+    }
+
+    /// Discards the cached FFT instances of size `len`, in both directions, from this planner's cache.
+    ///
+    /// Instances of other sizes that use a FFT of size `len` internally keep their own reference to it, so they aren't affected.
+    pub fn evict(&mut self, len: usize) {
+        self.internal_planner.cache_mut().evict(len);
+    }
+
+    /// Returns the number of heap bytes owned by the FFT instances in this planner's cache.
+    ///
+    /// This is the quantity limited by [`CachePolicy::MaxBytes`](crate::CachePolicy::MaxBytes). Each instance counts only the data returned by [`Fft::own_heap_bytes`](crate::Fft::own_heap_bytes),
+    /// so an inner FFT shared by several cached instances is only counted once.
+    pub fn cache_memory_bytes(&self) -> usize {
+        self.internal_planner.cache().memory_bytes()
+    }
+
+    /// Registers a custom FFT instance, which the planner will use whenever it needs a FFT of the same size and direction.
+    ///
+    /// The planner returns `fft` directly when asked for its size, and uses it as the base of larger sizes whenever one of the
+    /// planned steps has the same size. Registered instances are never discarded from the cache.
+    ///
+    /// FFT instances that were planned before registering won't use the registered instance, so register custom FFTs before planning.
+    pub fn register(&mut self, fft: Arc<dyn Fft<T>>) {
+        self.internal_planner.cache_mut().register(&fft);
+    }
+
+    /// Returns the smallest FFT size that's at least `min_len`, and that this planner computes especially fast: one of the form `2^n * 3^m`.
+    ///
+    /// # Panics
+    /// Panics if no such size fits in a `usize`.
+    pub fn next_fast_len(&self, min_len: usize) -> usize {
+        next_smooth_number(min_len, &FAST_ODD_PRIMES)
+            .unwrap_or_else(|| panic!("No fast FFT size of at least {} fits in a usize", min_len))
+    }
+
+    /// Returns the largest FFT size that's at most `max_len`, and that this planner computes especially fast: one of the form `2^n * 3^m`.
+    /// If `max_len` is zero, returns zero.
+    pub fn prev_fast_len(&self, max_len: usize) -> usize {
+        prev_smooth_number(max_len, &FAST_ODD_PRIMES)
+    }
+
+    /// Returns the estimated cost of the FFT that this planner creates for `len`, according to the [`cost_model`](crate::PlannerOptions::cost_model)
+    /// in this planner's options, or the default [`CostModel`](crate::CostModel) if the options don't have one.
+    ///
+    /// See [`estimate`](crate::FftPlannerAvx::estimate) for how the AVX algorithms are estimated.
+    pub fn estimated_cost(&self, len: usize) -> f64 {
+        self.estimate(len).cost
+    }
+
+    /// Returns an estimate of the operations, memory passes, and scratch of the FFT that this planner creates for `len`, without creating it.
+    ///
+    /// The estimate follows the AVX plan for `len`. The cost model measures scalar algorithms, so each AVX step is counted as the scalar algorithm
+    /// with the same structure: the operations and cost are comparable between sizes, but they overestimate the AVX instances. The scratch lengths are exact.
+    ///
+    /// The estimate describes the FFT that would be planned without any cached or registered instances, so it doesn't depend on what was planned before.
+    pub fn estimate(&self, len: usize) -> FftEstimate {
+        self.internal_planner.estimate(len)
+    }
+
     /// Returns a FFT plan without constructing it
     #[allow(unused)]
     pub(crate) fn debug_plan_fft(&self, len: usize, direction: FftDirection) -> MixedRadixPlan {
@@ -191,13 +333,17 @@ impl<T: FftNum> FftPlannerAvx<T> {
     }
 }
 
-trait AvxPlannerInternalAPI<T: FftNum> {
+trait AvxPlannerInternalAPI<T: FftNum>: Send + Sync {
     fn plan_and_construct_fft(&mut self, len: usize, direction: FftDirection) -> Arc<dyn Fft<T>>;
     fn debug_plan_fft(&self, len: usize, direction: FftDirection) -> MixedRadixPlan;
+    fn estimate(&self, len: usize) -> FftEstimate;
+    fn cache(&self) -> &FftCache<T>;
+    fn cache_mut(&mut self) -> &mut FftCache<T>;
 }
 
 struct AvxPlannerInternal<A: AvxNum, T: FftNum> {
     cache: FftCache<T>,
+    options: PlannerOptions,
     _phantom: std::marker::PhantomData<A>,
 }
 
@@ -217,6 +363,24 @@ impl<T: FftNum> AvxPlannerInternalAPI<T> for AvxPlannerInternal<f32, T> {
     fn debug_plan_fft(&self, len: usize, direction: FftDirection) -> MixedRadixPlan {
         self.plan_fft(len, direction, Self::plan_mixed_radix_base)
     }
+    fn estimate(&self, len: usize) -> FftEstimate {
+        let design = self.design_uncached(
+            len,
+            Self::plan_mixed_radix_base,
+            Self::butterfly_inplace_scratch_len,
+        );
+        FftEstimate {
+            inplace_scratch_len: design.inplace_scratch_len,
+            outofplace_scratch_len: design.outofplace_scratch_len,
+            ..self.options.estimate_recipe(&design.recipe)
+        }
+    }
+    fn cache(&self) -> &FftCache<T> {
+        &self.cache
+    }
+    fn cache_mut(&mut self) -> &mut FftCache<T> {
+        &mut self.cache
+    }
 }
 impl<T: FftNum> AvxPlannerInternalAPI<T> for AvxPlannerInternal<f64, T> {
     fn plan_and_construct_fft(&mut self, len: usize, direction: FftDirection) -> Arc<dyn Fft<T>> {
@@ -234,13 +398,31 @@ impl<T: FftNum> AvxPlannerInternalAPI<T> for AvxPlannerInternal<f64, T> {
     fn debug_plan_fft(&self, len: usize, direction: FftDirection) -> MixedRadixPlan {
         self.plan_fft(len, direction, Self::plan_mixed_radix_base)
     }
+    fn estimate(&self, len: usize) -> FftEstimate {
+        let design = self.design_uncached(
+            len,
+            Self::plan_mixed_radix_base,
+            Self::butterfly_inplace_scratch_len,
+        );
+        FftEstimate {
+            inplace_scratch_len: design.inplace_scratch_len,
+            outofplace_scratch_len: design.outofplace_scratch_len,
+            ..self.options.estimate_recipe(&design.recipe)
+        }
+    }
+    fn cache(&self) -> &FftCache<T> {
+        &self.cache
+    }
+    fn cache_mut(&mut self) -> &mut FftCache<T> {
+        &mut self.cache
+    }
 }
 
 //-------------------------------------------------------------------
 // f32-specific planning stuff
 //-------------------------------------------------------------------
 impl<T: FftNum> AvxPlannerInternal<f32, T> {
-    pub fn new() -> Self {
+    pub fn new(options: PlannerOptions) -> Self {
         // Internal sanity check: Make sure that T == f32.
         // This struct has two generic parameters A and T, but they must always be the same, and are only kept separate to help work around the lack of specialization.
         // It would be cool if we could do this as a static_assert instead
@@ -250,6 +432,7 @@ impl<T: FftNum> AvxPlannerInternal<f32, T> {
 
         Self {
             cache: FftCache::new(),
+            options,
             _phantom: std::marker::PhantomData,
         }
     }
@@ -260,13 +443,19 @@ impl<T: FftNum> AvxPlannerInternal<f32, T> {
             let other_factors = factors.get_other_factors();
 
             // We can only use rader's if `other_factors` is prime
-            if miller_rabin(other_factors as u64) {
+            let raders_allowed = self.options.is_allowed(PlannerAlgorithm::Raders);
+            let bluesteins_allowed = self.options.is_allowed(PlannerAlgorithm::Bluesteins);
+            if raders_allowed && miller_rabin(other_factors as u64) {
                 // len is prime, so we can use Rader's Algorithm as a base. Whether or not that's a good idea is a different story
                 // Rader's Algorithm is only faster in a few narrow cases.
                 // as a heuristic, only use rader's algorithm if its inner FFT can be computed entirely without bluestein's or rader's
                 // We're intentionally being too conservative here. Otherwise we'd be recursively applying a heuristic, and repeated heuristic failures could stack to make a rader's chain significantly slower.
                 // If we were writing a measuring planner, expanding this heuristic and measuring its effectiveness would be an opportunity for up to 2x performance gains.
+                // If bluestein's algorithm is denied, rader's is the only fast option left, so skip the heuristic
                 let inner_factors = PartialFactors::compute(other_factors - 1);
+                if !bluesteins_allowed {
+                    return MixedRadixPlan::new(MixedRadixBase::RadersBase(other_factors), vec![]);
+                }
                 if inner_factors.get_other_factors() == 1 {
                     // We only have factors of 2,3,5,7, and 11. If we don't have AVX2, we also have to exclude factors of 5 and 7 and 11, because avx2 gives us enough headroom for the overhead of those to not be a problem
                     if is_x86_feature_detected!("avx2")
@@ -280,6 +469,11 @@ impl<T: FftNum> AvxPlannerInternal<f32, T> {
                 }
             }
 
+            // If we can't use either algorithm, fall back to a naive DFT
+            if !bluesteins_allowed {
+                return MixedRadixPlan::new(MixedRadixBase::DftBase(other_factors), vec![]);
+            }
+
             // At this point, we know we're using bluestein's algorithm for the base. Next step is to plan the inner size we'll use for bluestein's algorithm.
             let inner_bluesteins_len =
                 self.plan_bluesteins(other_factors, |(_len, factor2, factor3)| {
@@ -405,6 +599,13 @@ impl<T: FftNum> AvxPlannerInternal<f32, T> {
         .contains(&len)
     }
 
+    fn butterfly_inplace_scratch_len(len: usize) -> usize {
+        match len {
+            0 | 1 | 128 | 256 | 512 => len,
+            _ => 0,
+        }
+    }
+
     fn construct_butterfly(&self, len: usize, direction: FftDirection) -> Arc<dyn Fft<T>> {
         match len {
             0 | 1 => wrap_fft(Dft::new(len, direction)),
@@ -445,7 +646,7 @@ impl<T: FftNum> AvxPlannerInternal<f32, T> {
 // f64-specific planning stuff
 //-------------------------------------------------------------------
 impl<T: FftNum> AvxPlannerInternal<f64, T> {
-    pub fn new() -> Self {
+    pub fn new(options: PlannerOptions) -> Self {
         // Internal sanity check: Make sure that T == f64.
         // This struct has two generic parameters A and T, but they must always be the same, and are only kept separate to help work around the lack of specialization.
         // It would be cool if we could do this as a static_assert instead
@@ -455,6 +656,7 @@ impl<T: FftNum> AvxPlannerInternal<f64, T> {
 
         Self {
             cache: FftCache::new(),
+            options,
             _phantom: std::marker::PhantomData,
         }
     }
@@ -465,13 +667,19 @@ impl<T: FftNum> AvxPlannerInternal<f64, T> {
             let other_factors = factors.get_other_factors();
 
             // We can only use rader's if `other_factors` is prime
-            if miller_rabin(other_factors as u64) {
+            let raders_allowed = self.options.is_allowed(PlannerAlgorithm::Raders);
+            let bluesteins_allowed = self.options.is_allowed(PlannerAlgorithm::Bluesteins);
+            if raders_allowed && miller_rabin(other_factors as u64) {
                 // len is prime, so we can use Rader's Algorithm as a base. Whether or not that's a good idea is a different story
                 // Rader's Algorithm is only faster in a few narrow cases.
                 // as a heuristic, only use rader's algorithm if its inner FFT can be computed entirely without bluestein's or rader's
                 // We're intentionally being too conservative here. Otherwise we'd be recursively applying a heuristic, and repeated heuristic failures could stack to make a rader's chain significantly slower.
                 // If we were writing a measuring planner, expanding this heuristic and measuring its effectiveness would be an opportunity for up to 2x performance gains.
+                // If bluestein's algorithm is denied, rader's is the only fast option left, so skip the heuristic
                 let inner_factors = PartialFactors::compute(other_factors - 1);
+                if !bluesteins_allowed {
+                    return MixedRadixPlan::new(MixedRadixBase::RadersBase(other_factors), vec![]);
+                }
                 if inner_factors.get_other_factors() == 1 {
                     // We only have factors of 2,3,5,7, and 11. If we don't have AVX2, we also have to exclude factors of 5 and 7 and 11, because avx2 gives us enough headroom for the overhead of those to not be a problem
                     if is_x86_feature_detected!("avx2")
@@ -485,6 +693,11 @@ impl<T: FftNum> AvxPlannerInternal<f64, T> {
                 }
             }
 
+            // If we can't use either algorithm, fall back to a naive DFT
+            if !bluesteins_allowed {
+                return MixedRadixPlan::new(MixedRadixBase::DftBase(other_factors), vec![]);
+            }
+
             // At this point, we know we're using bluestein's algorithm for the base. Next step is to plan the inner size we'll use for bluestein's algorithm.
             let inner_bluesteins_len =
                 self.plan_bluesteins(other_factors, |(_len, factor2, factor3)| {
@@ -614,6 +827,13 @@ impl<T: FftNum> AvxPlannerInternal<f64, T> {
         .contains(&len)
     }
 
+    fn butterfly_inplace_scratch_len(len: usize) -> usize {
+        match len {
+            0 | 1 | 64 | 128 | 256 | 512 => len,
+            _ => 0,
+        }
+    }
+
     fn construct_butterfly(&self, len: usize, direction: FftDirection) -> Arc<dyn Fft<T>> {
         match len {
             0 | 1 => wrap_fft(Dft::new(len, direction)),
@@ -664,12 +884,25 @@ impl<A: AvxNum, T: FftNum> AvxPlannerInternal<A, T> {
             return MixedRadixPlan::cached(len);
         }
 
-        // This length is not cached, so we have to come up with a new plan. The first step is to find a suitable base.
+        // This length is not cached, so we have to come up with a new plan
+        let uncached_plan = self.plan_uncached(len, base_fn);
+
+        // Last step: We have a full FFT plan, but some of the steps of that plan may have been cached. If they have, use the largest cached step as the base.
+        self.replan_with_cache(uncached_plan, direction)
+    }
+
+    // Given a length, return a plan for how this FFT should be computed if nothing was cached
+    fn plan_uncached(
+        &self,
+        len: usize,
+        base_fn: impl FnOnce(&Self, usize, &PartialFactors) -> MixedRadixPlan,
+    ) -> MixedRadixPlan {
+        // The first step is to find a suitable base.
         let factors = PartialFactors::compute(len);
         let base = base_fn(self, len, &factors);
 
         // it's possible that the base planner plans out the whole FFT. it's guaranteed if `len` is a prime number, or if it's a butterfly, for example
-        let uncached_plan = if base.len == len {
+        if base.len == len {
             base
         } else {
             // We have some mixed radix steps to compute! Compute the factors that need to computed by mixed radix steps,
@@ -682,10 +915,84 @@ impl<A: AvxNum, T: FftNum> AvxPlannerInternal<A, T> {
                     )
                 });
             self.plan_mixed_radix(radix_factors, base)
+        }
+    }
+
+    // Designs the FFT that `plan_fft` would construct for `len` if nothing was cached, for estimates.
+    // The scratch lengths must be kept in sync with the scratch computations in each AVX algorithm's constructor.
+    fn design_uncached(
+        &self,
+        len: usize,
+        base_fn: impl Fn(&Self, usize, &PartialFactors) -> MixedRadixPlan + Copy,
+        butterfly_scratch_fn: impl Fn(usize) -> usize + Copy,
+    ) -> AvxDesign {
+        let plan = self.plan_uncached(len, base_fn);
+
+        let mut design = match plan.base {
+            MixedRadixBase::ButterflyBase(len) => AvxDesign {
+                recipe: butterfly_recipe(len),
+                inplace_scratch_len: butterfly_scratch_fn(len),
+                outofplace_scratch_len: 0,
+            },
+            MixedRadixBase::RadersBase(len) => {
+                let inner = self.design_uncached(len - 1, base_fn, butterfly_scratch_fn);
+                let extra_inner_scratch = if inner.inplace_scratch_len > len - 1 {
+                    inner.inplace_scratch_len
+                } else {
+                    0
+                };
+                // construct_plan falls back to the scalar Rader's Algorithm, whose scratch is one element shorter, if the machine doesn't have AVX2
+                let buffer_len = if is_x86_feature_detected!("avx2") {
+                    len
+                } else {
+                    len - 1
+                };
+                AvxDesign {
+                    recipe: Arc::new(Recipe::RadersAlgorithm {
+                        inner_fft: inner.recipe,
+                    }),
+                    inplace_scratch_len: buffer_len + extra_inner_scratch,
+                    outofplace_scratch_len: extra_inner_scratch,
+                }
+            }
+            MixedRadixBase::BluesteinsBase(len, inner_fft_len) => {
+                let inner = self.design_uncached(inner_fft_len, base_fn, butterfly_scratch_fn);
+                let required_scratch = inner_fft_len + inner.inplace_scratch_len;
+                AvxDesign {
+                    recipe: Arc::new(Recipe::BluesteinsAlgorithm {
+                        len,
+                        inner_fft: inner.recipe,
+                    }),
+                    inplace_scratch_len: required_scratch,
+                    outofplace_scratch_len: required_scratch,
+                }
+            }
+            MixedRadixBase::DftBase(len) => AvxDesign {
+                recipe: Arc::new(Recipe::Dft(len)),
+                inplace_scratch_len: len,
+                outofplace_scratch_len: 0,
+            },
+            MixedRadixBase::CacheBase(_) => unreachable!(),
         };
 
-        // Last step: We have a full FFT plan, but some of the steps of that plan may have been cached. If they have, use the largest cached step as the base.
-        self.replan_with_cache(uncached_plan, direction)
+        // Each radix step is a MixedRadix*xnAvx on top of the previous design
+        for radix in plan.radixes {
+            let len = design.recipe.len() * radix as usize;
+            design = AvxDesign {
+                recipe: Arc::new(Recipe::MixedRadix {
+                    left_fft: butterfly_recipe(radix as usize),
+                    right_fft: design.recipe,
+                }),
+                inplace_scratch_len: len + design.outofplace_scratch_len,
+                outofplace_scratch_len: if design.inplace_scratch_len > len {
+                    design.inplace_scratch_len
+                } else {
+                    0
+                },
+            };
+        }
+
+        design
     }
 
     // Takes a plan and an algorithm cache, and replaces steps of the plan with cached steps, if possible
@@ -898,6 +1205,14 @@ impl<A: AvxNum, T: FftNum> AvxPlannerInternal<A, T> {
 
                 bluesteins_instance
             }
+            MixedRadixBase::DftBase(len) => {
+                let dft_instance = wrap_fft(Dft::new(len, direction));
+
+                // Cache this FFT instance for future calls to `plan_fft`
+                self.cache.insert(&dft_instance);
+
+                dft_instance
+            }
         };
 
         // We have constructed our base. Now, construct the radix chain.
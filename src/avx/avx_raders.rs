@@ -490,7 +490,10 @@ boilerplate_avx_fft!(
     RadersAvx2,
     |this: &RadersAvx2<_, _>| this.len,
     |this: &RadersAvx2<_, _>| this.inplace_scratch_len,
-    |this: &RadersAvx2<_, _>| this.outofplace_scratch_len
+    |this: &RadersAvx2<_, _>| this.outofplace_scratch_len,
+    |this: &RadersAvx2<_, _>| std::mem::size_of_val(&*this.output_index_mapping)
+        + std::mem::size_of_val(&*this.twiddles),
+    |this: &RadersAvx2<_, _>| vec![Arc::clone(&this.inner_fft)]
 );
 
 #[cfg(test)]
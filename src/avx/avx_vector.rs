@@ -5,7 +5,7 @@ use num_complex::Complex;
 use num_traits::Zero;
 
 use crate::{
-    array_utils::{RawSlice, RawSliceMut},
+    array_utils::{RawSlice, RawSliceMut, RawSplitSlice, RawSplitSliceMut},
     twiddles, FftDirection,
 };
 
@@ -529,6 +529,45 @@ pub trait AvxVector256: AvxVector {
     unsafe fn store_partial2_complex(ptr: *mut Complex<Self::ScalarType>, data: Self::HalfVector);
     unsafe fn store_partial3_complex(ptr: *mut Complex<Self::ScalarType>, data: Self);
 
+    // Loads and stores of complex numbers whose real parts and imaginary parts are in separate arrays. The loads interleave the
+    // real and imaginary parts in registers, and the stores separate them again, so split-complex data never gets interleaved in memory
+    unsafe fn load_split_complex(
+        real: *const Self::ScalarType,
+        imag: *const Self::ScalarType,
+    ) -> Self;
+    unsafe fn store_split_complex(
+        real: *mut Self::ScalarType,
+        imag: *mut Self::ScalarType,
+        data: Self,
+    );
+    unsafe fn load_split_partial1_complex(
+        real: *const Self::ScalarType,
+        imag: *const Self::ScalarType,
+    ) -> Self::HalfVector;
+    unsafe fn load_split_partial2_complex(
+        real: *const Self::ScalarType,
+        imag: *const Self::ScalarType,
+    ) -> Self::HalfVector;
+    unsafe fn load_split_partial3_complex(
+        real: *const Self::ScalarType,
+        imag: *const Self::ScalarType,
+    ) -> Self;
+    unsafe fn store_split_partial1_complex(
+        real: *mut Self::ScalarType,
+        imag: *mut Self::ScalarType,
+        data: Self::HalfVector,
+    );
+    unsafe fn store_split_partial2_complex(
+        real: *mut Self::ScalarType,
+        imag: *mut Self::ScalarType,
+        data: Self::HalfVector,
+    );
+    unsafe fn store_split_partial3_complex(
+        real: *mut Self::ScalarType,
+        imag: *mut Self::ScalarType,
+        data: Self,
+    );
+
     #[inline(always)]
     unsafe fn column_butterfly6(rows: [Self; 6], twiddles: Self) -> [Self; 6] {
         // Algorithm: 3x2 good-thomas
@@ -1194,6 +1233,79 @@ impl AvxVector256 for __m256 {
         Self::store_partial2_complex(ptr, data.lo());
         Self::store_partial1_complex(ptr.add(2), data.hi());
     }
+    #[inline(always)]
+    unsafe fn load_split_complex(
+        real: *const Self::ScalarType,
+        imag: *const Self::ScalarType,
+    ) -> Self {
+        let real = _mm_loadu_ps(real);
+        let imag = _mm_loadu_ps(imag);
+        Self::merge(_mm_unpacklo_ps(real, imag), _mm_unpackhi_ps(real, imag))
+    }
+    #[inline(always)]
+    unsafe fn store_split_complex(
+        real: *mut Self::ScalarType,
+        imag: *mut Self::ScalarType,
+        data: Self,
+    ) {
+        let (lo, hi) = data.split();
+        _mm_storeu_ps(real, _mm_shuffle_ps(lo, hi, 0b10_00_10_00));
+        _mm_storeu_ps(imag, _mm_shuffle_ps(lo, hi, 0b11_01_11_01));
+    }
+    #[inline(always)]
+    unsafe fn load_split_partial1_complex(
+        real: *const Self::ScalarType,
+        imag: *const Self::ScalarType,
+    ) -> Self::HalfVector {
+        _mm_unpacklo_ps(_mm_load_ss(real), _mm_load_ss(imag))
+    }
+    #[inline(always)]
+    unsafe fn load_split_partial2_complex(
+        real: *const Self::ScalarType,
+        imag: *const Self::ScalarType,
+    ) -> Self::HalfVector {
+        let real = _mm_castpd_ps(_mm_load_sd(real as *const f64));
+        let imag = _mm_castpd_ps(_mm_load_sd(imag as *const f64));
+        _mm_unpacklo_ps(real, imag)
+    }
+    #[inline(always)]
+    unsafe fn load_split_partial3_complex(
+        real: *const Self::ScalarType,
+        imag: *const Self::ScalarType,
+    ) -> Self {
+        let lo = Self::load_split_partial2_complex(real, imag);
+        let hi = Self::load_split_partial1_complex(real.add(2), imag.add(2));
+        Self::merge(lo, hi)
+    }
+    #[inline(always)]
+    unsafe fn store_split_partial1_complex(
+        real: *mut Self::ScalarType,
+        imag: *mut Self::ScalarType,
+        data: Self::HalfVector,
+    ) {
+        _mm_store_ss(real, data);
+        _mm_store_ss(imag, _mm_shuffle_ps(data, data, 0b01));
+    }
+    #[inline(always)]
+    unsafe fn store_split_partial2_complex(
+        real: *mut Self::ScalarType,
+        imag: *mut Self::ScalarType,
+        data: Self::HalfVector,
+    ) {
+        // Move the reals to the low half and the imaginaries to the high half
+        let separated = _mm_castps_pd(_mm_shuffle_ps(data, data, 0b11_01_10_00));
+        _mm_storel_pd(real as *mut f64, separated);
+        _mm_storeh_pd(imag as *mut f64, separated);
+    }
+    #[inline(always)]
+    unsafe fn store_split_partial3_complex(
+        real: *mut Self::ScalarType,
+        imag: *mut Self::ScalarType,
+        data: Self,
+    ) {
+        Self::store_split_partial2_complex(real, imag, data.lo());
+        Self::store_split_partial1_complex(real.add(2), imag.add(2), data.hi());
+    }
 }
 
 impl AvxVector for __m128 {
@@ -1787,6 +1899,71 @@ impl AvxVector256 for __m256d {
     unsafe fn store_partial3_complex(_ptr: *mut Complex<Self::ScalarType>, _data: Self) {
         unimplemented!("Impossible to do a partial store of 3 complex f64's")
     }
+    #[inline(always)]
+    unsafe fn load_split_complex(
+        real: *const Self::ScalarType,
+        imag: *const Self::ScalarType,
+    ) -> Self {
+        let real = _mm_loadu_pd(real);
+        let imag = _mm_loadu_pd(imag);
+        Self::merge(_mm_unpacklo_pd(real, imag), _mm_unpackhi_pd(real, imag))
+    }
+    #[inline(always)]
+    unsafe fn store_split_complex(
+        real: *mut Self::ScalarType,
+        imag: *mut Self::ScalarType,
+        data: Self,
+    ) {
+        let (lo, hi) = data.split();
+        _mm_storeu_pd(real, _mm_unpacklo_pd(lo, hi));
+        _mm_storeu_pd(imag, _mm_unpackhi_pd(lo, hi));
+    }
+    #[inline(always)]
+    unsafe fn load_split_partial1_complex(
+        real: *const Self::ScalarType,
+        imag: *const Self::ScalarType,
+    ) -> Self::HalfVector {
+        _mm_unpacklo_pd(_mm_load_sd(real), _mm_load_sd(imag))
+    }
+    #[inline(always)]
+    unsafe fn load_split_partial2_complex(
+        _real: *const Self::ScalarType,
+        _imag: *const Self::ScalarType,
+    ) -> Self::HalfVector {
+        unimplemented!("Impossible to do a partial load of 2 complex f64's")
+    }
+    #[inline(always)]
+    unsafe fn load_split_partial3_complex(
+        _real: *const Self::ScalarType,
+        _imag: *const Self::ScalarType,
+    ) -> Self {
+        unimplemented!("Impossible to do a partial load of 3 complex f64's")
+    }
+    #[inline(always)]
+    unsafe fn store_split_partial1_complex(
+        real: *mut Self::ScalarType,
+        imag: *mut Self::ScalarType,
+        data: Self::HalfVector,
+    ) {
+        _mm_storel_pd(real, data);
+        _mm_storeh_pd(imag, data);
+    }
+    #[inline(always)]
+    unsafe fn store_split_partial2_complex(
+        _real: *mut Self::ScalarType,
+        _imag: *mut Self::ScalarType,
+        _data: Self::HalfVector,
+    ) {
+        unimplemented!("Impossible to do a partial store of 2 complex f64's")
+    }
+    #[inline(always)]
+    unsafe fn store_split_partial3_complex(
+        _real: *mut Self::ScalarType,
+        _imag: *mut Self::ScalarType,
+        _data: Self,
+    ) {
+        unimplemented!("Impossible to do a partial store of 3 complex f64's")
+    }
 }
 
 impl AvxVector for __m128d {
@@ -2123,6 +2300,90 @@ impl<T: AvxNum> AvxArrayMut<T> for RawSliceMut<Complex<T>> {
     }
 }
 
+impl<T: AvxNum> AvxArray<T> for RawSplitSlice<T> {
+    #[inline(always)]
+    unsafe fn load_complex(&self, index: usize) -> T::VectorType {
+        debug_assert!(self.len() >= index + T::VectorType::COMPLEX_PER_VECTOR);
+        T::VectorType::load_split_complex(self.real_ptr().add(index), self.imag_ptr().add(index))
+    }
+    #[inline(always)]
+    unsafe fn load_partial1_complex(
+        &self,
+        index: usize,
+    ) -> <T::VectorType as AvxVector256>::HalfVector {
+        debug_assert!(self.len() >= index + 1);
+        T::VectorType::load_split_partial1_complex(
+            self.real_ptr().add(index),
+            self.imag_ptr().add(index),
+        )
+    }
+    #[inline(always)]
+    unsafe fn load_partial2_complex(
+        &self,
+        index: usize,
+    ) -> <T::VectorType as AvxVector256>::HalfVector {
+        debug_assert!(self.len() >= index + 2);
+        T::VectorType::load_split_partial2_complex(
+            self.real_ptr().add(index),
+            self.imag_ptr().add(index),
+        )
+    }
+    #[inline(always)]
+    unsafe fn load_partial3_complex(&self, index: usize) -> T::VectorType {
+        debug_assert!(self.len() >= index + 3);
+        T::VectorType::load_split_partial3_complex(
+            self.real_ptr().add(index),
+            self.imag_ptr().add(index),
+        )
+    }
+}
+impl<T: AvxNum> AvxArrayMut<T> for RawSplitSliceMut<T> {
+    #[inline(always)]
+    unsafe fn store_complex(&mut self, data: T::VectorType, index: usize) {
+        debug_assert!(self.len() >= index + T::VectorType::COMPLEX_PER_VECTOR);
+        T::VectorType::store_split_complex(
+            self.real_ptr().add(index),
+            self.imag_ptr().add(index),
+            data,
+        );
+    }
+    #[inline(always)]
+    unsafe fn store_partial1_complex(
+        &mut self,
+        data: <T::VectorType as AvxVector256>::HalfVector,
+        index: usize,
+    ) {
+        debug_assert!(self.len() >= index + 1);
+        T::VectorType::store_split_partial1_complex(
+            self.real_ptr().add(index),
+            self.imag_ptr().add(index),
+            data,
+        )
+    }
+    #[inline(always)]
+    unsafe fn store_partial2_complex(
+        &mut self,
+        data: <T::VectorType as AvxVector256>::HalfVector,
+        index: usize,
+    ) {
+        debug_assert!(self.len() >= index + 2);
+        T::VectorType::store_split_partial2_complex(
+            self.real_ptr().add(index),
+            self.imag_ptr().add(index),
+            data,
+        )
+    }
+    #[inline(always)]
+    unsafe fn store_partial3_complex(&mut self, data: T::VectorType, index: usize) {
+        debug_assert!(self.len() >= index + 3);
+        T::VectorType::store_split_partial3_complex(
+            self.real_ptr().add(index),
+            self.imag_ptr().add(index),
+            data,
+        )
+    }
+}
+
 // A custom butterfly-16 function that calls a lambda to load/store data instead of taking an array
 // This is particularly useful for butterfly 16, because the whole problem doesn't fit into registers, and the compiler isn't smart enough to only load data when it's needed
 // So the version that takes an array ends up loading data and immediately re-storing it on the stack. By lazily loading and storing exactly when we need to, we can avoid some data reshuffling
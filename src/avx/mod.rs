@@ -28,7 +28,7 @@ struct CommonSimdData<T, V> {
 }
 
 macro_rules! boilerplate_avx_fft {
-    ($struct_name:ident, $len_fn:expr, $inplace_scratch_len_fn:expr, $out_of_place_scratch_len_fn:expr) => {
+    ($struct_name:ident, $len_fn:expr, $inplace_scratch_len_fn:expr, $out_of_place_scratch_len_fn:expr, $own_heap_bytes_fn:expr, $inner_ffts_fn:expr) => {
         impl<A: AvxNum, T: FftNum> Fft<T> for $struct_name<A, T> {
             fn process_outofplace_with_scratch(
                 &self,
@@ -111,6 +111,12 @@ macro_rules! boilerplate_avx_fft {
             fn get_outofplace_scratch_len(&self) -> usize {
                 $out_of_place_scratch_len_fn(self)
             }
+            fn own_heap_bytes(&self) -> usize {
+                $own_heap_bytes_fn(self)
+            }
+            fn inner_ffts(&self) -> Vec<Arc<dyn Fft<T>>> {
+                $inner_ffts_fn(self)
+            }
         }
         impl<A: AvxNum, T> Length for $struct_name<A, T> {
             #[inline(always)]
@@ -129,6 +135,10 @@ macro_rules! boilerplate_avx_fft {
 
 macro_rules! boilerplate_avx_fft_commondata {
     ($struct_name:ident) => {
+        boilerplate_avx_fft_commondata!($struct_name, |_| 0);
+    };
+    // The second argument computes the heap bytes owned by fields outside of common_data
+    ($struct_name:ident, $extra_heap_bytes_fn:expr) => {
         impl<A: AvxNum, T: FftNum> Fft<T> for $struct_name<A, T> {
             fn process_outofplace_with_scratch(
                 &self,
@@ -219,6 +229,12 @@ macro_rules! boilerplate_avx_fft_commondata {
             fn get_outofplace_scratch_len(&self) -> usize {
                 self.common_data.outofplace_scratch_len
             }
+            fn own_heap_bytes(&self) -> usize {
+                std::mem::size_of_val(&*self.common_data.twiddles) + $extra_heap_bytes_fn(self)
+            }
+            fn inner_ffts(&self) -> Vec<Arc<dyn Fft<T>>> {
+                vec![Arc::clone(&self.common_data.inner_fft)]
+            }
         }
         impl<A: AvxNum, T> Length for $struct_name<A, T> {
             #[inline(always)]
@@ -0,0 +1,811 @@
+use std::ops::Range;
+use std::sync::Arc;
+
+use num_complex::Complex;
+use num_traits::Zero;
+
+use crate::algorithm::bluesteins_algorithm::{
+    compute_convolution_multiplier, convolve_with_multiplier, finish_chirp_convolution,
+    prepare_chirp_convolution,
+};
+use crate::{common::FftNum, Direction, Fft, FftDirection, FftPlanner, Length};
+
+/// Implementation of the [Chirp-Z transform](https://en.wikipedia.org/wiki/Chirp_Z-transform)
+///
+/// The Chirp-Z transform evaluates the z-transform of a size-N input at M points along a spiral contour in the z-plane:
+///
+/// `output[k] = sum(input[n] * a^(-n) * w^(n * k))`, for `k` in `0..M`
+///
+/// where `a` is the starting point of the contour, and `w` is the ratio between consecutive points. When `a = 1` and
+/// `w = exp(-2 * pi * i / N)`, and `M = N`, the Chirp-Z transform is identical to a forward FFT of size N. Points on the unit circle
+/// with an arc smaller than the full circle give a zoomed-in spectrum, and points off the unit circle can be used to search for
+/// poles and zeros.
+///
+/// Just like [`BluesteinsAlgorithm`](crate::algorithm::BluesteinsAlgorithm), this works by rewriting the transform as a convolution
+/// and computing that convolution with an inner FFT of size `>= N + M - 1`, so any input and output size can be computed in O(nlogn) time.
+///
+/// ~~~
+/// // Evaluate 100 points of the z-transform of a size-1000 signal, along a circle of radius 0.9
+/// use rustfft::chirp_z::ChirpZ;
+/// use rustfft::FftPlanner;
+/// use rustfft::num_complex::Complex;
+///
+/// let input = vec![Complex{ re: 0.0f32, im: 0.0f32 }; 1000];
+/// let mut output = vec![Complex{ re: 0.0f32, im: 0.0f32 }; 100];
+///
+/// let a = Complex::new(0.9, 0.0);
+/// let w = Complex::from_polar(1.0, -2.0 * std::f64::consts::PI / 100.0);
+///
+/// let mut planner = FftPlanner::new();
+/// let czt = ChirpZ::with_planner(1000, 100, a, w, &mut planner);
+/// czt.process(&input, &mut output);
+/// ~~~
+pub struct ChirpZ<T> {
+    inner_fft: Arc<dyn Fft<T>>,
+    inner_fft_multiplier: Box<[Complex<T>]>,
+
+    input_chirp: Box<[Complex<T>]>,
+    output_chirp: Box<[Complex<T>]>,
+}
+
+impl<T: FftNum> ChirpZ<T> {
+    /// Creates a Chirp-Z transform which will process inputs of size `len` and outputs of size `output_len`,
+    /// evaluated on the contour starting at `a`, with ratio `w` between consecutive points.
+    ///
+    /// `inner_fft.len()` must be >= `len + output_len - 1`. The inner FFT may be either direction.
+    ///
+    /// Note that this constructor is quite expensive to run; This algorithm must compute a FFT using `inner_fft` within the
+    /// constructor.
+    ///
+    /// # Panics
+    /// Panics if `len == 0`, if `output_len == 0`, if `a` or `w` is zero, or if `inner_fft.len() < len + output_len - 1`.
+    pub fn new(
+        len: usize,
+        output_len: usize,
+        a: Complex<f64>,
+        w: Complex<f64>,
+        inner_fft: Arc<dyn Fft<T>>,
+    ) -> Self {
+        assert!(
+            len > 0 && output_len > 0,
+            "The Chirp-Z transform requires non-empty inputs and outputs. Got len = {}, output_len = {}",
+            len,
+            output_len
+        );
+        assert!(
+            !a.is_zero() && !w.is_zero(),
+            "The Chirp-Z transform requires nonzero a and w. Got a = {}, w = {}",
+            a,
+            w
+        );
+        let ln_w = w.ln();
+        let w_turns = ln_w.im / (2f64 * std::f64::consts::PI);
+        Self::from_logarithms(len, output_len, a.ln(), ln_w.re, w_turns, inner_fft)
+    }
+
+    // Creates a Chirp-Z transform from the natural logarithm of `a`, and `w` split into the natural logarithm of its magnitude and
+    // its angle in turns. Callers that know these exactly, IE for contours on the unit circle, should use this to avoid the rounding
+    // error of computing them from `a` and `w`
+    fn from_logarithms(
+        len: usize,
+        output_len: usize,
+        ln_a: Complex<f64>,
+        ln_w_magnitude: f64,
+        w_turns: f64,
+        inner_fft: Arc<dyn Fft<T>>,
+    ) -> Self {
+        assert!(len + output_len - 1 <= inner_fft.len(), "The Chirp-Z transform requires inner_fft.len() >= len + output_len - 1. Expected >= {}, got {}", len + output_len - 1, inner_fft.len());
+
+        // Rewrite n * k as (n^2 + k^2 - (k - n)^2) / 2. Then w^(n * k) splits into a chirp applied to the input,
+        // a chirp applied to the output, and a convolution with a third chirp.
+        // All three chirps take powers of the same logarithm of w, so they recombine exactly even when w isn't on the unit circle.
+        // The angles grow with i^2, so they're reduced modulo one turn before converting them to radians
+        let phase = ChirpPhase::new(w_turns);
+        let chirp = |i: usize, sign: f64, ln_a_multiple: Complex<f64>| {
+            let half_square = (i as f64) * (i as f64) / 2f64;
+            let turns = phase.half_square_turns(i);
+            let c = Complex::from_polar(
+                (sign * ln_w_magnitude * half_square - ln_a_multiple.re).exp(),
+                sign * 2f64 * std::f64::consts::PI * turns - ln_a_multiple.im,
+            );
+            Complex {
+                re: T::from_f64(c.re).unwrap(),
+                im: T::from_f64(c.im).unwrap(),
+            }
+        };
+
+        let inner_fft_multiplier =
+            compute_convolution_multiplier(&*inner_fft, output_len, len, |i: isize| {
+                chirp(i.abs() as usize, -1f64, Complex::zero())
+            });
+        let input_chirp = (0..len).map(|n| chirp(n, 1f64, ln_a * n as f64)).collect();
+        let output_chirp = (0..output_len)
+            .map(|k| chirp(k, 1f64, Complex::zero()))
+            .collect();
+
+        Self {
+            inner_fft,
+            inner_fft_multiplier,
+
+            input_chirp,
+            output_chirp,
+        }
+    }
+
+    /// Creates a Chirp-Z transform like [`new`](ChirpZ::new), using `planner` to plan the inner FFT
+    pub fn with_planner(
+        len: usize,
+        output_len: usize,
+        a: Complex<f64>,
+        w: Complex<f64>,
+        planner: &mut FftPlanner<T>,
+    ) -> Self {
+        let inner_fft = plan_inner_fft(len, output_len, planner);
+        Self::new(len, output_len, a, w, inner_fft)
+    }
+
+    /// Returns the number of output points computed for each input chunk
+    pub fn output_len(&self) -> usize {
+        self.output_chirp.len()
+    }
+
+    /// Returns the size of the scratch buffer required by `process_with_scratch`
+    pub fn get_scratch_len(&self) -> usize {
+        self.inner_fft_multiplier.len() + self.inner_fft.get_inplace_scratch_len()
+    }
+
+    /// Divides `input` into chunks of size `self.len()`, and `output` into chunks of size `self.output_len()`,
+    /// and computes a Chirp-Z transform of each input chunk into the corresponding output chunk.
+    ///
+    /// Convenience method that allocates a `Vec` with the required scratch space and calls `self.process_with_scratch`.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if:
+    /// - `input.len() % self.len() > 0`
+    /// - `input.len() / self.len() != output.len() / self.output_len()`
+    /// - `output.len() % self.output_len() > 0`
+    pub fn process(&self, input: &[Complex<T>], output: &mut [Complex<T>]) {
+        let mut scratch = vec![Complex::zero(); self.get_scratch_len()];
+        self.process_with_scratch(input, output, &mut scratch);
+    }
+
+    /// Divides `input` into chunks of size `self.len()`, and `output` into chunks of size `self.output_len()`,
+    /// and computes a Chirp-Z transform of each input chunk into the corresponding output chunk.
+    ///
+    /// Uses the `scratch` buffer as scratch space, so the contents of `scratch` should be considered garbage
+    /// after calling.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if:
+    /// - `input.len() % self.len() > 0`
+    /// - `input.len() / self.len() != output.len() / self.output_len()`
+    /// - `output.len() % self.output_len() > 0`
+    /// - `scratch.len() < self.get_scratch_len()`
+    pub fn process_with_scratch(
+        &self,
+        input: &[Complex<T>],
+        output: &mut [Complex<T>],
+        scratch: &mut [Complex<T>],
+    ) {
+        let len = self.len();
+        let output_len = self.output_len();
+        let chunk_count = input.len() / len;
+        assert!(
+            chunk_count * len == input.len() && chunk_count * output_len == output.len(),
+            "Chirp-Z buffers must contain the same number of chunks. Expected input len multiple of {} and output len = input len / {} * {}, got input len = {}, output len = {}",
+            len,
+            len,
+            output_len,
+            input.len(),
+            output.len()
+        );
+        assert!(
+            scratch.len() >= self.get_scratch_len(),
+            "Not enough scratch space was provided. Expected scratch len >= {}, got scratch len = {}",
+            self.get_scratch_len(),
+            scratch.len()
+        );
+
+        let (inner_input, inner_scratch) = scratch.split_at_mut(self.inner_fft_multiplier.len());
+        let inner_scratch = &mut inner_scratch[..self.inner_fft.get_inplace_scratch_len()];
+        for (in_chunk, out_chunk) in input
+            .chunks_exact(len)
+            .zip(output.chunks_exact_mut(output_len))
+        {
+            prepare_chirp_convolution(in_chunk, &self.input_chirp, inner_input);
+            convolve_with_multiplier(
+                &*self.inner_fft,
+                &self.inner_fft_multiplier,
+                inner_input,
+                inner_scratch,
+            );
+            finish_chirp_convolution(inner_input, &self.output_chirp, out_chunk);
+        }
+    }
+}
+impl<T> Length for ChirpZ<T> {
+    fn len(&self) -> usize {
+        self.input_chirp.len()
+    }
+}
+
+// Plans an inner FFT that's large enough to compute a Chirp-Z transform with the given input and output lengths
+fn plan_inner_fft<T: FftNum>(
+    len: usize,
+    output_len: usize,
+    planner: &mut FftPlanner<T>,
+) -> Arc<dyn Fft<T>> {
+    assert!(
+        len > 0 && output_len > 0,
+        "The Chirp-Z transform requires non-empty inputs and outputs. Got len = {}, output_len = {}",
+        len,
+        output_len
+    );
+    let inner_fft_len = (len + output_len - 1).checked_next_power_of_two().unwrap();
+    planner.plan_fft_forward(inner_fft_len)
+}
+
+// The angle of the chirp `w^(i^2 / 2)`, in turns, reduced modulo one turn. Computing `turns * i^2 / 2` in floating point and then
+// reducing it loses precision as i^2 grows, so this reduces i^2 with exact integer arithmetic first
+enum ChirpPhase {
+    // The step is `numerator / denominator` turns, so the chirp repeats every `2 * denominator` values of i^2,
+    // the same way Bluestein's algorithm reduces its twiddle indexes modulo `2 * len`
+    Rational {
+        numerator: i64,
+        denominator: u64,
+    },
+    // The step is `mantissa * 2^exponent` turns, exactly as represented by the f64
+    Exact {
+        mantissa: u64,
+        exponent: i16,
+        sign: i8,
+    },
+}
+
+impl ChirpPhase {
+    // Steps closer than this many ulps to a fraction with a small denominator are treated as that fraction
+    const RATIONAL_TOLERANCE_ULPS: f64 = 4f64;
+    const MAX_DENOMINATOR: u64 = 1 << 24;
+
+    fn new(turns: f64) -> Self {
+        match Self::find_fraction(turns) {
+            Some((numerator, denominator)) => ChirpPhase::Rational {
+                numerator,
+                denominator,
+            },
+            None => {
+                let (mantissa, exponent, sign) = num_traits::Float::integer_decode(turns);
+                ChirpPhase::Exact {
+                    mantissa,
+                    exponent,
+                    sign,
+                }
+            }
+        }
+    }
+
+    // Finds the fraction with the smallest denominator that's within a few ulps of `turns`, by walking its continued fraction
+    fn find_fraction(turns: f64) -> Option<(i64, u64)> {
+        if !turns.is_finite() || turns.abs() >= Self::MAX_DENOMINATOR as f64 {
+            return None;
+        }
+        let tolerance = Self::RATIONAL_TOLERANCE_ULPS * std::f64::EPSILON * turns.abs();
+
+        let target = turns.abs();
+        let mut remainder = target;
+        let (mut numerator, mut previous_numerator) = (remainder.floor() as u64, 1u64);
+        let (mut denominator, mut previous_denominator) = (1u64, 0u64);
+        loop {
+            if (target - numerator as f64 / denominator as f64).abs() <= tolerance {
+                let numerator = numerator as i64;
+                return Some((
+                    if turns < 0f64 { -numerator } else { numerator },
+                    denominator,
+                ));
+            }
+
+            let fractional = remainder - remainder.floor();
+            if fractional == 0f64 {
+                return None;
+            }
+            remainder = 1f64 / fractional;
+            let term = remainder.floor() as u64;
+
+            let next_denominator = term
+                .checked_mul(denominator)
+                .and_then(|d| d.checked_add(previous_denominator))
+                .filter(|&d| d <= Self::MAX_DENOMINATOR)?;
+            let next_numerator = term * numerator + previous_numerator;
+            previous_numerator = numerator;
+            previous_denominator = denominator;
+            numerator = next_numerator;
+            denominator = next_denominator;
+        }
+    }
+
+    // Returns `turns * i^2 / 2`, modulo one turn
+    fn half_square_turns(&self, i: usize) -> f64 {
+        match *self {
+            ChirpPhase::Rational {
+                numerator,
+                denominator,
+            } => {
+                let period = 2 * denominator as u128;
+                let i = i as u128 % period;
+                let index = (numerator.abs() as u128 % period) * (i * i % period) % period;
+                let turns = index as f64 / period as f64;
+                if numerator < 0 {
+                    -turns
+                } else {
+                    turns
+                }
+            }
+            ChirpPhase::Exact {
+                mantissa,
+                exponent,
+                sign,
+            } => {
+                // turns * i^2 / 2 = mantissa * i^2 / 2^shift, so only the low `shift` bits of mantissa * i^2 contribute to the fraction
+                let square = i as u128 * i as u128;
+                let shift = 1 - exponent as i32;
+                let turns = if shift <= 0 {
+                    0f64
+                } else if shift <= 128 {
+                    // Multiplication modulo 2^128 keeps the low bits exact
+                    let product = (mantissa as u128).wrapping_mul(square);
+                    let mask = if shift == 128 {
+                        u128::max_value()
+                    } else {
+                        (1u128 << shift) - 1
+                    };
+                    (product & mask) as f64 * 2f64.powi(-shift)
+                } else {
+                    // The step is so small that the angle never reaches a whole turn
+                    mantissa as f64 * square as f64 * 2f64.powi(-shift)
+                };
+                sign as f64 * turns
+            }
+        }
+    }
+}
+
+/// Computes a high-resolution spectrum of a narrow frequency band, using the Chirp-Z transform
+///
+/// A FFT of size N computes N frequencies evenly spaced across the entire spectrum. `ZoomFft` instead computes M frequencies evenly spaced
+/// across a chosen band, so the band can be resolved far more finely than the FFT's bin spacing, without zero-padding the input to a huge size.
+///
+/// Frequencies are expressed in cycles per sample, so bin `k` of a size-N FFT corresponds to the frequency `k / N`. For a frequency band
+/// `start..end`, output `j` is the DFT of the input evaluated at the frequency `start + j * (end - start) / M`. This uses the same sign
+/// convention as [`Fft`](crate::Fft) for the given direction, so wherever that frequency lands exactly on a FFT bin, the output matches that bin.
+///
+/// ~~~
+/// // Compute 1000 frequencies between 0.1 and 0.11 cycles per sample, from an input of size 4096
+/// use rustfft::chirp_z::ZoomFft;
+/// use rustfft::{FftDirection, FftPlanner};
+/// use rustfft::num_complex::Complex;
+///
+/// let input = vec![Complex{ re: 0.0f32, im: 0.0f32 }; 4096];
+/// let mut output = vec![Complex{ re: 0.0f32, im: 0.0f32 }; 1000];
+///
+/// let mut planner = FftPlanner::new();
+/// let zoom = ZoomFft::with_planner(4096, 0.1..0.11, 1000, FftDirection::Forward, &mut planner);
+/// zoom.process(&input, &mut output);
+/// ~~~
+pub struct ZoomFft<T> {
+    czt: ChirpZ<T>,
+
+    start_frequency: f64,
+    frequency_step: f64,
+
+    direction: FftDirection,
+}
+
+impl<T: FftNum> ZoomFft<T> {
+    /// Creates a zoom FFT which will process inputs of size `len`, and output `output_len` evenly spaced frequencies from the band
+    /// `frequencies`. The start of the band is included in the output, and the end of the band is excluded.
+    ///
+    /// `inner_fft.len()` must be >= `len + output_len - 1`. The inner FFT may be either direction.
+    ///
+    /// # Panics
+    /// Panics if `len == 0`, if `output_len == 0`, if either end of `frequencies` isn't finite, or if `inner_fft.len() < len + output_len - 1`.
+    pub fn new(
+        len: usize,
+        frequencies: Range<f64>,
+        output_len: usize,
+        direction: FftDirection,
+        inner_fft: Arc<dyn Fft<T>>,
+    ) -> Self {
+        assert!(
+            len > 0 && output_len > 0,
+            "The zoom FFT requires non-empty inputs and outputs. Got len = {}, output_len = {}",
+            len,
+            output_len
+        );
+        assert!(
+            frequencies.start.is_finite() && frequencies.end.is_finite(),
+            "The zoom FFT requires a finite frequency band. Got {:?}",
+            frequencies
+        );
+
+        // Each output is the z-transform evaluated at exp(sign * 2pi * i * frequency), so walk a contour along the unit circle
+        let start_frequency = frequencies.start;
+        let frequency_step = (frequencies.end - frequencies.start) / output_len as f64;
+        let sign = match direction {
+            FftDirection::Forward => -1f64,
+            FftDirection::Inverse => 1f64,
+        };
+        let angle_per_frequency = sign * 2f64 * std::f64::consts::PI;
+
+        let ln_a = Complex::new(0f64, -angle_per_frequency * start_frequency);
+        let w_turns = sign * frequency_step;
+
+        Self {
+            czt: ChirpZ::from_logarithms(len, output_len, ln_a, 0f64, w_turns, inner_fft),
+
+            start_frequency,
+            frequency_step,
+
+            direction,
+        }
+    }
+
+    /// Creates a zoom FFT like [`new`](ZoomFft::new), using `planner` to plan the inner FFT
+    pub fn with_planner(
+        len: usize,
+        frequencies: Range<f64>,
+        output_len: usize,
+        direction: FftDirection,
+        planner: &mut FftPlanner<T>,
+    ) -> Self {
+        let inner_fft = plan_inner_fft(len, output_len, planner);
+        Self::new(len, frequencies, output_len, direction, inner_fft)
+    }
+
+    /// Returns the number of frequencies computed for each input chunk
+    pub fn output_len(&self) -> usize {
+        self.czt.output_len()
+    }
+
+    /// Returns the frequency, in cycles per sample, of output element `index`
+    pub fn frequency(&self, index: usize) -> f64 {
+        self.start_frequency + index as f64 * self.frequency_step
+    }
+
+    /// Returns the size of the scratch buffer required by `process_with_scratch`
+    pub fn get_scratch_len(&self) -> usize {
+        self.czt.get_scratch_len()
+    }
+
+    /// Divides `input` into chunks of size `self.len()`, and `output` into chunks of size `self.output_len()`,
+    /// and computes the spectrum of each input chunk into the corresponding output chunk.
+    ///
+    /// Convenience method that allocates a `Vec` with the required scratch space and calls `self.process_with_scratch`.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if:
+    /// - `input.len() % self.len() > 0`
+    /// - `input.len() / self.len() != output.len() / self.output_len()`
+    /// - `output.len() % self.output_len() > 0`
+    pub fn process(&self, input: &[Complex<T>], output: &mut [Complex<T>]) {
+        self.czt.process(input, output);
+    }
+
+    /// Divides `input` into chunks of size `self.len()`, and `output` into chunks of size `self.output_len()`,
+    /// and computes the spectrum of each input chunk into the corresponding output chunk.
+    ///
+    /// Uses the `scratch` buffer as scratch space, so the contents of `scratch` should be considered garbage
+    /// after calling.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if:
+    /// - `input.len() % self.len() > 0`
+    /// - `input.len() / self.len() != output.len() / self.output_len()`
+    /// - `output.len() % self.output_len() > 0`
+    /// - `scratch.len() < self.get_scratch_len()`
+    pub fn process_with_scratch(
+        &self,
+        input: &[Complex<T>],
+        output: &mut [Complex<T>],
+        scratch: &mut [Complex<T>],
+    ) {
+        self.czt.process_with_scratch(input, output, scratch);
+    }
+}
+impl<T> Length for ZoomFft<T> {
+    fn len(&self) -> usize {
+        self.czt.len()
+    }
+}
+impl<T> Direction for ZoomFft<T> {
+    fn fft_direction(&self) -> FftDirection {
+        self.direction
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::algorithm::{Dft, Radix4};
+    use crate::test_utils::{compare_vectors, random_signal};
+
+    // Evaluates the Chirp-Z transform directly from its definition
+    fn naive_czt(
+        input: &[Complex<f64>],
+        output_len: usize,
+        a: Complex<f64>,
+        w: Complex<f64>,
+    ) -> Vec<Complex<f64>> {
+        (0..output_len)
+            .map(|k| {
+                let z = a * w.powi(-(k as i32));
+                input
+                    .iter()
+                    .enumerate()
+                    .map(|(n, x)| x * z.powi(-(n as i32)))
+                    .sum()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_chirp_z_matches_fft() {
+        for len in 1usize..20 {
+            for &direction in &[FftDirection::Forward, FftDirection::Inverse] {
+                let sign = match direction {
+                    FftDirection::Forward => -1f64,
+                    FftDirection::Inverse => 1f64,
+                };
+                let w = Complex::from_polar(1f64, sign * 2f64 * std::f64::consts::PI / len as f64);
+                let inner_fft = Arc::new(Radix4::new(
+                    (2 * len - 1).checked_next_power_of_two().unwrap(),
+                    direction,
+                ));
+                let czt = ChirpZ::new(len, len, Complex::new(1f64, 0f64), w, inner_fft);
+
+                let input: Vec<Complex<f64>> = random_signal(len * 3);
+                let mut expected = input.clone();
+                Dft::new(len, direction).process(&mut expected);
+
+                let mut actual = vec![Complex::zero(); len * 3];
+                czt.process(&input, &mut actual);
+                assert!(
+                    compare_vectors(&expected, &actual),
+                    "len = {}, direction = {}",
+                    len,
+                    direction
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_chirp_z_spiral() {
+        // Arbitrary spirals, with output lengths both smaller and larger than the input
+        let contours = [
+            (
+                Complex::from_polar(0.9, 0.3),
+                Complex::from_polar(1.01, -0.05),
+            ),
+            (
+                Complex::from_polar(1.1, -1.0),
+                Complex::from_polar(0.995, 0.11),
+            ),
+            (Complex::new(1f64, 0f64), Complex::from_polar(1f64, -0.01)),
+        ];
+        let mut planner = FftPlanner::new();
+        for &(len, output_len) in &[(1, 1), (7, 30), (30, 7), (25, 25)] {
+            for &(a, w) in &contours {
+                let input: Vec<Complex<f64>> = random_signal(len);
+                let expected = naive_czt(&input, output_len, a, w);
+
+                let czt = ChirpZ::with_planner(len, output_len, a, w, &mut planner);
+                assert_eq!(czt.len(), len);
+                assert_eq!(czt.output_len(), output_len);
+
+                let mut actual = vec![Complex::zero(); output_len];
+                czt.process(&input, &mut actual);
+                assert!(
+                    compare_vectors(&expected, &actual),
+                    "len = {}, output_len = {}, a = {}, w = {}",
+                    len,
+                    output_len,
+                    a,
+                    w
+                );
+            }
+        }
+    }
+
+    // Evaluates the DTFT of `input` at `frequency` directly, using the same sign convention as the FFT
+    fn naive_dtft(input: &[Complex<f64>], frequency: f64, direction: FftDirection) -> Complex<f64> {
+        let sign = match direction {
+            FftDirection::Forward => -1f64,
+            FftDirection::Inverse => 1f64,
+        };
+        input
+            .iter()
+            .enumerate()
+            .map(|(n, x)| {
+                x * Complex::from_polar(
+                    1f64,
+                    sign * 2f64 * std::f64::consts::PI * frequency * n as f64,
+                )
+            })
+            .sum()
+    }
+
+    fn assert_close(expected: &[Complex<f64>], actual: &[Complex<f64>], tolerance: f64) {
+        let max_expected = expected.iter().map(|x| x.norm()).fold(0f64, f64::max);
+        for (i, (e, a)) in expected.iter().zip(actual.iter()).enumerate() {
+            assert!(
+                (e - a).norm() <= tolerance * max_expected,
+                "index = {}, expected {}, got {}",
+                i,
+                e,
+                a
+            );
+        }
+    }
+
+    #[test]
+    fn test_zoom_fft_matches_dft_bins() {
+        // Zooming in on a range that lines up with the DFT's bins should reproduce those bins
+        let len = 1000;
+        let input: Vec<Complex<f64>> = random_signal(len);
+        let mut planner = FftPlanner::new();
+
+        for &direction in &[FftDirection::Forward, FftDirection::Inverse] {
+            let mut expected = input.clone();
+            Dft::new(len, direction).process(&mut expected);
+
+            // Bins 100..200 at the DFT's spacing, then the same bins at 4x finer spacing
+            let zoom = ZoomFft::with_planner(len, 0.1..0.2, 100, direction, &mut planner);
+            let mut actual = vec![Complex::zero(); zoom.output_len()];
+            zoom.process(&input, &mut actual);
+            assert_close(&expected[100..200], &actual, 1e-10);
+
+            let zoom = ZoomFft::with_planner(len, 0.1..0.2, 400, direction, &mut planner);
+            let mut actual = vec![Complex::zero(); zoom.output_len()];
+            zoom.process(&input, &mut actual);
+            let every_fourth: Vec<_> = actual.iter().step_by(4).cloned().collect();
+            assert_close(&expected[100..200], &every_fourth, 1e-10);
+        }
+    }
+
+    #[test]
+    fn test_zoom_fft_matches_dtft() {
+        let mut planner = FftPlanner::new();
+        for &(len, output_len, start, end) in &[
+            (1, 5, 0.0, 0.5),
+            (64, 300, 0.123, 0.1234),
+            (333, 17, -0.3, 0.2),
+            (500, 500, 0.9, 0.1),
+        ] {
+            for &direction in &[FftDirection::Forward, FftDirection::Inverse] {
+                let input: Vec<Complex<f64>> = random_signal(len);
+                let zoom =
+                    ZoomFft::with_planner(len, start..end, output_len, direction, &mut planner);
+
+                let expected: Vec<_> = (0..output_len)
+                    .map(|j| naive_dtft(&input, zoom.frequency(j), direction))
+                    .collect();
+                let mut actual = vec![Complex::zero(); output_len];
+                zoom.process(&input, &mut actual);
+                assert_close(&expected, &actual, 1e-10);
+            }
+        }
+    }
+
+    #[test]
+    fn test_chirp_z_large_len() {
+        // The chirp angles grow with i^2, so large sizes need them reduced exactly to stay accurate
+        let len = 50_000;
+        let mut planner = FftPlanner::new();
+        let input: Vec<Complex<f64>> = random_signal(len);
+        for &direction in &[FftDirection::Forward, FftDirection::Inverse] {
+            let mut expected = input.clone();
+            planner.plan_fft(len, direction).process(&mut expected);
+
+            let sign = match direction {
+                FftDirection::Forward => -1f64,
+                FftDirection::Inverse => 1f64,
+            };
+            let w = Complex::from_polar(1f64, sign * 2f64 * std::f64::consts::PI / len as f64);
+            let czt = ChirpZ::with_planner(len, len, Complex::new(1f64, 0f64), w, &mut planner);
+            let mut actual = vec![Complex::zero(); len];
+            czt.process(&input, &mut actual);
+            assert_close(&expected, &actual, 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_zoom_fft_large_len() {
+        // A frequency step that isn't a fraction with a small denominator, over a large input and output
+        let len = 60_000;
+        let output_len = 50_000;
+        let (start, end) = (0.1234567, 0.3456789);
+        let mut planner = FftPlanner::new();
+        let input: Vec<Complex<f64>> = random_signal(len);
+        for &direction in &[FftDirection::Forward, FftDirection::Inverse] {
+            let zoom = ZoomFft::with_planner(len, start..end, output_len, direction, &mut planner);
+            let mut actual = vec![Complex::zero(); output_len];
+            zoom.process(&input, &mut actual);
+
+            // The naive DTFT is too slow to check every output, so check a spread of them, weighted towards the end where i^2 is largest
+            let indexes: Vec<usize> = (0..20).map(|j| output_len - 1 - j * j * 100).collect();
+            let expected: Vec<_> = indexes
+                .iter()
+                .map(|&j| naive_dtft(&input, zoom.frequency(j), direction))
+                .collect();
+            let actual: Vec<_> = indexes.iter().map(|&j| actual[j]).collect();
+            assert_close(&expected, &actual, 1e-10);
+        }
+    }
+
+    #[test]
+    fn test_chirp_phase() {
+        // Steps that are fractions with small denominators are reduced modulo 2 * denominator
+        match ChirpPhase::new(-1f64 / 3f64) {
+            ChirpPhase::Rational {
+                numerator,
+                denominator,
+            } => assert_eq!((numerator, denominator), (-1, 3)),
+            ChirpPhase::Exact { .. } => panic!("Expected -1/3 to be treated as a fraction"),
+        }
+        let phase = ChirpPhase::new(1f64 / 50_000f64);
+        for &i in &[0usize, 1, 7, 99_999, 100_000, 123_456_789] {
+            let expected = ((i as u128 * i as u128) % 100_000) as f64 / 100_000f64;
+            assert_eq!(phase.half_square_turns(i), expected, "i = {}", i);
+        }
+
+        // Other steps are reduced exactly as represented by the f64
+        let turns = 0.1234567e-3;
+        let phase = ChirpPhase::new(turns);
+        match phase {
+            ChirpPhase::Exact { .. } => {}
+            ChirpPhase::Rational { .. } => panic!("Expected {} to be reduced exactly", turns),
+        }
+        for &i in &[0usize, 1, 1000, 99_999] {
+            let expected = turns * (i * i) as f64 / 2f64;
+            let expected = expected - expected.floor();
+            let actual = phase.half_square_turns(i);
+            assert!(
+                (actual - expected).abs() < 1e-9,
+                "i = {}, expected {}, got {}",
+                i,
+                expected,
+                actual
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "The Chirp-Z transform requires non-empty inputs and outputs")]
+    fn test_chirp_z_with_planner_empty() {
+        let mut planner = FftPlanner::<f32>::new();
+        ChirpZ::with_planner(
+            0,
+            0,
+            Complex::new(1f64, 0f64),
+            Complex::new(0f64, 1f64),
+            &mut planner,
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_chirp_z_inner_fft_too_small() {
+        let inner_fft = Arc::new(Dft::new(15, FftDirection::Forward));
+        let _: ChirpZ<f32> = ChirpZ::new(
+            8,
+            9,
+            Complex::new(1f64, 0f64),
+            Complex::new(0f64, 1f64),
+            inner_fft,
+        );
+    }
+}
@@ -37,6 +37,27 @@ pub fn fft_error_inplace(
     );
 }
 
+// Prints an error raised by a FFT algorithm's `process_split_with_scratch` method
+// Marked cold and inline never to keep all formatting code out of the many monomorphized process_split_with_scratch methods
+#[cold]
+#[inline(never)]
+pub fn fft_error_split(
+    expected_len: usize,
+    actual_real: usize,
+    actual_imag: usize,
+    expected_scratch: usize,
+    actual_scratch: usize,
+) {
+    assert_eq!(
+        actual_real,
+        actual_imag,
+        "Real and imaginary buffers must have the same length. Got real len = {}, imaginary len = {}",
+        actual_real,
+        actual_imag
+    );
+    fft_error_inplace(expected_len, actual_real, expected_scratch, actual_scratch);
+}
+
 // Prints an error raised by an in-place FFT algorithm's `process_inplace` method
 // Marked cold and inline never to keep all formatting code out of the many monomorphized process_inplace methods
 #[cold]
@@ -71,7 +92,7 @@ pub fn fft_error_outofplace(
 }
 
 macro_rules! boilerplate_fft_oop {
-    ($struct_name:ident, $len_fn:expr) => {
+    ($struct_name:ident, $len_fn:expr, $own_heap_bytes_fn:expr, $inner_ffts_fn:expr) => {
         impl<T: FftNum> Fft<T> for $struct_name<T> {
             fn process_outofplace_with_scratch(
                 &self,
@@ -138,6 +159,70 @@ macro_rules! boilerplate_fft_oop {
                     );
                 }
             }
+            fn process_split_with_scratch(
+                &self,
+                real: &mut [T],
+                imag: &mut [T],
+                scratch: &mut [Complex<T>],
+            ) {
+                let required_scratch = self.get_split_scratch_len();
+                if real.len() != imag.len() {
+                    // We want to trigger a panic, but we want to avoid doing it in this function to reduce code size, so call a function marked cold and inline(never) that will do it for us
+                    fft_error_split(
+                        self.len(),
+                        real.len(),
+                        imag.len(),
+                        required_scratch,
+                        scratch.len(),
+                    );
+                    return; // Unreachable, because fft_error_split asserts, but it helps codegen to put it here
+                }
+                if self.len() == 0 {
+                    return;
+                }
+                if scratch.len() < required_scratch || real.len() < self.len() {
+                    // We want to trigger a panic, but we want to avoid doing it in this function to reduce code size, so call a function marked cold and inline(never) that will do it for us
+                    fft_error_split(
+                        self.len(),
+                        real.len(),
+                        imag.len(),
+                        required_scratch,
+                        scratch.len(),
+                    );
+                    return; // Unreachable, because fft_error_split asserts, but it helps codegen to put it here
+                }
+
+                // The scratch holds the real parts of the output in its first half, and the imaginary parts in its second half
+                let (scratch_real, scratch_imag) =
+                    array_utils::split_scratch(&mut scratch[..required_scratch]);
+                let result = array_utils::iter_chunks_zipped(
+                    real,
+                    imag,
+                    self.len(),
+                    |real_chunk, imag_chunk| {
+                        self.perform_split_fft_out_of_place(
+                            real_chunk,
+                            imag_chunk,
+                            scratch_real,
+                            scratch_imag,
+                        );
+                        real_chunk.copy_from_slice(scratch_real);
+                        imag_chunk.copy_from_slice(scratch_imag);
+                    },
+                );
+
+                if result.is_err() {
+                    // We want to trigger a panic, because the buffer sizes weren't cleanly divisible by the FFT size,
+                    // but we want to avoid doing it in this function to reduce code size, so call a function marked cold and inline(never) that will do it for us
+                    fft_error_split(
+                        self.len(),
+                        real.len(),
+                        imag.len(),
+                        required_scratch,
+                        scratch.len(),
+                    );
+                }
+            }
             #[inline(always)]
             fn get_inplace_scratch_len(&self) -> usize {
                 self.len()
@@ -146,6 +231,17 @@ macro_rules! boilerplate_fft_oop {
             fn get_outofplace_scratch_len(&self) -> usize {
                 0
             }
+            #[inline(always)]
+            fn get_split_scratch_len(&self) -> usize {
+                // `self.len()` complex elements hold `self.len()` real parts followed by `self.len()` imaginary parts
+                self.len()
+            }
+            fn own_heap_bytes(&self) -> usize {
+                $own_heap_bytes_fn(self)
+            }
+            fn inner_ffts(&self) -> Vec<std::sync::Arc<dyn Fft<T>>> {
+                $inner_ffts_fn(self)
+            }
         }
         impl<T> Length for $struct_name<T> {
             #[inline(always)]
@@ -163,7 +259,7 @@ macro_rules! boilerplate_fft_oop {
 }
 
 macro_rules! boilerplate_fft {
-    ($struct_name:ident, $len_fn:expr, $inplace_scratch_len_fn:expr, $out_of_place_scratch_len_fn:expr) => {
+    ($struct_name:ident, $len_fn:expr, $inplace_scratch_len_fn:expr, $out_of_place_scratch_len_fn:expr, $own_heap_bytes_fn:expr, $inner_ffts_fn:expr) => {
         impl<T: FftNum> Fft<T> for $struct_name<T> {
             fn process_outofplace_with_scratch(
                 &self,
@@ -254,6 +350,72 @@ macro_rules! boilerplate_fft {
             fn get_outofplace_scratch_len(&self) -> usize {
                 $out_of_place_scratch_len_fn(self)
             }
+            fn own_heap_bytes(&self) -> usize {
+                $own_heap_bytes_fn(self)
+            }
+            fn inner_ffts(&self) -> Vec<std::sync::Arc<dyn Fft<T>>> {
+                $inner_ffts_fn(self)
+            }
+        }
+        impl<T: FftNum> Length for $struct_name<T> {
+            #[inline(always)]
+            fn len(&self) -> usize {
+                $len_fn(self)
+            }
+        }
+        impl<T: FftNum> Direction for $struct_name<T> {
+            #[inline(always)]
+            fn fft_direction(&self) -> FftDirection {
+                self.direction
+            }
+        }
+    };
+}
+
+macro_rules! boilerplate_output_pruned_fft {
+    ($struct_name:ident, $len_fn:expr, $inplace_scratch_len_fn:expr, $computes_output_fn:expr) => {
+        impl<T: FftNum> OutputPrunedFft<T> for $struct_name<T> {
+            #[inline(always)]
+            fn computes_output(&self, index: usize) -> bool {
+                $computes_output_fn(self, index)
+            }
+            fn process_with_scratch(&self, buffer: &mut [Complex<T>], scratch: &mut [Complex<T>]) {
+                if self.len() == 0 {
+                    return;
+                }
+
+                let required_scratch = self.get_inplace_scratch_len();
+                if scratch.len() < required_scratch || buffer.len() < self.len() {
+                    // We want to trigger a panic, but we want to avoid doing it in this function to reduce code size, so call a function marked cold and inline(never) that will do it for us
+                    fft_error_inplace(
+                        self.len(),
+                        buffer.len(),
+                        self.get_inplace_scratch_len(),
+                        scratch.len(),
+                    );
+                    return; // Unreachable, because fft_error_inplace asserts, but it helps codegen to put it here
+                }
+
+                let scratch = &mut scratch[..required_scratch];
+                let result = array_utils::iter_chunks(buffer, self.len(), |chunk| {
+                    self.perform_fft_inplace(chunk, scratch)
+                });
+
+                if result.is_err() {
+                    // We want to trigger a panic, because the buffer sizes weren't cleanly divisible by the FFT size,
+                    // but we want to avoid doing it in this function to reduce code size, so call a function marked cold and inline(never) that will do it for us
+                    fft_error_inplace(
+                        self.len(),
+                        buffer.len(),
+                        self.get_inplace_scratch_len(),
+                        scratch.len(),
+                    );
+                }
+            }
+            #[inline(always)]
+            fn get_inplace_scratch_len(&self) -> usize {
+                $inplace_scratch_len_fn(self)
+            }
         }
         impl<T: FftNum> Length for $struct_name<T> {
             #[inline(always)]
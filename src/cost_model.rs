@@ -0,0 +1,492 @@
+use crate::plan::Recipe;
+
+// Default coefficients, measured for f32 FFTs on a desktop x86_64 CPU, in roughly nanoseconds
+const BUTTERFLY_COST: f64 = 0.7; // per element per bit of length
+const PRIME_BUTTERFLY_COST: f64 = 0.27; // per squared length
+const DFT_COST: f64 = 1.9; // per squared length
+const RADIX4_COST: f64 = 0.78; // per element per bit of length
+const MIXED_RADIX_COST: f64 = 2.5; // per element
+const MIXED_RADIX_SMALL_COST: f64 = 1.5; // per element
+const GOOD_THOMAS_COST: f64 = 2.0; // per element
+const GOOD_THOMAS_SMALL_COST: f64 = 0.8; // per element
+const RADERS_COST: f64 = 11.0; // per element
+const BLUESTEINS_COST: f64 = 1.5; // per element of the inner FFT
+const MEMORY_PASS_COST: f64 = 2.0; // per element per pass over memory, for FFTs that don't fit in the cache
+const CACHE_LEN: usize = 1 << 16; // number of complex elements that fit in the cache
+
+// The number of passes over memory that each algorithm makes, when counting its cost outside the cache.
+// Radix4 makes one pass per layer, while the transposes and index remapping of the split algorithms are strided, so they count as several passes
+const MIXED_RADIX_PASSES: f64 = 6.0;
+const GOOD_THOMAS_PASSES: f64 = 8.0;
+const RADERS_PASSES: f64 = 4.0;
+const BLUESTEINS_PASSES: f64 = 3.0;
+
+/// Estimates how long FFT algorithms take, so that a planner can search for the fastest combination of algorithms for a size.
+///
+/// Pass a cost model to [`PlannerOptions::cost_model`](crate::PlannerOptions::cost_model) to make the scalar planner search every way of
+/// splitting a size into factors, and every inner FFT size for Rader's and Bluestein's algorithms, and choose the combination with the lowest
+/// estimated cost, instead of following its built-in heuristics.
+///
+/// The cost of a composite algorithm is the cost of its inner FFTs, plus an overhead for the algorithm itself: a MixedRadix FFT of size `w * h` costs
+/// `h` FFTs of size `w`, `w` FFTs of size `h`, and `mixed_radix` per element. Costs don't have a unit, but the default coefficients were
+/// measured in nanoseconds for `f32` FFTs on a desktop x86_64 CPU. They're a reasonable starting point for other machines, but to get the best plans,
+/// time FFTs on the target machine, compare them to [`FftPlannerScalar::estimated_cost`](crate::FftPlannerScalar::estimated_cost), and adjust the coefficients.
+///
+/// ~~~
+/// // Search for the cheapest plan, on a machine where Good-Thomas is relatively slow
+/// use rustfft::{CostModel, FftPlannerScalar, PlannerOptions};
+///
+/// let cost_model = CostModel::new().good_thomas(3.0).good_thomas_small(1.5);
+///
+/// let mut planner = FftPlannerScalar::<f32>::with_options(PlannerOptions::new().cost_model(cost_model));
+/// let fft = planner.plan_fft_forward(1234);
+/// ~~~
+#[derive(Clone, Debug, PartialEq)]
+pub struct CostModel {
+    butterfly: f64,
+    prime_butterfly: f64,
+    dft: f64,
+    radix4: f64,
+    mixed_radix: f64,
+    mixed_radix_small: f64,
+    good_thomas: f64,
+    good_thomas_small: f64,
+    raders: f64,
+    bluesteins: f64,
+    memory_pass: f64,
+    cache_len: usize,
+}
+
+// The setters reject NaN, so every coefficient is equal to itself
+impl Eq for CostModel {}
+
+impl CostModel {
+    /// Creates a cost model with the default coefficients
+    pub fn new() -> Self {
+        Self {
+            butterfly: BUTTERFLY_COST,
+            prime_butterfly: PRIME_BUTTERFLY_COST,
+            dft: DFT_COST,
+            radix4: RADIX4_COST,
+            mixed_radix: MIXED_RADIX_COST,
+            mixed_radix_small: MIXED_RADIX_SMALL_COST,
+            good_thomas: GOOD_THOMAS_COST,
+            good_thomas_small: GOOD_THOMAS_SMALL_COST,
+            raders: RADERS_COST,
+            bluesteins: BLUESTEINS_COST,
+            memory_pass: MEMORY_PASS_COST,
+            cache_len: CACHE_LEN,
+        }
+    }
+
+    /// Sets the cost of a butterfly whose size isn't a prime of 5 or more, per element per bit of length: a butterfly of size `n` costs `cost * n * log2(n)`. Defaults to 0.7.
+    ///
+    /// # Panics
+    /// Panics if `cost` is negative, infinite, or NaN. The other cost setters panic in the same cases.
+    pub fn butterfly(mut self, cost: f64) -> Self {
+        self.butterfly = checked_cost(cost);
+        self
+    }
+
+    /// Sets the cost of a butterfly whose size is a prime of 5 or more, per squared length: a butterfly of prime size `p` costs `cost * p * p`. Defaults to 0.27.
+    pub fn prime_butterfly(mut self, cost: f64) -> Self {
+        self.prime_butterfly = checked_cost(cost);
+        self
+    }
+
+    /// Sets the cost of the naive `Dft`, per squared length. Defaults to 1.9.
+    pub fn dft(mut self, cost: f64) -> Self {
+        self.dft = checked_cost(cost);
+        self
+    }
+
+    /// Sets the cost of `Radix4`, per element per bit of length. Defaults to 0.78.
+    pub fn radix4(mut self, cost: f64) -> Self {
+        self.radix4 = checked_cost(cost);
+        self
+    }
+
+    /// Sets the overhead of `MixedRadix` per element, on top of its inner FFTs. Defaults to 2.5.
+    pub fn mixed_radix(mut self, cost: f64) -> Self {
+        self.mixed_radix = checked_cost(cost);
+        self
+    }
+
+    /// Sets the overhead of `MixedRadixSmall` per element, on top of its inner FFTs. Defaults to 1.5.
+    pub fn mixed_radix_small(mut self, cost: f64) -> Self {
+        self.mixed_radix_small = checked_cost(cost);
+        self
+    }
+
+    /// Sets the overhead of `GoodThomasAlgorithm` per element, on top of its inner FFTs. Defaults to 2.0.
+    pub fn good_thomas(mut self, cost: f64) -> Self {
+        self.good_thomas = checked_cost(cost);
+        self
+    }
+
+    /// Sets the overhead of `GoodThomasAlgorithmSmall` per element, on top of its inner FFTs. Defaults to 0.8.
+    pub fn good_thomas_small(mut self, cost: f64) -> Self {
+        self.good_thomas_small = checked_cost(cost);
+        self
+    }
+
+    /// Sets the overhead of `RadersAlgorithm` per element, on top of its two inner FFTs. Defaults to 11.
+    pub fn raders(mut self, cost: f64) -> Self {
+        self.raders = checked_cost(cost);
+        self
+    }
+
+    /// Sets the overhead of `BluesteinsAlgorithm` per element of its inner FFT, on top of its two inner FFTs. Defaults to 1.5.
+    pub fn bluesteins(mut self, cost: f64) -> Self {
+        self.bluesteins = checked_cost(cost);
+        self
+    }
+
+    /// Sets the extra cost per element of each pass that an algorithm makes over a buffer that doesn't fit in the cache. Defaults to 2.0.
+    ///
+    /// Radix4 makes one pass per layer of radix-4 butterflies. The transposes and index remapping of the other algorithms access memory with
+    /// large strides, so they count as several passes: 6 for MixedRadix, 8 for Good-Thomas, 4 for Rader's algorithm, and 3 for Bluestein's algorithm.
+    pub fn memory_pass(mut self, cost: f64) -> Self {
+        self.memory_pass = checked_cost(cost);
+        self
+    }
+
+    /// Sets the number of complex elements that fit in the cache. FFTs larger than this pay the [`memory_pass`](crate::CostModel::memory_pass) cost. Defaults to 65536.
+    pub fn cache_len(mut self, len: usize) -> Self {
+        self.cache_len = len;
+        self
+    }
+
+    /// Returns the estimated cost of the FFT described by `recipe`, including its inner FFTs
+    pub(crate) fn recipe_cost(&self, recipe: &Recipe) -> f64 {
+        let len = recipe.len();
+        let n = len as f64;
+        match recipe {
+            Recipe::Dft(_) => self.dft * n * n,
+            Recipe::Radix4(_) => {
+                let bits = n.log2();
+                self.radix4 * n * bits + self.memory_cost(len, (bits / 2.0).ceil())
+            }
+            Recipe::MixedRadix {
+                left_fft,
+                right_fft,
+            } => {
+                self.split_cost(left_fft, right_fft)
+                    + self.mixed_radix * n
+                    + self.memory_cost(len, MIXED_RADIX_PASSES)
+            }
+            Recipe::GoodThomasAlgorithm {
+                left_fft,
+                right_fft,
+            } => {
+                self.split_cost(left_fft, right_fft)
+                    + self.good_thomas * n
+                    + self.memory_cost(len, GOOD_THOMAS_PASSES)
+            }
+            Recipe::MixedRadixSmall {
+                left_fft,
+                right_fft,
+            } => self.split_cost(left_fft, right_fft) + self.mixed_radix_small * n,
+            Recipe::GoodThomasAlgorithmSmall {
+                left_fft,
+                right_fft,
+            } => self.split_cost(left_fft, right_fft) + self.good_thomas_small * n,
+            Recipe::RadersAlgorithm { inner_fft } => {
+                2.0 * self.recipe_cost(inner_fft)
+                    + self.raders * n
+                    + self.memory_cost(len, RADERS_PASSES)
+            }
+            Recipe::BluesteinsAlgorithm { inner_fft, .. } => {
+                let inner_len = inner_fft.len();
+                2.0 * self.recipe_cost(inner_fft)
+                    + self.bluesteins * inner_len as f64
+                    + self.memory_cost(inner_len, BLUESTEINS_PASSES)
+            }
+            Recipe::Butterfly5
+            | Recipe::Butterfly7
+            | Recipe::Butterfly11
+            | Recipe::Butterfly13
+            | Recipe::Butterfly17
+            | Recipe::Butterfly19
+            | Recipe::Butterfly23
+            | Recipe::Butterfly29
+            | Recipe::Butterfly31 => self.prime_butterfly * n * n,
+            _ => self.butterfly * n * n.log2(), // other butterflies
+        }
+    }
+
+    // A split FFT of size w * h computes h FFTs of size w, and w FFTs of size h
+    fn split_cost(&self, left_fft: &Recipe, right_fft: &Recipe) -> f64 {
+        right_fft.len() as f64 * self.recipe_cost(left_fft)
+            + left_fft.len() as f64 * self.recipe_cost(right_fft)
+    }
+
+    fn memory_cost(&self, len: usize, passes: f64) -> f64 {
+        if len > self.cache_len {
+            self.memory_pass * len as f64 * passes
+        } else {
+            0.0
+        }
+    }
+}
+
+impl Default for CostModel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An estimate of the work done by a FFT, computed from the algorithms that a planner chooses for it, without creating the FFT.
+/// Created by [`FftPlannerScalar::estimate`](crate::FftPlannerScalar::estimate).
+///
+/// The operation counts are for a single FFT, and don't include precomputing twiddle factors. They're based on the textbook operation counts
+/// of each algorithm, so they're estimates: the exact counts depend on how each algorithm is implemented, and how the compiler optimizes it.
+///
+/// ~~~
+/// // Find the cheapest size between 1000 and 1010, to pad a signal of size 1000
+/// use rustfft::FftPlannerScalar;
+///
+/// let mut planner = FftPlannerScalar::<f32>::new();
+/// let best_len = (1000..=1010)
+///     .min_by_key(|&len| planner.estimate(len).multiplications)
+///     .unwrap();
+/// assert_eq!(best_len, 1008);
+/// ~~~
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct FftEstimate {
+    /// The size of the FFT
+    pub len: usize,
+    /// The number of real multiplications
+    pub multiplications: u64,
+    /// The number of real additions and subtractions
+    pub additions: u64,
+    /// The number of passes over memory, where reading and writing every element of the FFT once counts as one pass
+    pub memory_passes: f64,
+    /// The in-place scratch length that the FFT will require, as returned by [`Fft::get_inplace_scratch_len`](crate::Fft::get_inplace_scratch_len)
+    pub inplace_scratch_len: usize,
+    /// The out-of-place scratch length that the FFT will require, as returned by [`Fft::get_outofplace_scratch_len`](crate::Fft::get_outofplace_scratch_len)
+    pub outofplace_scratch_len: usize,
+    /// The estimated cost of the FFT, according to the planner's [`CostModel`](crate::CostModel)
+    pub cost: f64,
+}
+impl FftEstimate {
+    pub(crate) fn from_recipe(recipe: &Recipe, cost_model: &CostModel) -> Self {
+        let counts = OperationCounts::from_recipe(recipe);
+        let len = recipe.len();
+        Self {
+            len,
+            multiplications: counts.multiplications,
+            additions: counts.additions,
+            memory_passes: if len > 0 {
+                counts.memory_elements / len as f64
+            } else {
+                0.0
+            },
+            inplace_scratch_len: recipe.inplace_scratch_len(),
+            outofplace_scratch_len: recipe.outofplace_scratch_len(),
+            cost: cost_model.recipe_cost(recipe),
+        }
+    }
+}
+
+// Real operations done by one FFT, and the number of elements it reads and writes, counting each element read and written once as one element
+#[derive(Copy, Clone, Debug)]
+struct OperationCounts {
+    multiplications: u64,
+    additions: u64,
+    memory_elements: f64,
+}
+impl OperationCounts {
+    fn new(multiplications: u64, additions: u64, passes: f64, len: usize) -> Self {
+        Self {
+            multiplications,
+            additions,
+            memory_elements: passes * len as f64,
+        }
+    }
+
+    fn from_recipe(recipe: &Recipe) -> Self {
+        let len = recipe.len();
+        let n = len as u64;
+        match recipe {
+            // Each output is a sum of n complex products
+            Recipe::Dft(_) => Self::new(4 * n * n, 4 * n * n, 1.0, len),
+            Recipe::Radix4(_) => {
+                // Radix4 transposes its input into bit-reversed order, computes butterflies of size 8 or 16,
+                // and then computes layers of radix-4 butterflies, each with 3 twiddle factors, on top of them
+                let bits = len.trailing_zeros();
+                if bits <= 2 {
+                    return Self::power_of_two_butterfly(len);
+                }
+                let base_bits = if bits % 2 == 1 { 3 } else { 4 };
+                let base = Self::power_of_two_butterfly(1 << base_bits).repeat(n >> base_bits);
+                let layers = u64::from((bits - base_bits) / 2);
+                let cross = Self::new(3 * n * layers, 22 * n / 4 * layers, layers as f64, len);
+                base.plus(cross).plus(Self::new(0, 0, 1.0, len))
+            }
+            Recipe::MixedRadix {
+                left_fft,
+                right_fft,
+            }
+            | Recipe::MixedRadixSmall {
+                left_fft,
+                right_fft,
+            } => {
+                // Three transposes, and one complex multiplication per element for the twiddle factors
+                Self::split(left_fft, right_fft).plus(Self::new(4 * n, 2 * n, 4.0, len))
+            }
+            Recipe::GoodThomasAlgorithm {
+                left_fft,
+                right_fft,
+            }
+            | Recipe::GoodThomasAlgorithmSmall {
+                left_fft,
+                right_fft,
+            } => {
+                // Index remapping on input and output, and one transpose, but no twiddle factors
+                Self::split(left_fft, right_fft).plus(Self::new(0, 0, 3.0, len))
+            }
+            Recipe::RadersAlgorithm { inner_fft } => {
+                // Reorder the input, multiply the inner FFT's output by a precomputed spectrum, and reorder the output.
+                // The first input is added to every output, and the first output is the sum of every input
+                let inner_n = n - 1;
+                Self::from_recipe(inner_fft).repeat(2).plus(Self::new(
+                    4 * inner_n,
+                    4 * inner_n + 2,
+                    3.0,
+                    len,
+                ))
+            }
+            Recipe::BluesteinsAlgorithm { inner_fft, .. } => {
+                // Multiply the input by a chirp, multiply the inner FFT's output by a precomputed spectrum, and multiply the output by a chirp
+                let inner_len = inner_fft.len();
+                let complex_multiplications = 2 * n + inner_len as u64;
+                Self::from_recipe(inner_fft).repeat(2).plus(Self::new(
+                    4 * complex_multiplications,
+                    2 * complex_multiplications,
+                    3.0,
+                    inner_len,
+                ))
+            }
+            Recipe::Butterfly2
+            | Recipe::Butterfly4
+            | Recipe::Butterfly8
+            | Recipe::Butterfly16
+            | Recipe::Butterfly32 => Self::power_of_two_butterfly(len),
+            // Butterfly6 is a good-thomas step made of two butterflies of size 3, and three butterflies of size 2
+            Recipe::Butterfly6 => Self::prime_butterfly(3)
+                .repeat(2)
+                .plus(Self::power_of_two_butterfly(2).repeat(3)),
+            _ => Self::prime_butterfly(len),
+        }
+    }
+
+    // The split-radix operation counts, which the hand-optimized power-of-two butterflies are close to
+    fn power_of_two_butterfly(len: usize) -> Self {
+        if len < 2 {
+            return Self::new(0, 0, 1.0, len);
+        }
+        let n = len as u64;
+        let bits = u64::from(len.trailing_zeros());
+        Self::new(
+            (n * bits + 4).saturating_sub(3 * n),
+            (3 * n * bits + 4).saturating_sub(3 * n),
+            1.0,
+            len,
+        )
+    }
+
+    // Prime butterflies combine each pair of inputs x[k] and x[len - k], then compute each pair of outputs with (len - 1) / 2 real multiply-adds per component
+    fn prime_butterfly(len: usize) -> Self {
+        let p = len as u64;
+        Self::new((p - 1) * (p - 1), (p - 1) * (p + 3), 1.0, len)
+    }
+
+    // A split FFT of size w * h computes h FFTs of size w, and w FFTs of size h
+    fn split(left_fft: &Recipe, right_fft: &Recipe) -> Self {
+        Self::from_recipe(left_fft)
+            .repeat(right_fft.len() as u64)
+            .plus(Self::from_recipe(right_fft).repeat(left_fft.len() as u64))
+    }
+
+    fn repeat(self, count: u64) -> Self {
+        Self {
+            multiplications: self.multiplications * count,
+            additions: self.additions * count,
+            memory_elements: self.memory_elements * count as f64,
+        }
+    }
+
+    fn plus(self, other: Self) -> Self {
+        Self {
+            multiplications: self.multiplications + other.multiplications,
+            additions: self.additions + other.additions,
+            memory_elements: self.memory_elements + other.memory_elements,
+        }
+    }
+}
+
+fn checked_cost(cost: f64) -> f64 {
+    assert!(
+        cost >= 0.0 && cost.is_finite(),
+        "Costs must be finite and non-negative. Got {}",
+        cost
+    );
+    cost
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_recipe_cost() {
+        let model = CostModel::new().mixed_radix(2.0).dft(1.0).butterfly(1.0);
+
+        // Butterfly4 costs 4 * log2(4) = 8, so a 4x4 mixed radix costs 4 * 8 + 4 * 8 + 2 * 16
+        let butterfly4 = Arc::new(Recipe::Butterfly4);
+        let mixed_radix = Recipe::MixedRadix {
+            left_fft: Arc::clone(&butterfly4),
+            right_fft: butterfly4,
+        };
+        assert_eq!(model.recipe_cost(&mixed_radix), 96.0);
+        assert_eq!(model.recipe_cost(&Recipe::Dft(10)), 100.0);
+
+        // Only FFTs that don't fit in the cache pay for their passes over memory
+        let small_model = model.clone().cache_len(8).memory_pass(1.0);
+        assert_eq!(small_model.recipe_cost(&mixed_radix), 96.0 + 16.0 * 6.0);
+        assert_eq!(small_model.recipe_cost(&Recipe::Dft(8)), 64.0);
+    }
+
+    #[test]
+    fn test_operation_counts() {
+        // A 4x4 mixed radix computes 8 butterflies of size 4, with 16 additions each, and 16 complex multiplications for its twiddle factors
+        let butterfly4 = Arc::new(Recipe::Butterfly4);
+        let mixed_radix = Recipe::MixedRadix {
+            left_fft: Arc::clone(&butterfly4),
+            right_fft: butterfly4,
+        };
+        let estimate = FftEstimate::from_recipe(&mixed_radix, &CostModel::new());
+        assert_eq!(estimate.multiplications, 64);
+        assert_eq!(estimate.additions, 8 * 16 + 32);
+        assert_eq!(estimate.memory_passes, 6.0);
+        assert_eq!(estimate.inplace_scratch_len, 16);
+
+        // The split-radix counts for size 8 are 4 multiplications and 52 additions
+        let estimate = FftEstimate::from_recipe(&Recipe::Butterfly8, &CostModel::new());
+        assert_eq!((estimate.multiplications, estimate.additions), (4, 52));
+
+        // A radix4 FFT of size 64 is 4 butterflies of size 16, then one radix-4 layer
+        let estimate = FftEstimate::from_recipe(&Recipe::Radix4(64), &CostModel::new());
+        assert_eq!(estimate.multiplications, 4 * 20 + 3 * 64);
+        assert_eq!(estimate.additions, 4 * 148 + 22 * 16);
+        assert_eq!(estimate.memory_passes, 3.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_cost() {
+        CostModel::new().radix4(std::f64::NAN);
+    }
+}
@@ -1,16 +1,63 @@
 use std::{collections::HashMap, sync::Arc};
 
-use crate::{Fft, FftDirection};
+use crate::{Fft, FftDirection, FftNum};
+
+/// Limits on the FFT instances that a planner keeps in its cache.
+///
+/// Planners cache every FFT instance they create, including the inner FFTs of composite algorithms, so that planning the same
+/// size again, or planning a size that shares an inner FFT, reuses the existing instance and its precomputed data. By default the cache
+/// is unbounded, which is ideal when a program only uses a handful of sizes. When a long-running program plans many distinct sizes,
+/// a bounded policy discards the least recently used instances instead.
+///
+/// Discarding an instance only removes it from the cache: FFT instances that have already been returned by the planner stay valid,
+/// and their memory is freed once they're dropped. FFT instances registered with a planner's `register` method are never discarded.
+///
+/// ~~~
+/// // Keep at most 100 FFT instances in the planner's cache
+/// use rustfft::{CachePolicy, FftPlanner};
+///
+/// let mut planner = FftPlanner::<f32>::new();
+/// planner.set_cache_policy(CachePolicy::MaxEntries(100));
+///
+/// for len in 1..1000 {
+///     let fft = planner.plan_fft_forward(len);
+/// }
+/// ~~~
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CachePolicy {
+    /// Keep every FFT instance. This is the default.
+    Unbounded,
+    /// Keep at most this many FFT instances, discarding the least recently used instances first.
+    MaxEntries(usize),
+    /// Keep FFT instances until the heap bytes they own exceed this many bytes, discarding the least recently used instances first.
+    ///
+    /// Each instance is measured with [`Fft::own_heap_bytes`](crate::Fft::own_heap_bytes).
+    MaxBytes(usize),
+}
+
+struct CacheEntry<T> {
+    fft: Arc<dyn Fft<T>>,
+    bytes: usize,
+    last_used: u64,
+    // Registered instances were provided by the user, so they're never discarded
+    registered: bool,
+}
 
 pub(crate) struct FftCache<T> {
-    forward_cache: HashMap<usize, Arc<dyn Fft<T>>>,
-    inverse_cache: HashMap<usize, Arc<dyn Fft<T>>>,
+    forward_cache: HashMap<usize, CacheEntry<T>>,
+    inverse_cache: HashMap<usize, CacheEntry<T>>,
+    policy: CachePolicy,
+    total_bytes: usize,
+    use_counter: u64,
 }
-impl<T> FftCache<T> {
+impl<T: FftNum> FftCache<T> {
     pub fn new() -> Self {
         Self {
             forward_cache: HashMap::new(),
             inverse_cache: HashMap::new(),
+            policy: CachePolicy::Unbounded,
+            total_bytes: 0,
+            use_counter: 0,
         }
     }
     #[allow(unused)]
@@ -20,20 +67,147 @@ impl<T> FftCache<T> {
             FftDirection::Inverse => self.inverse_cache.contains_key(&len),
         }
     }
-    pub fn get(&self, len: usize, direction: FftDirection) -> Option<Arc<dyn Fft<T>>> {
-        match direction {
-            FftDirection::Forward => self.forward_cache.get(&len),
-            FftDirection::Inverse => self.inverse_cache.get(&len),
-        }
-        .map(Arc::clone)
+    #[allow(unused)]
+    pub fn contains_len(&self, len: usize) -> bool {
+        self.forward_cache.contains_key(&len) || self.inverse_cache.contains_key(&len)
     }
-    pub fn insert(&mut self, fft: &Arc<dyn Fft<T>>) {
+    pub fn get(&mut self, len: usize, direction: FftDirection) -> Option<Arc<dyn Fft<T>>> {
+        self.use_counter += 1;
+        let use_counter = self.use_counter;
+
+        let entry = match direction {
+            FftDirection::Forward => self.forward_cache.get_mut(&len),
+            FftDirection::Inverse => self.inverse_cache.get_mut(&len),
+        }?;
+        entry.last_used = use_counter;
+        Some(Arc::clone(&entry.fft))
+    }
+    /// Inserts `fft` into the cache, then discards instances until the cache fits its policy. Returns true if any instances were discarded.
+    pub fn insert(&mut self, fft: &Arc<dyn Fft<T>>) -> bool {
+        self.insert_entry(fft, false)
+    }
+    /// Inserts `fft` into the cache, and keeps it there until the cache is dropped. Returns true if any other instances were discarded to fit the policy.
+    pub fn register(&mut self, fft: &Arc<dyn Fft<T>>) -> bool {
+        self.insert_entry(fft, true)
+    }
+    pub fn is_registered(&self, len: usize) -> bool {
+        self.registered_lens()
+            .any(|registered_len| registered_len == len)
+    }
+    /// Returns the sizes of the registered instances. Sizes registered in both directions are returned twice.
+    pub fn registered_lens<'a>(&'a self) -> impl Iterator<Item = usize> + 'a {
+        self.forward_cache
+            .iter()
+            .chain(self.inverse_cache.iter())
+            .filter(|(_, entry)| entry.registered)
+            .map(|(len, _)| *len)
+    }
+    fn insert_entry(&mut self, fft: &Arc<dyn Fft<T>>, registered: bool) -> bool {
+        self.use_counter += 1;
         let cloned = Arc::clone(fft);
         let len = cloned.len();
+        let entry = CacheEntry {
+            // Inner FFTs are usually cached too, so only count the instance's own data, to avoid counting inner FFTs several times
+            bytes: cloned.own_heap_bytes(),
+            fft: cloned,
+            last_used: self.use_counter,
+            registered,
+        };
 
-        match cloned.fft_direction() {
-            FftDirection::Forward => self.forward_cache.insert(len, cloned),
-            FftDirection::Inverse => self.inverse_cache.insert(len, cloned),
+        self.total_bytes += entry.bytes;
+        let replaced = match entry.fft.fft_direction() {
+            FftDirection::Forward => self.forward_cache.insert(len, entry),
+            FftDirection::Inverse => self.inverse_cache.insert(len, entry),
         };
+        if let Some(replaced) = replaced {
+            self.total_bytes -= replaced.bytes;
+        }
+
+        self.enforce_policy()
+    }
+    /// Removes the unregistered instances of size `len`, in both directions. Returns true if any instances were removed.
+    pub fn evict(&mut self, len: usize) -> bool {
+        let mut evicted = false;
+        for cache in &mut [&mut self.forward_cache, &mut self.inverse_cache] {
+            let is_evictable = match cache.get(&len) {
+                Some(entry) => !entry.registered,
+                None => false,
+            };
+            if is_evictable {
+                self.total_bytes -= cache.remove(&len).unwrap().bytes;
+                evicted = true;
+            }
+        }
+        evicted
+    }
+    /// Removes every unregistered instance
+    pub fn clear(&mut self) {
+        self.forward_cache.retain(|_, entry| entry.registered);
+        self.inverse_cache.retain(|_, entry| entry.registered);
+        self.total_bytes = self
+            .forward_cache
+            .values()
+            .chain(self.inverse_cache.values())
+            .map(|entry| entry.bytes)
+            .sum();
+    }
+    /// Changes the cache policy, then discards instances until the cache fits the new policy. Returns true if any instances were discarded.
+    pub fn set_policy(&mut self, policy: CachePolicy) -> bool {
+        self.policy = policy;
+        self.enforce_policy()
+    }
+    pub fn policy(&self) -> CachePolicy {
+        self.policy
+    }
+    /// Returns the number of heap bytes owned by the cached instances
+    pub fn memory_bytes(&self) -> usize {
+        self.total_bytes
+    }
+    pub fn entry_count(&self) -> usize {
+        self.forward_cache.len() + self.inverse_cache.len()
+    }
+
+    fn exceeds_policy(&self) -> bool {
+        match self.policy {
+            CachePolicy::Unbounded => false,
+            CachePolicy::MaxEntries(max_entries) => self.entry_count() > max_entries,
+            CachePolicy::MaxBytes(max_bytes) => self.total_bytes > max_bytes,
+        }
+    }
+
+    fn enforce_policy(&mut self) -> bool {
+        let mut evicted = false;
+        while self.exceeds_policy() {
+            // Find the least recently used entry. Caches are small enough that a linear scan is cheaper than maintaining a priority queue.
+            // If only registered entries are left, the cache can't shrink any further, so stop there.
+            let forward_lru = self
+                .forward_cache
+                .iter()
+                .filter(|(_, entry)| !entry.registered)
+                .map(|(len, entry)| (entry.last_used, *len))
+                .min();
+            let inverse_lru = self
+                .inverse_cache
+                .iter()
+                .filter(|(_, entry)| !entry.registered)
+                .map(|(len, entry)| (entry.last_used, *len))
+                .min();
+            let (len, direction) = match (forward_lru, inverse_lru) {
+                (Some(forward), Some(inverse)) if inverse < forward => {
+                    (inverse.1, FftDirection::Inverse)
+                }
+                (Some(forward), _) => (forward.1, FftDirection::Forward),
+                (None, Some(inverse)) => (inverse.1, FftDirection::Inverse),
+                (None, None) => break,
+            };
+
+            let entry = match direction {
+                FftDirection::Forward => self.forward_cache.remove(&len),
+                FftDirection::Inverse => self.inverse_cache.remove(&len),
+            };
+            self.total_bytes -= entry.unwrap().bytes;
+            evicted = true;
+        }
+        evicted
     }
 }
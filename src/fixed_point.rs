@@ -0,0 +1,598 @@
+use std::convert::TryFrom;
+use std::f64::consts::PI;
+use std::fmt::Debug;
+
+use num_complex::Complex;
+
+use crate::algorithm::radix4::prepare_radix4;
+use crate::{Direction, FftDirection, Length};
+
+/// A fixed-point sample type, with every bit below the sign bit used for the fraction. Implemented for `i16` (Q15) and `i32` (Q31).
+pub trait FixedPointSample: Copy + Default + Debug + Send + Sync + 'static {
+    /// The number of fractional bits, IE 15 for Q15
+    const FRACTIONAL_BITS: u32;
+
+    /// Widens this value to `i64`
+    fn to_i64(self) -> i64;
+
+    /// Narrows `value` to this type, saturating if it's out of range. Returns the narrowed value, and whether it saturated
+    fn saturate_from_i64(value: i64) -> (Self, bool);
+}
+
+impl FixedPointSample for i16 {
+    const FRACTIONAL_BITS: u32 = 15;
+
+    #[inline(always)]
+    fn to_i64(self) -> i64 {
+        self as i64
+    }
+    #[inline(always)]
+    fn saturate_from_i64(value: i64) -> (Self, bool) {
+        match i16::try_from(value) {
+            Ok(narrowed) => (narrowed, false),
+            Err(_) if value > 0 => (0x7FFF, true),
+            Err(_) => (-0x8000, true),
+        }
+    }
+}
+
+impl FixedPointSample for i32 {
+    const FRACTIONAL_BITS: u32 = 31;
+
+    #[inline(always)]
+    fn to_i64(self) -> i64 {
+        self as i64
+    }
+    #[inline(always)]
+    fn saturate_from_i64(value: i64) -> (Self, bool) {
+        match i32::try_from(value) {
+            Ok(narrowed) => (narrowed, false),
+            Err(_) if value > 0 => (0x7FFF_FFFF, true),
+            Err(_) => (-0x8000_0000, true),
+        }
+    }
+}
+
+/// How a [`FixedPointFft`](crate::fixed_point::FixedPointFft) scales intermediate results to avoid overflow
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FixedPointScaling {
+    /// Never scale. Outputs are the unnormalized FFT, and saturate wherever it doesn't fit.
+    None,
+
+    /// Shift right by 1 after a radix-2 stage and by 2 after a radix-4 stage, so the output is the FFT divided by `len`.
+    ShiftPerStage,
+
+    /// Shift right by the given amount after each stage, starting with the first stage. Must contain one shift per stage, see
+    /// [`stage_count`](crate::fixed_point::FixedPointFft::stage_count), and each shift must be smaller than the bit width of the sample type.
+    Shifts(Vec<u32>),
+
+    /// Before each stage, check the largest magnitude in the buffer, and shift right by just enough to guarantee that the stage can't overflow.
+    /// The total shift depends on the input, and is reported as the status's exponent.
+    BlockFloatingPoint,
+}
+
+/// The result of a fixed-point FFT computation
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FixedPointStatus {
+    /// The total right shift applied across all stages: the output is the FFT divided by `2^exponent`
+    pub exponent: u32,
+
+    /// The number of times an intermediate or output value saturated. Zero means no overflow occurred.
+    pub overflow_count: usize,
+}
+
+/// Computes power-of-two FFTs of Q15 or Q31 fixed-point data, bit-exactly.
+///
+/// Uses the same structure as [`Radix4`](crate::algorithm::Radix4): a digit-reversal pass, an optional radix-2 stage if `len` is
+/// an odd power of two, then radix-4 stages. All arithmetic is done in `i64` and narrowed with saturation at the end of each stage, so
+/// results are reproducible on every platform, and can be used as a reference for DSP implementations:
+/// - Twiddle factors are `round(w * 2^FRACTIONAL_BITS)`, with +/-1.0 saturated to the largest representable magnitude.
+/// - Twiddle multiplication rounds once per complex product, by adding `2^(FRACTIONAL_BITS - 1)` and shifting right.
+///   Products are saturated before the butterfly sums.
+/// - Stage shifts round the same way, and are applied to the butterfly outputs before they're saturated.
+///
+/// ~~~
+/// // Computes a forward FFT of size 1024, in Q15 with block floating point scaling
+/// use rustfft::fixed_point::{FixedPointFft, FixedPointScaling};
+/// use rustfft::FftDirection;
+/// use rustfft::num_complex::Complex;
+///
+/// let mut buffer = vec![Complex{ re: 0i16, im: 0i16 }; 1024];
+///
+/// let fft = FixedPointFft::new(1024, FftDirection::Forward, FixedPointScaling::BlockFloatingPoint);
+/// let status = fft.process(&mut buffer);
+/// assert_eq!(status.overflow_count, 0);
+/// ~~~
+pub struct FixedPointFft<S> {
+    twiddles: Box<[Complex<S>]>,
+    base_len: usize,
+    len: usize,
+    scaling: FixedPointScaling,
+    direction: FftDirection,
+}
+
+impl<S: FixedPointSample> FixedPointFft<S> {
+    /// Preallocates necessary arrays and precomputes necessary data to efficiently compute the power-of-two fixed-point FFT
+    ///
+    /// # Panics
+    /// Panics if `len` isn't a power of two, or if `scaling` is `FixedPointScaling::Shifts` with the wrong number of shifts,
+    /// or with a shift that isn't smaller than the bit width of the sample type.
+    pub fn new(len: usize, direction: FftDirection, scaling: FixedPointScaling) -> Self {
+        assert!(
+            len.is_power_of_two(),
+            "Fixed-point FFT requires a power-of-two input size. Got {}",
+            len
+        );
+
+        // Without hardcoded butterflies to bottom out on, the base layer is either single elements or a radix-2 stage
+        let base_len = if len.trailing_zeros() % 2 == 1 { 2 } else { 1 };
+
+        // same layout as Radix4: the twiddle factors of each layer, starting with the bottom layer and going up
+        let mut twiddle_stride = len / (base_len * 4);
+        let mut twiddle_factors = Vec::with_capacity(len * 2);
+        while twiddle_stride > 0 {
+            let num_rows = len / (twiddle_stride * 4);
+            for i in 0..num_rows {
+                for k in 1..4 {
+                    twiddle_factors.push(quantize_twiddle(i * k * twiddle_stride, len, direction));
+                }
+            }
+            twiddle_stride >>= 2;
+        }
+
+        let result = Self {
+            twiddles: twiddle_factors.into_boxed_slice(),
+            base_len,
+            len,
+            scaling,
+            direction,
+        };
+        if let FixedPointScaling::Shifts(shifts) = &result.scaling {
+            assert_eq!(
+                shifts.len(),
+                result.stage_count(),
+                "FixedPointScaling::Shifts must contain one shift per stage. Expected {}, got {}",
+                result.stage_count(),
+                shifts.len()
+            );
+            let sample_bits = S::FRACTIONAL_BITS + 1;
+            if let Some(shift) = shifts.iter().find(|&&shift| shift >= sample_bits) {
+                panic!(
+                    "FixedPointScaling::Shifts must be smaller than the sample's bit width. Expected shifts < {}, got {}",
+                    sample_bits, shift
+                );
+            }
+        }
+        result
+    }
+
+    /// Returns the number of stages this FFT computes: one radix-2 stage if `len` is an odd power of two, plus one stage per radix-4 layer
+    pub fn stage_count(&self) -> usize {
+        let radix2_stages = if self.base_len == 2 { 1 } else { 0 };
+        radix2_stages + (self.len / self.base_len).trailing_zeros() as usize / 2
+    }
+
+    /// Returns the scaling strategy of this FFT
+    pub fn scaling(&self) -> &FixedPointScaling {
+        &self.scaling
+    }
+
+    /// Returns the size of the scratch buffer required by `process_with_scratch`
+    pub fn get_inplace_scratch_len(&self) -> usize {
+        self.len
+    }
+
+    /// Computes a FFT in-place, and reports the scaling that was applied and whether anything overflowed.
+    ///
+    /// Convenience method that allocates a scratch buffer of the size returned by `get_inplace_scratch_len()`.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `buffer.len() != self.len()`
+    pub fn process(&self, buffer: &mut [Complex<S>]) -> FixedPointStatus {
+        let mut scratch = vec![Complex::default(); self.get_inplace_scratch_len()];
+        self.process_with_scratch(buffer, &mut scratch)
+    }
+
+    /// Computes a FFT in-place, and reports the scaling that was applied and whether anything overflowed.
+    ///
+    /// Unlike the floating-point FFTs, this processes exactly one FFT per call, because each block floating point FFT has its own exponent.
+    /// Uses the `scratch` buffer as scratch space, so the contents of `scratch` should be considered garbage after calling.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if:
+    /// - `buffer.len() != self.len()`
+    /// - `scratch.len() < self.get_inplace_scratch_len()`
+    pub fn process_with_scratch(
+        &self,
+        buffer: &mut [Complex<S>],
+        scratch: &mut [Complex<S>],
+    ) -> FixedPointStatus {
+        assert_eq!(
+            buffer.len(),
+            self.len,
+            "Fixed-point FFT buffer must contain exactly one FFT. Expected len = {}, got len = {}",
+            self.len,
+            buffer.len()
+        );
+        assert!(
+            scratch.len() >= self.len,
+            "Not enough scratch space was provided. Expected scratch len >= {}, got scratch len = {}",
+            self.len,
+            scratch.len()
+        );
+        let spectrum = &mut scratch[..self.len];
+
+        let mut status = FixedPointStatus {
+            exponent: 0,
+            overflow_count: 0,
+        };
+
+        // copy the data into the spectrum vector
+        prepare_radix4(self.len, self.base_len, buffer, spectrum, 1);
+
+        // Base-level radix-2 stage
+        let mut stage = 0;
+        if self.base_len == 2 {
+            let shift = self.stage_shift(stage, spectrum, 3);
+            for pair in spectrum.chunks_exact_mut(2) {
+                let a = widen(pair[0]);
+                let b = widen(pair[1]);
+                pair[0] = narrow(a + b, shift, &mut status.overflow_count);
+                pair[1] = narrow(a - b, shift, &mut status.overflow_count);
+            }
+            status.exponent += shift;
+            stage += 1;
+        }
+
+        // radix-4 stages
+        let mut current_size = self.base_len * 4;
+        let mut layer_twiddles: &[Complex<S>] = &self.twiddles;
+
+        while current_size <= self.len {
+            let shift = self.stage_shift(stage, spectrum, 6);
+            for row in spectrum.chunks_exact_mut(current_size) {
+                self.butterfly_4(
+                    row,
+                    layer_twiddles,
+                    current_size / 4,
+                    shift,
+                    &mut status.overflow_count,
+                );
+            }
+            status.exponent += shift;
+            stage += 1;
+
+            //skip past all the twiddle factors used in this layer
+            let twiddle_offset = (current_size * 3) / 4;
+            layer_twiddles = &layer_twiddles[twiddle_offset..];
+
+            current_size *= 4;
+        }
+
+        buffer.copy_from_slice(spectrum);
+        status
+    }
+
+    // Returns the right shift to apply to the outputs of the given stage. `max_growth` bounds how much larger a stage's output components
+    // can be than its largest input component: |a + wb| <= (1 + sqrt(2)) * max < 3 for radix 2, and 4 * sqrt(2) * max < 6 for radix 4
+    fn stage_shift(&self, stage: usize, data: &[Complex<S>], max_growth: i64) -> u32 {
+        match &self.scaling {
+            FixedPointScaling::None => 0,
+            FixedPointScaling::ShiftPerStage => {
+                if stage == 0 && self.base_len == 2 {
+                    1
+                } else {
+                    2
+                }
+            }
+            FixedPointScaling::Shifts(shifts) => shifts[stage],
+            FixedPointScaling::BlockFloatingPoint => {
+                let max_magnitude = data
+                    .iter()
+                    .map(|element| {
+                        std::cmp::max(element.re.to_i64().abs(), element.im.to_i64().abs())
+                    })
+                    .max()
+                    .unwrap_or(0);
+
+                // find the smallest shift that makes the largest possible output fit
+                let limit = 1i64 << S::FRACTIONAL_BITS;
+                let mut shift = 0;
+                while (max_magnitude * max_growth) >> shift >= limit {
+                    shift += 1;
+                }
+                shift
+            }
+        }
+    }
+
+    fn butterfly_4(
+        &self,
+        data: &mut [Complex<S>],
+        twiddles: &[Complex<S>],
+        num_ffts: usize,
+        shift: u32,
+        overflow_count: &mut usize,
+    ) {
+        for i in 0..num_ffts {
+            let a = widen(data[i]);
+            let b = twiddle_multiply(data[i + num_ffts], twiddles[3 * i], overflow_count);
+            let c = twiddle_multiply(data[i + 2 * num_ffts], twiddles[3 * i + 1], overflow_count);
+            let d = twiddle_multiply(data[i + 3 * num_ffts], twiddles[3 * i + 2], overflow_count);
+
+            let a_plus_c = a + c;
+            let a_minus_c = a - c;
+            let b_plus_d = b + d;
+            let b_minus_d = b - d;
+
+            // multiplying by -i for forward FFTs, or +i for inverse FFTs, is exact
+            let b_minus_d_rotated = match self.direction {
+                FftDirection::Forward => Complex::new(b_minus_d.im, -b_minus_d.re),
+                FftDirection::Inverse => Complex::new(-b_minus_d.im, b_minus_d.re),
+            };
+
+            data[i] = narrow(a_plus_c + b_plus_d, shift, overflow_count);
+            data[i + num_ffts] = narrow(a_minus_c + b_minus_d_rotated, shift, overflow_count);
+            data[i + 2 * num_ffts] = narrow(a_plus_c - b_plus_d, shift, overflow_count);
+            data[i + 3 * num_ffts] = narrow(a_minus_c - b_minus_d_rotated, shift, overflow_count);
+        }
+    }
+}
+impl<S> Length for FixedPointFft<S> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+impl<S> Direction for FixedPointFft<S> {
+    fn fft_direction(&self) -> FftDirection {
+        self.direction
+    }
+}
+
+fn quantize_twiddle<S: FixedPointSample>(
+    index: usize,
+    len: usize,
+    direction: FftDirection,
+) -> Complex<S> {
+    let angle = match direction {
+        FftDirection::Forward => -2.0 * PI * index as f64 / len as f64,
+        FftDirection::Inverse => 2.0 * PI * index as f64 / len as f64,
+    };
+
+    // saturate +/-1.0 to the largest representable magnitude, keeping the twiddles symmetric.
+    // This also keeps every twiddle product within an i64, even for Q31
+    let scale = (1i64 << S::FRACTIONAL_BITS) as f64;
+    let max = (1i64 << S::FRACTIONAL_BITS) - 1;
+    let quantize = |value: f64| {
+        let quantized = (value * scale).round() as i64;
+        S::saturate_from_i64(std::cmp::max(-max, std::cmp::min(max, quantized))).0
+    };
+    Complex::new(quantize(angle.cos()), quantize(angle.sin()))
+}
+
+#[inline(always)]
+fn widen<S: FixedPointSample>(value: Complex<S>) -> Complex<i64> {
+    Complex::new(value.re.to_i64(), value.im.to_i64())
+}
+
+// Shifts right by `shift` with round-half-up, then saturates
+#[inline(always)]
+fn narrow<S: FixedPointSample>(
+    value: Complex<i64>,
+    shift: u32,
+    overflow_count: &mut usize,
+) -> Complex<S> {
+    let round_shift = |component: i64| {
+        if shift == 0 {
+            component
+        } else {
+            (component + (1 << (shift - 1))) >> shift
+        }
+    };
+    let (re, re_saturated) = S::saturate_from_i64(round_shift(value.re));
+    let (im, im_saturated) = S::saturate_from_i64(round_shift(value.im));
+    *overflow_count += re_saturated as usize + im_saturated as usize;
+    Complex::new(re, im)
+}
+
+// Multiplies by a twiddle factor, rounding once per component, and saturates the product to the sample type
+#[inline(always)]
+fn twiddle_multiply<S: FixedPointSample>(
+    value: Complex<S>,
+    twiddle: Complex<S>,
+    overflow_count: &mut usize,
+) -> Complex<i64> {
+    let value = widen(value);
+    let twiddle = widen(twiddle);
+    let product = Complex::new(
+        value.re * twiddle.re - value.im * twiddle.im,
+        value.re * twiddle.im + value.im * twiddle.re,
+    );
+    widen(narrow::<S>(product, S::FRACTIONAL_BITS, overflow_count))
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::algorithm::Dft;
+    use crate::test_utils::random_signal;
+    use crate::Fft;
+
+    // Checks the fixed-point output against a floating-point FFT of the same input, scaled by the reported exponent, in units of the least significant bit
+    fn check_accuracy<S: FixedPointSample>(
+        len: usize,
+        direction: FftDirection,
+        scaling: FixedPointScaling,
+        amplitude: f64,
+    ) {
+        let fft = FixedPointFft::<S>::new(len, direction, scaling.clone());
+        assert_eq!(fft.len(), len);
+        assert_eq!(fft.fft_direction(), direction);
+
+        let lsb = (1i64 << S::FRACTIONAL_BITS) as f64;
+        let input: Vec<Complex<S>> = random_signal::<f64>(len)
+            .iter()
+            .map(|c| {
+                let quantize =
+                    |value: f64| S::saturate_from_i64((value * amplitude * lsb).round() as i64).0;
+                Complex::new(quantize(c.re), quantize(c.im))
+            })
+            .collect();
+
+        let mut expected: Vec<Complex<f64>> = input
+            .iter()
+            .map(|c| Complex::new(c.re.to_i64() as f64, c.im.to_i64() as f64))
+            .collect();
+        Dft::new(len, direction).process(&mut expected);
+
+        let mut buffer = input.clone();
+        let mut scratch =
+            vec![Complex::new(S::saturate_from_i64(-5).0, S::saturate_from_i64(7).0); len];
+        let status = fft.process_with_scratch(&mut buffer, &mut scratch);
+        assert_eq!(
+            status.overflow_count, 0,
+            "len = {}, scaling = {:?}",
+            len, scaling
+        );
+
+        // Each stage rounds once, and rounding errors from earlier stages can grow by up to 4x per stage before their scaling
+        let tolerance = 4.0 * (fft.stage_count() + 1) as f64;
+        let scale = (1u64 << status.exponent) as f64;
+        for (actual, expected) in buffer.iter().zip(expected.iter()) {
+            let expected = expected / scale;
+            let error = (Complex::new(actual.re.to_i64() as f64, actual.im.to_i64() as f64)
+                - expected)
+                .norm();
+            assert!(
+                error <= tolerance,
+                "len = {}, direction = {}, scaling = {:?}, error = {} LSB",
+                len,
+                direction,
+                scaling,
+                error
+            );
+        }
+    }
+
+    #[test]
+    fn test_fixed_point_accuracy() {
+        for pow in 0..11 {
+            let len = 1 << pow;
+            for &direction in &[FftDirection::Forward, FftDirection::Inverse] {
+                check_accuracy::<i16>(len, direction, FixedPointScaling::ShiftPerStage, 0.5);
+                check_accuracy::<i16>(len, direction, FixedPointScaling::BlockFloatingPoint, 0.9);
+                check_accuracy::<i32>(len, direction, FixedPointScaling::ShiftPerStage, 0.5);
+                check_accuracy::<i32>(len, direction, FixedPointScaling::BlockFloatingPoint, 0.9);
+            }
+        }
+    }
+
+    #[test]
+    fn test_fixed_point_impulse_is_exact() {
+        for &len in &[16, 32] {
+            let fft = FixedPointFft::<i16>::new(
+                len,
+                FftDirection::Forward,
+                FixedPointScaling::ShiftPerStage,
+            );
+            let mut buffer = vec![Complex::new(0, 0); len];
+            buffer[0] = Complex::new(16384, -8192);
+
+            let status = fft.process(&mut buffer);
+            assert_eq!(status.exponent, len.trailing_zeros());
+            assert_eq!(status.overflow_count, 0);
+            let expected = Complex::new(16384 / len as i16, -8192 / len as i16);
+            assert!(
+                buffer.iter().all(|&element| element == expected),
+                "len = {}",
+                len
+            );
+        }
+    }
+
+    #[test]
+    fn test_fixed_point_overflow() {
+        let len = 64;
+        let input = vec![Complex::new(20000i16, -20000i16); len];
+
+        // Without scaling, the DC bin saturates
+        let fft = FixedPointFft::new(len, FftDirection::Forward, FixedPointScaling::None);
+        let mut buffer = input.clone();
+        let status = fft.process(&mut buffer);
+        assert_eq!(status.exponent, 0);
+        assert!(status.overflow_count > 0);
+        assert_eq!(buffer[0], Complex::new(32767, -32768));
+
+        // Block floating point scales just enough to avoid it
+        let fft = FixedPointFft::new(
+            len,
+            FftDirection::Forward,
+            FixedPointScaling::BlockFloatingPoint,
+        );
+        let mut buffer = input.clone();
+        let status = fft.process(&mut buffer);
+        assert_eq!(status.overflow_count, 0);
+        assert!(status.exponent > 0);
+        // The twiddle factor for 1.0 saturates to 1 - 2^-15, so the result can be a few LSB low
+        let dc = (20000 * len as i64) >> status.exponent;
+        assert!((buffer[0].re as i64 - dc).abs() <= 4 && (buffer[0].im as i64 + dc).abs() <= 4);
+
+        // Custom shifts that match ShiftPerStage give identical results
+        let shift_per_stage =
+            FixedPointFft::new(len, FftDirection::Forward, FixedPointScaling::ShiftPerStage);
+        let custom = FixedPointFft::new(
+            len,
+            FftDirection::Forward,
+            FixedPointScaling::Shifts(vec![2; 3]),
+        );
+        assert_eq!(custom.stage_count(), 3);
+        let mut expected = input.clone();
+        let mut buffer = input.clone();
+        assert_eq!(
+            shift_per_stage.process(&mut expected),
+            custom.process(&mut buffer)
+        );
+        assert_eq!(expected, buffer);
+    }
+    #[test]
+    fn test_fixed_point_shift_limits() {
+        // The largest shift that fits in the sample type is allowed, for both sample types
+        let len = 64;
+        FixedPointFft::<i16>::new(
+            len,
+            FftDirection::Forward,
+            FixedPointScaling::Shifts(vec![0, 15, 2]),
+        );
+        FixedPointFft::<i32>::new(
+            len,
+            FftDirection::Forward,
+            FixedPointScaling::Shifts(vec![31, 0, 2]),
+        );
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "FixedPointScaling::Shifts must be smaller than the sample's bit width"
+    )]
+    fn test_fixed_point_shift_too_large_i16() {
+        FixedPointFft::<i16>::new(
+            64,
+            FftDirection::Forward,
+            FixedPointScaling::Shifts(vec![2, 16, 2]),
+        );
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "FixedPointScaling::Shifts must be smaller than the sample's bit width"
+    )]
+    fn test_fixed_point_shift_too_large_i32() {
+        FixedPointFft::<i32>::new(
+            64,
+            FftDirection::Forward,
+            FixedPointScaling::Shifts(vec![2, 2, 64]),
+        );
+    }
+}
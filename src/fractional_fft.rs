@@ -0,0 +1,470 @@
+use std::f64::consts::{FRAC_PI_2, PI};
+use std::sync::Arc;
+
+use num_complex::Complex;
+use num_traits::Zero;
+
+use crate::algorithm::bluesteins_algorithm::{
+    compute_convolution_multiplier, convolve_with_multiplier, finish_chirp_convolution,
+    prepare_chirp_convolution,
+};
+use crate::{common::FftNum, Fft, FftPlanner, Length};
+
+// Angles within this many quarter turns of a multiple of pi/2 are computed exactly, as an identity, FFT, reversal, or inverse FFT
+const QUARTER_TURN_TOLERANCE: f64 = 1e-12;
+
+enum Strategy<T> {
+    Identity,
+    Fft {
+        fft: Arc<dyn Fft<T>>,
+        // 1 / sqrt(len), to make the FFT unitary like every other angle
+        unitary_scale: T,
+    },
+    Reversal,
+    Chirp {
+        // If the angle is too close to 0 or pi for the chirps to be sampled accurately, we first rotate by a quarter turn with this FFT
+        pre_fft: Option<Arc<dyn Fft<T>>>,
+
+        inner_fft: Arc<dyn Fft<T>>,
+        inner_fft_multiplier: Box<[Complex<T>]>,
+
+        input_chirp: Box<[Complex<T>]>,
+        output_chirp: Box<[Complex<T>]>,
+    },
+}
+
+/// Implementation of the discrete [fractional Fourier transform](https://en.wikipedia.org/wiki/Fractional_Fourier_transform)
+///
+/// The fractional Fourier transform generalizes the FFT to arbitrary angles of rotation in the time-frequency plane.
+/// An angle of 0 is the identity, an angle of pi/2 is a forward FFT, an angle of pi reverses the input (IE `output[n] = input[(len - n) % len]`),
+/// and an angle of 3pi/2 is an inverse FFT. At these four angles, the output is exact.
+///
+/// All other angles are computed using the chirp multiplication, chirp convolution, chirp multiplication decomposition
+/// from Ozaktas et al., "Digital computation of the fractional Fourier transform". The input is treated as samples of a continuous signal
+/// at spacing `1 / sqrt(len)`, arranged in the same order as a FFT's input, so that sample `n` is at `n / sqrt(len)`, wrapping around
+/// to negative positions for `n >= len / 2`. The output is arranged the same way. The result is a sampled approximation of the continuous transform:
+/// It's accurate for inputs that are smooth, and that are small near `len / 2`.
+///
+/// Every angle is normalized to be unitary, so that the output is continuous in the angle. Unlike the rest of RustFFT, that includes
+/// the FFTs at pi/2 and 3pi/2: their output is smaller than a FFT's output by a factor of `sqrt(len)`.
+///
+/// Angles near 0 and pi are first rotated by a quarter turn with a FFT, so that the chirps are always sampled accurately.
+///
+/// ~~~
+/// // Rotate a size-1000 signal by 0.3 radians in the time-frequency plane
+/// use rustfft::fractional_fft::FractionalFft;
+/// use rustfft::FftPlanner;
+/// use rustfft::num_complex::Complex;
+///
+/// let mut buffer = vec![Complex{ re: 0.0f32, im: 0.0f32 }; 1000];
+///
+/// let mut planner = FftPlanner::new();
+/// let frft = FractionalFft::new(1000, 0.3, &mut planner);
+/// frft.process(&mut buffer);
+/// ~~~
+pub struct FractionalFft<T> {
+    strategy: Strategy<T>,
+
+    angle: f64,
+    len: usize,
+}
+
+impl<T: FftNum> FractionalFft<T> {
+    /// Creates a fractional Fourier transform which will process inputs of size `len`, rotating them by `angle` radians.
+    ///
+    /// FFTs for the inner convolution, and for angles near a quarter turn, are planned with `planner`.
+    ///
+    /// # Panics
+    /// Panics if `len == 0`, or if `angle` isn't finite.
+    pub fn new(len: usize, angle: f64, planner: &mut FftPlanner<T>) -> Self {
+        assert!(len > 0, "The fractional FFT requires a non-zero length");
+        assert!(
+            angle.is_finite(),
+            "The fractional FFT requires a finite angle. Got {}",
+            angle
+        );
+
+        // Express the angle in quarter turns, in the range -2..2
+        let mut order = (angle / FRAC_PI_2) % 4f64;
+        if order > 2f64 {
+            order -= 4f64;
+        } else if order <= -2f64 {
+            order += 4f64;
+        }
+
+        let nearest_quarter_turn = order.round();
+        let strategy = if (order - nearest_quarter_turn).abs() < QUARTER_TURN_TOLERANCE {
+            let unitary_scale = T::from_f64(1f64 / (len as f64).sqrt()).unwrap();
+            match nearest_quarter_turn as isize {
+                0 => Strategy::Identity,
+                1 => Strategy::Fft {
+                    fft: planner.plan_fft_forward(len),
+                    unitary_scale,
+                },
+                -1 => Strategy::Fft {
+                    fft: planner.plan_fft_inverse(len),
+                    unitary_scale,
+                },
+                _ => Strategy::Reversal,
+            }
+        } else {
+            Self::design_chirp(len, order, planner)
+        };
+
+        Self {
+            strategy,
+
+            angle,
+            len,
+        }
+    }
+
+    fn design_chirp(len: usize, order: f64, planner: &mut FftPlanner<T>) -> Strategy<T> {
+        // The chirps are only sampled accurately when the order is between 0.5 and 1.5 quarter turns, in either direction.
+        // Outside that range, rotate by a quarter turn with a FFT first, and then rotate by the remainder with chirps
+        let (pre_fft, order) = if order.abs() < 0.5 || order < -1.5 {
+            (Some(planner.plan_fft_inverse(len)), order + 1f64)
+        } else if order > 1.5 {
+            (Some(planner.plan_fft_forward(len)), order - 1f64)
+        } else {
+            (None, order)
+        };
+
+        // The continuous transform's kernel is A * exp(pi * i * (cot * (x^2 + u^2) - 2 * csc * x * u)).
+        // Rewrite -2 * x * u as (x - u)^2 - x^2 - u^2, to split it into an input chirp, a convolution with a chirp, and an output chirp
+        let (sin, cos) = (order * FRAC_PI_2).sin_cos();
+        let cot = cos / sin;
+        let csc = 1f64 / sin;
+        let amplitude = Complex::new(1f64, -cot).sqrt();
+
+        // Samples are spaced 1 / sqrt(len) apart, so the unitary transform is scaled by 1 / sqrt(len). The pre-rotation needs the same scale
+        let len_float = len as f64;
+        let unitary_scale = 1f64 / len_float.sqrt();
+        let input_scale = if pre_fft.is_some() {
+            unitary_scale
+        } else {
+            1f64
+        };
+
+        // Positions are relative to the center of the buffer, because we rotate the input to put position 0 in the center
+        let center = len / 2;
+        let edge_chirp = |n: usize| {
+            let position = n as f64 - center as f64;
+            Complex::from_polar(1f64, PI * (cot - csc) * position * position / len_float)
+        };
+        let to_complex_t = |c: Complex<f64>| Complex {
+            re: T::from_f64(c.re).unwrap(),
+            im: T::from_f64(c.im).unwrap(),
+        };
+
+        let inner_fft =
+            planner.plan_fft_forward((2 * len - 1).checked_next_power_of_two().unwrap());
+        let inner_fft_multiplier =
+            compute_convolution_multiplier(&*inner_fft, len, len, |i: isize| {
+                let i = i as f64;
+                to_complex_t(Complex::from_polar(1f64, PI * csc * i * i / len_float))
+            });
+        let input_chirp = (0..len)
+            .map(|n| to_complex_t(edge_chirp(n) * input_scale))
+            .collect();
+        let output_chirp = (0..len)
+            .map(|k| to_complex_t(edge_chirp(k) * amplitude * unitary_scale))
+            .collect();
+
+        Strategy::Chirp {
+            pre_fft,
+
+            inner_fft,
+            inner_fft_multiplier,
+
+            input_chirp,
+            output_chirp,
+        }
+    }
+
+    /// Returns the angle of rotation, in radians, that was passed to the constructor
+    pub fn angle(&self) -> f64 {
+        self.angle
+    }
+
+    /// Returns the size of the scratch buffer required by `process_with_scratch`
+    pub fn get_scratch_len(&self) -> usize {
+        match &self.strategy {
+            Strategy::Identity | Strategy::Reversal => 0,
+            Strategy::Fft { fft, .. } => fft.get_inplace_scratch_len(),
+            Strategy::Chirp {
+                pre_fft,
+                inner_fft,
+                inner_fft_multiplier,
+                ..
+            } => {
+                let pre_fft_scratch = pre_fft
+                    .as_ref()
+                    .map_or(0, |fft| fft.get_inplace_scratch_len());
+                inner_fft_multiplier.len()
+                    + std::cmp::max(pre_fft_scratch, inner_fft.get_inplace_scratch_len())
+            }
+        }
+    }
+
+    /// Divides `buffer` into chunks of size `self.len()`, and computes a fractional Fourier transform on each chunk.
+    ///
+    /// Convenience method that allocates a `Vec` with the required scratch space and calls `self.process_with_scratch`.
+    ///
+    /// # Panics
+    /// This method panics if `buffer.len() % self.len() > 0`.
+    pub fn process(&self, buffer: &mut [Complex<T>]) {
+        let mut scratch = vec![Complex::zero(); self.get_scratch_len()];
+        self.process_with_scratch(buffer, &mut scratch);
+    }
+
+    /// Divides `buffer` into chunks of size `self.len()`, and computes a fractional Fourier transform on each chunk.
+    ///
+    /// Uses the `scratch` buffer as scratch space, so the contents of `scratch` should be considered garbage
+    /// after calling.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if:
+    /// - `buffer.len() % self.len() > 0`
+    /// - `scratch.len() < self.get_scratch_len()`
+    pub fn process_with_scratch(&self, buffer: &mut [Complex<T>], scratch: &mut [Complex<T>]) {
+        let len = self.len;
+        assert!(
+            buffer.len() / len * len == buffer.len(),
+            "The fractional FFT buffer must be a multiple of the FFT length. Expected multiple of {}, got buffer len = {}",
+            len,
+            buffer.len()
+        );
+        assert!(
+            scratch.len() >= self.get_scratch_len(),
+            "Not enough scratch space was provided. Expected scratch len >= {}, got scratch len = {}",
+            self.get_scratch_len(),
+            scratch.len()
+        );
+
+        match &self.strategy {
+            Strategy::Identity => {}
+            Strategy::Fft { fft, unitary_scale } => {
+                fft.process_with_scratch(buffer, &mut scratch[..fft.get_inplace_scratch_len()]);
+                for element in buffer.iter_mut() {
+                    *element = *element * *unitary_scale;
+                }
+            }
+            Strategy::Reversal => {
+                for chunk in buffer.chunks_exact_mut(len) {
+                    chunk[1..].reverse();
+                }
+            }
+            Strategy::Chirp {
+                pre_fft,
+                inner_fft,
+                inner_fft_multiplier,
+                input_chirp,
+                output_chirp,
+            } => {
+                let (inner_input, inner_scratch) = scratch.split_at_mut(inner_fft_multiplier.len());
+                if let Some(pre_fft) = pre_fft {
+                    pre_fft.process_with_scratch(
+                        buffer,
+                        &mut inner_scratch[..pre_fft.get_inplace_scratch_len()],
+                    );
+                }
+
+                let inner_scratch = &mut inner_scratch[..inner_fft.get_inplace_scratch_len()];
+                let center = len / 2;
+                for chunk in buffer.chunks_exact_mut(len) {
+                    // The chirps are centered on position 0, so move position 0 from the start of the chunk to the center
+                    chunk.rotate_right(center);
+
+                    prepare_chirp_convolution(chunk, input_chirp, inner_input);
+                    convolve_with_multiplier(
+                        &**inner_fft,
+                        inner_fft_multiplier,
+                        inner_input,
+                        inner_scratch,
+                    );
+                    finish_chirp_convolution(inner_input, output_chirp, chunk);
+
+                    chunk.rotate_left(center);
+                }
+            }
+        }
+    }
+}
+impl<T> Length for FractionalFft<T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::algorithm::Dft;
+    use crate::test_utils::random_signal;
+    use crate::FftDirection;
+
+    fn assert_close(expected: &[Complex<f64>], actual: &[Complex<f64>], tolerance: f64) {
+        let max_expected = expected.iter().map(|x| x.norm()).fold(0f64, f64::max);
+        for (i, (e, a)) in expected.iter().zip(actual.iter()).enumerate() {
+            assert!(
+                (e - a).norm() <= tolerance * max_expected,
+                "index = {}, expected {}, got {}",
+                i,
+                e,
+                a
+            );
+        }
+    }
+
+    // Computes the same discretization as FractionalFft directly from the kernel, in O(n^2) time
+    fn naive_fractional_fft(input: &[Complex<f64>], angle: f64) -> Vec<Complex<f64>> {
+        let len = input.len();
+        let len_float = len as f64;
+        let mut order = (angle / FRAC_PI_2) % 4f64;
+        if order > 2f64 {
+            order -= 4f64;
+        } else if order <= -2f64 {
+            order += 4f64;
+        }
+
+        let mut signal = input.to_vec();
+        if order.abs() < 0.5 || order < -1.5 {
+            Dft::new(len, FftDirection::Inverse).process(&mut signal);
+            signal.iter_mut().for_each(|x| *x = *x / len_float.sqrt());
+            order += 1f64;
+        } else if order > 1.5 {
+            Dft::new(len, FftDirection::Forward).process(&mut signal);
+            signal.iter_mut().for_each(|x| *x = *x / len_float.sqrt());
+            order -= 1f64;
+        }
+
+        let alpha = order * FRAC_PI_2;
+        let cot = alpha.cos() / alpha.sin();
+        let csc = 1f64 / alpha.sin();
+        let amplitude = Complex::new(1f64, -cot).sqrt() / len_float.sqrt();
+
+        // Sample n is at position n / sqrt(len), wrapping around to negative positions in the second half
+        let position = |n: usize| {
+            let n = if n < len - len / 2 {
+                n as f64
+            } else {
+                n as f64 - len_float
+            };
+            n / len_float.sqrt()
+        };
+
+        (0..len)
+            .map(|k| {
+                let u = position(k);
+                signal
+                    .iter()
+                    .enumerate()
+                    .map(|(n, x)| {
+                        let x_pos = position(n);
+                        let phase = PI * (cot * (x_pos * x_pos + u * u) - 2f64 * csc * x_pos * u);
+                        x * Complex::from_polar(1f64, phase)
+                    })
+                    .sum::<Complex<f64>>()
+                    * amplitude
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fractional_fft_quarter_turns() {
+        let mut planner = FftPlanner::new();
+        for len in 1..20 {
+            let input: Vec<Complex<f64>> = random_signal(len);
+
+            let unitary_scale = 1f64 / (len as f64).sqrt();
+            let mut forward = input.clone();
+            Dft::new(len, FftDirection::Forward).process(&mut forward);
+            forward.iter_mut().for_each(|x| *x = *x * unitary_scale);
+            let mut inverse = input.clone();
+            Dft::new(len, FftDirection::Inverse).process(&mut inverse);
+            inverse.iter_mut().for_each(|x| *x = *x * unitary_scale);
+            let reversed: Vec<_> = (0..len).map(|n| input[(len - n) % len]).collect();
+
+            for &(angle, ref expected) in &[
+                (0f64, &input),
+                (FRAC_PI_2, &forward),
+                (PI, &reversed),
+                (3f64 * FRAC_PI_2, &inverse),
+                (-FRAC_PI_2, &inverse),
+                (-PI, &reversed),
+                (2f64 * PI, &input),
+                (5f64 * FRAC_PI_2, &forward),
+            ] {
+                let frft = FractionalFft::new(len, angle, &mut planner);
+                let mut actual = input.clone();
+                frft.process(&mut actual);
+                assert_close(expected, &actual, 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn test_fractional_fft_continuous_at_quarter_turns() {
+        // The quarter turns are computed exactly, and every other angle with chirps. Both must agree on either side of a quarter turn
+        let mut planner = FftPlanner::new();
+        for &len in &[1, 2, 7, 16, 33] {
+            let input: Vec<Complex<f64>> = random_signal(len);
+            for &angle in &[0f64, FRAC_PI_2, PI, 3f64 * FRAC_PI_2, -FRAC_PI_2] {
+                let mut exact = input.clone();
+                FractionalFft::new(len, angle, &mut planner).process(&mut exact);
+
+                for &offset in &[-1e-9, 1e-9] {
+                    let mut nearby = input.clone();
+                    FractionalFft::new(len, angle + offset, &mut planner).process(&mut nearby);
+                    assert_close(&exact, &nearby, 1e-6);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_fractional_fft_matches_naive() {
+        let mut planner = FftPlanner::new();
+        for &len in &[1, 2, 7, 16, 33] {
+            for &angle in &[0.1, -0.3, 1.0, 2.0, 2.9, 3.5, 4.5, -1.3, 6.0] {
+                let input: Vec<Complex<f64>> = random_signal(len * 2);
+                let frft = FractionalFft::new(len, angle, &mut planner);
+                let mut actual = input.clone();
+                frft.process(&mut actual);
+
+                for (in_chunk, out_chunk) in input.chunks(len).zip(actual.chunks(len)) {
+                    let expected = naive_fractional_fft(in_chunk, angle);
+                    assert_close(&expected, out_chunk, 1e-10);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_fractional_fft_gaussian() {
+        // exp(-pi * x^2) is an eigenfunction of the continuous fractional Fourier transform at every angle,
+        // so a well-sampled gaussian should come back out unchanged
+        let len = 64;
+        let gaussian: Vec<Complex<f64>> = (0..len)
+            .map(|n| {
+                let x = if n < len / 2 {
+                    n as f64
+                } else {
+                    n as f64 - len as f64
+                };
+                Complex::new((-PI * x * x / len as f64).exp(), 0f64)
+            })
+            .collect();
+
+        let mut planner = FftPlanner::new();
+        for i in 0..40 {
+            let angle = i as f64 * 0.17 - 1.0;
+            let frft = FractionalFft::new(len, angle, &mut planner);
+            let mut actual = gaussian.clone();
+            frft.process(&mut actual);
+            assert_close(&gaussian, &actual, 1e-3);
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicPtr, Ordering};
+use std::sync::{Arc, Mutex, Once, PoisonError};
+
+use num_complex::Complex;
+
+use crate::{CachePolicy, Fft, FftDirection, FftNum, FftPlannerSync};
+
+/// The maximum number of FFT instances that the global planner for each numeric type keeps in its cache
+const GLOBAL_PLANNER_MAX_ENTRIES: usize = 256;
+
+type GlobalPlannerMap = HashMap<TypeId, Box<dyn Any + Send>>;
+
+fn global_planners() -> &'static Mutex<GlobalPlannerMap> {
+    static INIT: Once = Once::new();
+    static PLANNERS: AtomicPtr<Mutex<GlobalPlannerMap>> = AtomicPtr::new(std::ptr::null_mut());
+
+    INIT.call_once(|| {
+        let planners = Box::new(Mutex::new(HashMap::new()));
+        PLANNERS.store(Box::into_raw(planners), Ordering::Release);
+    });
+
+    // The map is leaked when it's created, and never freed, so it lives for the rest of the program
+    unsafe { &*PLANNERS.load(Ordering::Acquire) }
+}
+
+/// Returns a `Fft` instance which computes FFTs of size `len`, using a planner shared by the whole process.
+///
+/// The first call for each numeric type creates a [`FftPlannerSync`](crate::FftPlannerSync) for that type, and later calls from any thread
+/// reuse its caches. To keep the caches from growing without bound, the global planner for each type keeps at most 256 FFT instances,
+/// including inner FFTs, and discards the least recently used instances first. FFT instances that have already been returned stay valid either way.
+///
+/// If the provided `direction` is `FftDirection::Forward`, the returned instance will compute forward FFTs. If it's `FftDirection::Inverse`, it will compute inverse FFTs.
+pub fn plan_fft_global<T: FftNum>(len: usize, direction: FftDirection) -> Arc<dyn Fft<T>> {
+    let planner = {
+        let mut planners = global_planners()
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let planner = planners
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| {
+                let planner = FftPlannerSync::<T>::new();
+                planner.set_cache_policy(CachePolicy::MaxEntries(GLOBAL_PLANNER_MAX_ENTRIES));
+                Box::new(Arc::new(planner))
+            })
+            .downcast_ref::<Arc<FftPlannerSync<T>>>()
+            .unwrap();
+        Arc::clone(planner)
+    };
+
+    // Plan outside the lock on the map, so that planning for one numeric type doesn't block planning for the others
+    planner.plan_fft(len, direction)
+}
+
+/// Discards the caches of the global planners used by [`plan_fft_global`](crate::plan_fft_global), [`fft`](crate::fft), and [`ifft`](crate::ifft).
+///
+/// FFT instances that have already been returned stay valid, and the memory they use is freed once they're dropped.
+pub fn clear_global_planners() {
+    global_planners()
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clear();
+}
+
+/// Computes a forward FFT of `buffer` in-place, with a FFT size of `buffer.len()`.
+///
+/// Plans the FFT using the global planner described in [`plan_fft_global`](crate::plan_fft_global), and allocates a scratch buffer.
+/// To compute many FFTs of the same size, it's more efficient to plan a FFT once, and use [`FftProcessor`](crate::FftProcessor) or
+/// [`Fft::process_with_scratch`](crate::Fft::process_with_scratch).
+///
+/// ~~~
+/// use rustfft::num_complex::Complex;
+///
+/// let mut buffer = vec![Complex{ re: 1.0f32, im: 0.0f32 }; 1234];
+/// rustfft::fft(&mut buffer);
+/// ~~~
+pub fn fft<T: FftNum>(buffer: &mut [Complex<T>]) {
+    if !buffer.is_empty() {
+        plan_fft_global(buffer.len(), FftDirection::Forward).process(buffer);
+    }
+}
+
+/// Computes an inverse FFT of `buffer` in-place, with a FFT size of `buffer.len()`.
+///
+/// Like every FFT in RustFFT, the result isn't normalized: computing `fft` and then `ifft` multiplies each element by `buffer.len()`.
+///
+/// Plans the FFT using the global planner described in [`plan_fft_global`](crate::plan_fft_global), and allocates a scratch buffer.
+/// To compute many FFTs of the same size, it's more efficient to plan a FFT once, and use [`FftProcessor`](crate::FftProcessor) or
+/// [`Fft::process_with_scratch`](crate::Fft::process_with_scratch).
+pub fn ifft<T: FftNum>(buffer: &mut [Complex<T>]) {
+    if !buffer.is_empty() {
+        plan_fft_global(buffer.len(), FftDirection::Inverse).process(buffer);
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::algorithm::Dft;
+    use crate::test_utils::{compare_vectors, random_signal};
+
+    #[test]
+    fn test_global_fft() {
+        for &len in &[1, 7, 64, 1234] {
+            let input: Vec<Complex<f64>> = random_signal(len);
+
+            let mut expected = input.clone();
+            Dft::new(len, FftDirection::Forward).process(&mut expected);
+            let mut buffer = input.clone();
+            fft(&mut buffer);
+            assert!(
+                compare_vectors(&expected, &buffer),
+                "fft() failed, len = {}",
+                len
+            );
+
+            Dft::new(len, FftDirection::Inverse).process(&mut expected);
+            ifft(&mut buffer);
+            assert!(
+                compare_vectors(&expected, &buffer),
+                "ifft() failed, len = {}",
+                len
+            );
+        }
+
+        // Empty buffers have nothing to transform
+        fft::<f32>(&mut []);
+    }
+
+    #[test]
+    fn test_global_planner_cache() {
+        // This is the only test that plans f32 FFTs with the global planner, so other tests can't fill or clear its cache in between
+        let fft_a = plan_fft_global::<f32>(100, FftDirection::Forward);
+        let fft_b = plan_fft_global::<f32>(100, FftDirection::Forward);
+        assert!(Arc::ptr_eq(&fft_a, &fft_b), "Existing fft was not reused");
+
+        // Planning more sizes than the limit should discard the least recently used instances
+        for len in 1..=GLOBAL_PLANNER_MAX_ENTRIES {
+            plan_fft_global::<f32>(len + 100, FftDirection::Forward);
+        }
+        let fft_c = plan_fft_global::<f32>(100, FftDirection::Forward);
+        assert!(
+            !Arc::ptr_eq(&fft_a, &fft_c),
+            "Least recently used fft was not discarded after reaching the limit"
+        );
+
+        clear_global_planners();
+        let fft_d = plan_fft_global::<f32>(100, FftDirection::Forward);
+        assert!(
+            !Arc::ptr_eq(&fft_c, &fft_d),
+            "Cache was not discarded by clear_global_planners()"
+        );
+    }
+}
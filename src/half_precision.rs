@@ -0,0 +1,503 @@
+use std::sync::Arc;
+
+use half::{bf16, f16};
+use num_complex::Complex;
+use num_traits::Zero;
+
+use crate::algorithm::butterflies::{
+    Butterfly16, Butterfly2, Butterfly3, Butterfly4, Butterfly5, Butterfly6, Butterfly7, Butterfly8,
+};
+use crate::array_utils::{self, LoadComplex, StoreComplex};
+use crate::common::{fft_error_inplace, fft_error_outofplace};
+use crate::{twiddles, Direction, Fft, FftDirection, Length};
+
+/// A half-precision floating point type that can be used as FFT storage. Implemented for [`f16`](half::f16) and [`bf16`](half::bf16).
+pub trait HalfFloat: Copy + Send + Sync + 'static {
+    /// Converts this value to `f32`, without any loss of precision
+    fn to_f32(self) -> f32;
+
+    /// Converts an `f32` to this type, rounding to the nearest representable value
+    fn from_f32(value: f32) -> Self;
+}
+
+impl HalfFloat for f16 {
+    #[inline(always)]
+    fn to_f32(self) -> f32 {
+        f16::to_f32(self)
+    }
+    #[inline(always)]
+    fn from_f32(value: f32) -> Self {
+        f16::from_f32(value)
+    }
+}
+
+impl HalfFloat for bf16 {
+    #[inline(always)]
+    fn to_f32(self) -> f32 {
+        bf16::to_f32(self)
+    }
+    #[inline(always)]
+    fn from_f32(value: f32) -> Self {
+        bf16::from_f32(value)
+    }
+}
+
+/// Computes FFTs of half-precision buffers, using an `f32` FFT internally
+///
+/// The conversions to and from `f32` are part of the FFT's first and last passes, rather than separate passes over the data. The
+/// first pass computes butterflies of size `first_pass_len` on the half-precision input, converting each element as it's loaded,
+/// and writes the twiddled `f32` results into a working buffer. The inner FFT then computes the remaining FFTs in-place in the
+/// working buffer, and the last pass transposes the working buffer into the output, converting each element as it's stored.
+/// So the scratch buffer holds `self.len()` elements for the working buffer, plus the inner FFT's in-place scratch. Only one
+/// chunk is in `f32` at a time, so there's never an `f32` copy of the whole buffer.
+///
+/// If `first_pass_len` is 1, the first pass has no butterflies to compute, so it only converts the input into the working buffer.
+///
+/// Intermediate results are computed in `f32`, so the only precision lost is the final rounding to half precision. Keep in mind
+/// that unnormalized FFT outputs grow with the FFT size, and `f16` overflows above 65504.
+///
+/// ~~~
+/// // Computes a forward FFT of size 1234, on f16 data
+/// use rustfft::half::f16;
+/// use rustfft::num_complex::Complex;
+/// use rustfft::FftPlanner;
+///
+/// let mut buffer = vec![Complex{ re: f16::from_f32(0.0), im: f16::from_f32(0.0) }; 1234];
+///
+/// let mut planner = FftPlanner::<f32>::new();
+/// let fft = planner.plan_fft_half_precision_forward::<f16>(1234);
+/// fft.process(&mut buffer);
+/// ~~~
+pub struct HalfPrecisionFft<H> {
+    first_pass: FirstPass,
+    inner_fft: Arc<dyn Fft<f32>>,
+    twiddles: Box<[Complex<f32>]>,
+    len: usize,
+    scratch_len: usize,
+    _storage: std::marker::PhantomData<H>,
+}
+
+impl<H: HalfFloat> HalfPrecisionFft<H> {
+    /// Creates a FFT instance which computes FFTs of size `first_pass_len * inner_fft.len()` on half-precision data. The first pass
+    /// computes butterflies of size `first_pass_len` directly on the half-precision input, and `inner_fft` computes the rest in `f32`.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `first_pass_len` isn't 1, 2, 3, 4, 5, 6, 7, 8, or 16.
+    pub fn new(first_pass_len: usize, inner_fft: Arc<dyn Fft<f32>>) -> Self {
+        let direction = inner_fft.fft_direction();
+        let first_pass = FirstPass::new(first_pass_len, direction);
+
+        let width = first_pass_len;
+        let height = inner_fft.len();
+        let len = width * height;
+
+        // The first pass multiplies output `x` of the butterfly on column `y` by twiddle `x * y`. There's nothing to multiply if there's no butterfly
+        let mut twiddles = Vec::new();
+        if width > 1 {
+            twiddles.reserve(len);
+            for y in 0..height {
+                for x in 0..width {
+                    twiddles.push(twiddles::compute_twiddle(x * y, len, direction));
+                }
+            }
+        }
+
+        // The working buffer holds the whole chunk in f32, followed by the inner FFT's scratch
+        let scratch_len = len + inner_fft.get_inplace_scratch_len();
+        Self {
+            first_pass,
+            inner_fft,
+            twiddles: twiddles.into_boxed_slice(),
+            len,
+            scratch_len,
+            _storage: std::marker::PhantomData,
+        }
+    }
+
+    /// Returns the size of the scratch buffer required by `process_with_scratch` and `process_outofplace_with_scratch`
+    ///
+    /// This is `self.len()`, for the `f32` working buffer, plus the inner FFT's in-place scratch length.
+    pub fn get_scratch_len(&self) -> usize {
+        self.scratch_len
+    }
+
+    /// Computes a FFT in-place.
+    ///
+    /// Convenience method that allocates a scratch buffer of the size returned by `get_scratch_len()`.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `buffer.len() % self.len() > 0`, or if `buffer.len() < self.len()`
+    pub fn process(&self, buffer: &mut [Complex<H>]) {
+        let mut scratch = vec![Complex::zero(); self.get_scratch_len()];
+        self.process_with_scratch(buffer, &mut scratch);
+    }
+
+    /// Divides `buffer` into chunks of size `self.len()`, and computes a FFT on each chunk.
+    ///
+    /// Uses the `scratch` buffer as scratch space, so the contents of `scratch` should be considered garbage
+    /// after calling.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if:
+    /// - `buffer.len() % self.len() > 0`
+    /// - `buffer.len() < self.len()`
+    /// - `scratch.len() < self.get_scratch_len()`
+    pub fn process_with_scratch(&self, buffer: &mut [Complex<H>], scratch: &mut [Complex<f32>]) {
+        let len = self.len();
+        if len == 0 {
+            return;
+        }
+        if scratch.len() < self.scratch_len || buffer.len() < len {
+            fft_error_inplace(len, buffer.len(), self.scratch_len, scratch.len());
+            return; // Unreachable, because fft_error_inplace asserts, but it helps codegen to put it here
+        }
+
+        let scratch = &mut scratch[..self.scratch_len];
+        let result = array_utils::iter_chunks(buffer, len, |chunk| {
+            let (working_buffer, inner_scratch) = scratch.split_at_mut(len);
+            self.perform_fft(chunk, working_buffer, inner_scratch);
+            self.perform_last_pass(working_buffer, chunk);
+        });
+
+        if result.is_err() {
+            // We want to trigger a panic, but we want to avoid doing it in this function to reduce code size, so call a function marked cold and inline(never) that will do it for us
+            fft_error_inplace(len, buffer.len(), self.scratch_len, scratch.len());
+        }
+    }
+
+    /// Divides `input` and `output` into chunks of size `self.len()`, and computes a FFT on each chunk.
+    ///
+    /// Unlike [`Fft::process_outofplace_with_scratch`](crate::Fft::process_outofplace_with_scratch), `input` isn't modified.
+    /// Uses the `scratch` buffer as scratch space, so the contents of `scratch` should be considered garbage after calling.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if:
+    /// - `output.len() != input.len()`
+    /// - `input.len() % self.len() > 0`
+    /// - `input.len() < self.len()`
+    /// - `scratch.len() < self.get_scratch_len()`
+    pub fn process_outofplace_with_scratch(
+        &self,
+        input: &[Complex<H>],
+        output: &mut [Complex<H>],
+        scratch: &mut [Complex<f32>],
+    ) {
+        let len = self.len();
+        if len == 0 {
+            return;
+        }
+        if scratch.len() < self.scratch_len || input.len() < len || output.len() != input.len() {
+            fft_error_outofplace(
+                len,
+                input.len(),
+                output.len(),
+                self.scratch_len,
+                scratch.len(),
+            );
+            return; // Unreachable, because fft_error_outofplace asserts, but it helps codegen to put it here
+        }
+
+        let scratch = &mut scratch[..self.scratch_len];
+        let mut remaining_input = input;
+        let result = array_utils::iter_chunks(output, len, |output_chunk| {
+            let (input_chunk, tail) = remaining_input.split_at(len);
+            remaining_input = tail;
+
+            let (working_buffer, inner_scratch) = scratch.split_at_mut(len);
+            self.perform_fft(input_chunk, working_buffer, inner_scratch);
+            self.perform_last_pass(working_buffer, output_chunk);
+        });
+
+        if result.is_err() {
+            // We want to trigger a panic, but we want to avoid doing it in this function to reduce code size, so call a function marked cold and inline(never) that will do it for us
+            fft_error_outofplace(
+                len,
+                input.len(),
+                output.len(),
+                self.scratch_len,
+                scratch.len(),
+            );
+        }
+    }
+
+    // Computes everything but the last pass: the first pass's butterflies, from `input` into `working_buffer`, and then the inner FFTs.
+    // Afterwards, row `x` of `working_buffer` holds the outputs `x`, `x + width`, `x + 2 * width`, etc
+    fn perform_fft(
+        &self,
+        input: &[Complex<H>],
+        working_buffer: &mut [Complex<f32>],
+        inner_scratch: &mut [Complex<f32>],
+    ) {
+        let height = self.inner_fft.len();
+        if self.twiddles.is_empty() {
+            for (working, input) in working_buffer.iter_mut().zip(input.iter()) {
+                *working = Complex::new(input.re.to_f32(), input.im.to_f32());
+            }
+        } else {
+            // Column `y` is every `height`th input, starting at `y`. Output `x` of its butterfly goes to row `x`, column `y` of the working buffer
+            let width = self.len / height;
+            for (column, twiddles) in self.twiddles.chunks_exact(width).enumerate() {
+                unsafe {
+                    let input = StridedHalfInput {
+                        ptr: input.as_ptr().add(column),
+                        stride: height,
+                    };
+                    let output = TwiddledStridedOutput {
+                        ptr: working_buffer.as_mut_ptr().add(column),
+                        stride: height,
+                        twiddles: twiddles.as_ptr(),
+                    };
+                    self.first_pass.perform_fft(input, output);
+                }
+            }
+        }
+
+        self.inner_fft
+            .process_with_scratch(working_buffer, inner_scratch);
+    }
+
+    // Transposes `working_buffer` into `output`, converting each element back to half precision
+    fn perform_last_pass(&self, working_buffer: &[Complex<f32>], output: &mut [Complex<H>]) {
+        let height = self.inner_fft.len();
+        let width = self.len / height;
+        for (y, output_row) in output.chunks_exact_mut(width).enumerate() {
+            for (x, output) in output_row.iter_mut().enumerate() {
+                let value = working_buffer[x * height + y];
+                *output = Complex::new(H::from_f32(value.re), H::from_f32(value.im));
+            }
+        }
+    }
+}
+impl<H> Length for HalfPrecisionFft<H> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+impl<H> Direction for HalfPrecisionFft<H> {
+    fn fft_direction(&self) -> FftDirection {
+        self.inner_fft.fft_direction()
+    }
+}
+
+// The butterflies the first pass can compute on half-precision input
+enum FirstPass {
+    Convert,
+    Butterfly2(Butterfly2<f32>),
+    Butterfly3(Butterfly3<f32>),
+    Butterfly4(Butterfly4<f32>),
+    Butterfly5(Butterfly5<f32>),
+    Butterfly6(Butterfly6<f32>),
+    Butterfly7(Butterfly7<f32>),
+    Butterfly8(Butterfly8<f32>),
+    Butterfly16(Butterfly16<f32>),
+}
+impl FirstPass {
+    fn new(len: usize, direction: FftDirection) -> Self {
+        match len {
+            1 => FirstPass::Convert,
+            2 => FirstPass::Butterfly2(Butterfly2::new(direction)),
+            3 => FirstPass::Butterfly3(Butterfly3::new(direction)),
+            4 => FirstPass::Butterfly4(Butterfly4::new(direction)),
+            5 => FirstPass::Butterfly5(Butterfly5::new(direction)),
+            6 => FirstPass::Butterfly6(Butterfly6::new(direction)),
+            7 => FirstPass::Butterfly7(Butterfly7::new(direction)),
+            8 => FirstPass::Butterfly8(Butterfly8::new(direction)),
+            16 => FirstPass::Butterfly16(Butterfly16::new(direction)),
+            _ => panic!(
+                "Unsupported first pass length. Expected 1, 2, 3, 4, 5, 6, 7, 8, or 16, got {}",
+                len
+            ),
+        }
+    }
+
+    #[inline(always)]
+    unsafe fn perform_fft(&self, input: impl LoadComplex<f32>, output: impl StoreComplex<f32>) {
+        match self {
+            FirstPass::Convert => output.store(input.load(0), 0),
+            FirstPass::Butterfly2(fft) => fft.perform_fft_contiguous(input, output),
+            FirstPass::Butterfly3(fft) => fft.perform_fft_contiguous(input, output),
+            FirstPass::Butterfly4(fft) => fft.perform_fft_contiguous(input, output),
+            FirstPass::Butterfly5(fft) => fft.perform_fft_contiguous(input, output),
+            FirstPass::Butterfly6(fft) => fft.perform_fft_contiguous(input, output),
+            FirstPass::Butterfly7(fft) => fft.perform_fft_contiguous(input, output),
+            FirstPass::Butterfly8(fft) => fft.perform_fft_contiguous(input, output),
+            FirstPass::Butterfly16(fft) => fft.perform_fft_contiguous(input, output),
+        }
+    }
+}
+
+// Loads every `stride`th element of a half-precision buffer, converting it to f32
+#[derive(Copy, Clone)]
+struct StridedHalfInput<H> {
+    ptr: *const Complex<H>,
+    stride: usize,
+}
+impl<H: HalfFloat> LoadComplex<f32> for StridedHalfInput<H> {
+    #[inline(always)]
+    unsafe fn load(&self, index: usize) -> Complex<f32> {
+        let value = *self.ptr.add(index * self.stride);
+        Complex::new(value.re.to_f32(), value.im.to_f32())
+    }
+}
+
+// Multiplies each element by its twiddle factor, and stores it to every `stride`th element of a f32 buffer
+#[derive(Copy, Clone)]
+struct TwiddledStridedOutput {
+    ptr: *mut Complex<f32>,
+    stride: usize,
+    twiddles: *const Complex<f32>,
+}
+impl StoreComplex<f32> for TwiddledStridedOutput {
+    #[inline(always)]
+    unsafe fn store(&self, value: Complex<f32>, index: usize) {
+        *self.ptr.add(index * self.stride) = value * *self.twiddles.add(index);
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::algorithm::Dft;
+    use crate::test_utils::random_signal;
+    use crate::FftPlanner;
+
+    fn check_half_precision<H: HalfFloat>(fft: &HalfPrecisionFft<H>, tolerance: f64) {
+        let len = fft.len();
+        let direction = fft.fft_direction();
+
+        // process a few chunks at once, to make sure batches work
+        let input: Vec<Complex<H>> = random_signal::<f32>(len * 3)
+            .iter()
+            .map(|c| Complex::new(H::from_f32(c.re), H::from_f32(c.im)))
+            .collect();
+
+        // The reference computes the FFT of the exact half-precision inputs, in f64
+        let mut expected: Vec<Complex<f64>> = input
+            .iter()
+            .map(|c| Complex::new(c.re.to_f32() as f64, c.im.to_f32() as f64))
+            .collect();
+        Dft::new(len, direction).process(&mut expected);
+
+        let check = |actual: &[Complex<H>], name: &str| {
+            let mut error = 0.0;
+            let mut magnitude = 0.0;
+            for (actual, expected) in actual.iter().zip(expected.iter()) {
+                let actual = Complex::new(actual.re.to_f32() as f64, actual.im.to_f32() as f64);
+                error += (actual - expected).norm_sqr();
+                magnitude += expected.norm_sqr();
+            }
+            let relative_error = (error / magnitude).sqrt();
+            assert!(
+                relative_error < tolerance,
+                "{} failed, len = {}, direction = {}, relative error = {}",
+                name,
+                len,
+                direction,
+                relative_error
+            );
+        };
+
+        // Use exactly the advertised amount of scratch, filled with garbage
+        let mut buffer = input.clone();
+        let mut scratch = vec![Complex::new(100.0, 100.0); fft.get_scratch_len()];
+        fft.process_with_scratch(&mut buffer, &mut scratch);
+        check(&buffer, "process_with_scratch()");
+
+        let mut output = vec![Complex::new(H::from_f32(0.0), H::from_f32(0.0)); len * 3];
+        let mut scratch = vec![Complex::new(100.0, 100.0); fft.get_scratch_len()];
+        fft.process_outofplace_with_scratch(&input, &mut output, &mut scratch);
+        check(&output, "process_outofplace_with_scratch()");
+
+        let mut buffer = input.clone();
+        fft.process(&mut buffer);
+        check(&buffer, "process()");
+    }
+
+    #[test]
+    fn test_half_precision_fft() {
+        let mut planner = FftPlanner::<f32>::new();
+        for &len in &[1, 2, 16, 30, 64, 97, 1000] {
+            for &direction in &[FftDirection::Forward, FftDirection::Inverse] {
+                let fft = planner.plan_fft_half_precision::<f16>(len, direction);
+                assert_eq!(fft.len(), len);
+                assert_eq!(fft.fft_direction(), direction);
+                check_half_precision(&fft, 1e-3);
+
+                let fft = planner.plan_fft_half_precision::<bf16>(len, direction);
+                check_half_precision(&fft, 1e-2);
+            }
+        }
+    }
+
+    #[test]
+    fn test_half_precision_first_pass_lens() {
+        for &first_pass_len in &[1, 2, 3, 4, 5, 6, 7, 8, 16] {
+            for &inner_len in &[1, 6] {
+                for &direction in &[FftDirection::Forward, FftDirection::Inverse] {
+                    let inner_fft = Arc::new(Dft::new(inner_len, direction));
+                    let fft = HalfPrecisionFft::<f16>::new(first_pass_len, inner_fft);
+                    assert_eq!(fft.len(), first_pass_len * inner_len);
+                    check_half_precision(&fft, 1e-3);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_half_precision_scratch_len() {
+        // The working buffer holds one chunk in f32, and the inner FFT works in-place inside it
+        let inner_fft: Arc<dyn Fft<f32>> = Arc::new(Dft::new(12, FftDirection::Forward));
+        let inner_scratch_len = inner_fft.get_inplace_scratch_len();
+        let fft = HalfPrecisionFft::<f16>::new(8, inner_fft);
+        assert_eq!(fft.get_scratch_len(), 96 + inner_scratch_len);
+    }
+
+    #[test]
+    fn test_half_precision_zero_len() {
+        let mut planner = FftPlanner::<f32>::new();
+        let fft = planner.plan_fft_half_precision_forward::<f16>(0);
+        assert_eq!(fft.len(), 0);
+        fft.process(&mut []);
+        fft.process_outofplace_with_scratch(&[], &mut [], &mut []);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unsupported first pass length")]
+    fn test_half_precision_unsupported_first_pass() {
+        let inner_fft = Arc::new(Dft::new(4, FftDirection::Forward));
+        HalfPrecisionFft::<f16>::new(9, inner_fft);
+    }
+
+    fn half_buffer(len: usize) -> Vec<Complex<f16>> {
+        vec![Complex::new(f16::from_f32(0.0), f16::from_f32(0.0)); len]
+    }
+
+    #[test]
+    #[should_panic(expected = "Input FFT buffer must be a multiple of FFT length")]
+    fn test_half_precision_partial_chunk() {
+        let fft = FftPlanner::<f32>::new().plan_fft_half_precision_forward::<f16>(16);
+        fft.process(&mut half_buffer(17));
+    }
+
+    #[test]
+    #[should_panic(expected = "Not enough scratch space was provided")]
+    fn test_half_precision_small_scratch() {
+        let fft = FftPlanner::<f32>::new().plan_fft_half_precision_forward::<f16>(16);
+        let mut scratch = vec![Complex::new(0.0, 0.0); fft.get_scratch_len() - 1];
+        fft.process_with_scratch(&mut half_buffer(16), &mut scratch);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Provided FFT input buffer and output buffer must have the same length"
+    )]
+    fn test_half_precision_mismatched_output() {
+        let fft = FftPlanner::<f32>::new().plan_fft_half_precision_forward::<f16>(16);
+        let mut scratch = vec![Complex::new(0.0, 0.0); fft.get_scratch_len()];
+        fft.process_outofplace_with_scratch(&half_buffer(32), &mut half_buffer(16), &mut scratch);
+    }
+}
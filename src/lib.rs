@@ -52,6 +52,11 @@
 //!     client CPU supports AVX, while disabling it reduces compile time and binary size.
 //!     On every other platform, this feature does nothing, and RustFFT will behave like it's not set.
 //!
+//! * `half` (Disabled by default)
+//!
+//!     Enables the [`half_precision`](crate::half_precision) module, which computes FFTs of `f16` and `bf16` buffers from the
+//!     [`half`](https://crates.io/crates/half) crate, converting to `f32` internally.
+//!
 //! ### Normalization
 //!
 //! RustFFT does not normalize outputs. Callers must manually normalize the results by scaling each element by
@@ -85,28 +90,72 @@
 //! Our advice is to start by trying the size that's most convenient to your application.
 //! If that's too slow, see if you can find a nearby size whose prime factors are all 11 or smaller, and you can expect a 2x-5x speedup.
 //! If that's still too slow, find a nearby size whose prime factors are all 2 or 3, and you can expect a 1.1x-1.5x speedup.
+//! [`FftPlanner::next_fast_len`](crate::FftPlanner::next_fast_len) and [`FftPlanner::prev_fast_len`](crate::FftPlanner::prev_fast_len)
+//! find these sizes for you: with AVX, they search sizes whose prime factors are all 2 or 3, and without AVX, sizes whose prime factors are all 11 or smaller.
 
+use std::collections::HashSet;
 use std::fmt::Display;
+use std::sync::Arc;
 
 pub use num_complex;
 pub use num_traits;
 
+#[cfg(feature = "half")]
+pub use half;
+
 #[macro_use]
 mod common;
 
 /// Individual FFT algorithms
 pub mod algorithm;
 mod array_utils;
+mod cost_model;
 mod fft_cache;
+mod global;
 mod math_utils;
 mod plan;
+mod processor;
 mod twiddles;
 
+/// The Chirp-Z transform and zoom FFT, for evaluating the z-transform along spiral contours
+pub mod chirp_z;
+
+/// Fixed-point Q15 and Q31 FFTs, for bit-exact reference implementations of DSP code
+pub mod fixed_point;
+
+/// The fractional Fourier transform, for rotating signals by arbitrary angles in the time-frequency plane
+pub mod fractional_fft;
+
+/// Number-theoretic transforms, for exact integer convolution modulo primes
+///
+/// Sizes with a large prime factor `p` are computed with a `O(p^2)` DFT for that factor, see [`Ntt`](crate::ntt::Ntt).
+pub mod ntt;
+
+/// Half-precision FFT storage, converting to and from `f32` internally
+#[cfg(feature = "half")]
+pub mod half_precision;
+
+/// Non-uniform FFTs, for transforming between non-uniformly spaced samples and uniformly spaced frequencies
+pub mod nufft;
+
+/// Goertzel algorithm and sliding DFT, for computing individual DFT bins
+pub mod single_bin;
+
+/// Power spectral density estimation using Welch's method
+pub mod welch;
+
 use num_complex::Complex;
 use num_traits::Zero;
 
+pub use crate::array_utils::{interleaved_as_complex, interleaved_as_complex_mut};
 pub use crate::common::FftNum;
-pub use crate::plan::{FftPlanner, FftPlannerScalar};
+pub use crate::cost_model::{CostModel, FftEstimate};
+pub use crate::fft_cache::CachePolicy;
+pub use crate::global::{clear_global_planners, fft, ifft, plan_fft_global};
+pub use crate::plan::{
+    FftPlanner, FftPlannerScalar, FftPlannerSync, PlannerAlgorithm, PlannerOptions,
+};
+pub use crate::processor::FftProcessor;
 
 /// A trait that allows FFT algorithms to report their expected input/output size
 pub trait Length {
@@ -213,6 +262,329 @@ pub trait Fft<T: FftNum>: Length + Direction + Sync + Send {
     ///
     /// For many FFT sizes, out-of-place FFTs require zero scratch, and this method will return zero - although that may change from one RustFFT version to the next.
     fn get_outofplace_scratch_len(&self) -> usize;
+
+    /// Computes a FFT in-place, on data stored in split-complex format: the real parts in `real`, and the imaginary parts in `imag`.
+    ///
+    /// Convenience method that allocates a `Vec` with the required scratch space and calls `self.process_split_with_scratch`.
+    /// If you want to re-use that allocation across multiple FFT computations, consider calling `process_split_with_scratch` instead.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if:
+    /// - `real.len() != imag.len()`
+    /// - `real.len() % self.len() > 0`
+    /// - `real.len() < self.len()`
+    fn process_split(&self, real: &mut [T], imag: &mut [T]) {
+        let mut scratch = vec![Complex::zero(); self.get_split_scratch_len()];
+        self.process_split_with_scratch(real, imag, &mut scratch);
+    }
+
+    /// Divides `real` and `imag` into chunks of size `self.len()`, and computes a FFT on each pair of chunks, where `real` holds the real parts and
+    /// `imag` holds the imaginary parts.
+    ///
+    /// Butterflies, [`Dft`](crate::algorithm::Dft), [`Radix4`](crate::algorithm::Radix4), and the AVX butterflies that need no scratch compute split-complex FFTs
+    /// natively, reading and writing `real` and `imag` directly. Other algorithms interleave each chunk into `scratch`, transform it, and split it back out.
+    /// Uses the `scratch` buffer as scratch space, so the contents of `scratch` should be considered garbage after calling.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if:
+    /// - `real.len() != imag.len()`
+    /// - `real.len() % self.len() > 0`
+    /// - `real.len() < self.len()`
+    /// - `scratch.len() < self.get_split_scratch_len()`
+    fn process_split_with_scratch(
+        &self,
+        real: &mut [T],
+        imag: &mut [T],
+        scratch: &mut [Complex<T>],
+    ) {
+        let len = self.len();
+        let required_scratch = self.get_split_scratch_len();
+        if real.len() != imag.len() {
+            common::fft_error_split(len, real.len(), imag.len(), required_scratch, scratch.len());
+            return; // Unreachable, because fft_error_split asserts, but it helps codegen to put it here
+        }
+        if len == 0 {
+            return;
+        }
+        if scratch.len() < required_scratch || real.len() < len {
+            common::fft_error_split(len, real.len(), imag.len(), required_scratch, scratch.len());
+            return; // Unreachable, because fft_error_split asserts, but it helps codegen to put it here
+        }
+
+        let (chunk_buffer, inner_scratch) = scratch[..required_scratch].split_at_mut(len);
+        let result = array_utils::iter_chunks_zipped(real, imag, len, |real_chunk, imag_chunk| {
+            for ((element, re), im) in chunk_buffer
+                .iter_mut()
+                .zip(real_chunk.iter())
+                .zip(imag_chunk.iter())
+            {
+                *element = Complex::new(*re, *im);
+            }
+
+            self.process_with_scratch(chunk_buffer, inner_scratch);
+
+            for ((element, re), im) in chunk_buffer
+                .iter()
+                .zip(real_chunk.iter_mut())
+                .zip(imag_chunk.iter_mut())
+            {
+                *re = element.re;
+                *im = element.im;
+            }
+        });
+
+        if result.is_err() {
+            // We want to trigger a panic, but we want to avoid doing it in this function to reduce code size, so call a function marked cold and inline(never) that will do it for us
+            common::fft_error_split(len, real.len(), imag.len(), required_scratch, scratch.len());
+        }
+    }
+
+    /// Returns the size of the scratch buffer required by `process_split_with_scratch`
+    ///
+    /// For algorithms that compute split-complex FFTs natively, this is the same as `get_inplace_scratch_len()`. For other algorithms,
+    /// it's `self.len()`, for the interleaved copy of one chunk, plus `self.get_inplace_scratch_len()`.
+    fn get_split_scratch_len(&self) -> usize {
+        self.len() + self.get_inplace_scratch_len()
+    }
+
+    /// Computes a FFT in-place, on interleaved data: `[re0, im0, re1, im1, ...]`.
+    ///
+    /// Convenience method that allocates a `Vec` with the required scratch space and calls `self.process_interleaved_with_scratch`.
+    /// If you want to re-use that allocation across multiple FFT computations, consider calling `process_interleaved_with_scratch` instead.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if:
+    /// - `buffer.len()` is odd, or `buffer` isn't aligned to the alignment of `Complex<T>`
+    /// - `buffer.len() / 2 % self.len() > 0`
+    /// - `buffer.len() / 2 < self.len()`
+    fn process_interleaved(&self, buffer: &mut [T]) {
+        self.process(array_utils::interleaved_as_complex_mut(buffer));
+    }
+
+    /// Divides the interleaved `buffer` into chunks of `2 * self.len()` scalars, and computes a FFT on each chunk.
+    ///
+    /// The buffer is reinterpreted as complex numbers without copying, as if by [`interleaved_as_complex_mut`](crate::interleaved_as_complex_mut).
+    /// Uses the `scratch` buffer as scratch space, so the contents of `scratch` should be considered garbage after calling.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if:
+    /// - `buffer.len()` is odd, or `buffer` isn't aligned to the alignment of `Complex<T>`
+    /// - `buffer.len() / 2 % self.len() > 0`
+    /// - `buffer.len() / 2 < self.len()`
+    /// - `scratch.len() < self.get_inplace_scratch_len()`
+    fn process_interleaved_with_scratch(&self, buffer: &mut [T], scratch: &mut [Complex<T>]) {
+        self.process_with_scratch(array_utils::interleaved_as_complex_mut(buffer), scratch);
+    }
+
+    /// Divides the interleaved `input` and `output` into chunks of `2 * self.len()` scalars, and computes a FFT on each chunk.
+    ///
+    /// The buffers are reinterpreted as complex numbers without copying, as if by [`interleaved_as_complex_mut`](crate::interleaved_as_complex_mut).
+    /// This method uses both the `input` buffer and `scratch` buffer as scratch space, so the contents of both should be
+    /// considered garbage after calling.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if:
+    /// - `input.len()` or `output.len()` is odd, or either buffer isn't aligned to the alignment of `Complex<T>`
+    /// - `output.len() != input.len()`
+    /// - `input.len() / 2 % self.len() > 0`
+    /// - `input.len() / 2 < self.len()`
+    /// - `scratch.len() < self.get_outofplace_scratch_len()`
+    fn process_outofplace_interleaved_with_scratch(
+        &self,
+        input: &mut [T],
+        output: &mut [T],
+        scratch: &mut [Complex<T>],
+    ) {
+        self.process_outofplace_with_scratch(
+            array_utils::interleaved_as_complex_mut(input),
+            array_utils::interleaved_as_complex_mut(output),
+            scratch,
+        );
+    }
+
+    /// Returns the number of heap bytes owned directly by this instance: its twiddle factors, and any other precomputed data.
+    ///
+    /// Inner FFTs aren't included, because they may be shared with other FFT instances. Neither are scratch buffers, which are
+    /// provided by the caller. Use [`heap_bytes`](crate::Fft::heap_bytes) to include inner FFTs.
+    ///
+    /// The default implementation returns zero, which is correct for algorithms that store everything inline, such as butterflies.
+    fn own_heap_bytes(&self) -> usize {
+        0
+    }
+
+    /// Returns the inner FFT instances that this instance computes its FFT with, IE the row and column FFTs of a mixed radix FFT.
+    ///
+    /// The default implementation returns an empty `Vec`, which is correct for algorithms that don't use any inner FFTs.
+    fn inner_ffts(&self) -> Vec<Arc<dyn Fft<T>>> {
+        Vec::new()
+    }
+
+    /// Returns the number of heap bytes owned by this instance and all of its inner FFTs.
+    ///
+    /// Inner FFTs are often shared, both between FFT instances and within a single instance, such as a mixed radix FFT whose width and height are equal.
+    /// Each inner FFT instance is counted once, no matter how many times it appears in the tree of inner FFTs.
+    fn heap_bytes(&self) -> usize {
+        self.heap_bytes_breakdown().total_bytes
+    }
+
+    /// Returns a breakdown of the heap bytes owned by this instance, and by each instance in its tree of inner FFTs.
+    ///
+    /// Each inner FFT instance is broken down the first time it appears in the tree. Later appearances of the same instance are
+    /// marked as [`repeated`](crate::FftMemoryReport::repeated), and don't count toward any totals.
+    fn heap_bytes_breakdown(&self) -> FftMemoryReport {
+        let mut visited = HashSet::new();
+        FftMemoryReport::build(
+            self.len(),
+            self.fft_direction(),
+            self.own_heap_bytes(),
+            self.inner_ffts(),
+            &mut visited,
+        )
+    }
+}
+
+/// A breakdown of the heap memory owned by a FFT instance and its inner FFTs, created by [`Fft::heap_bytes_breakdown`](crate::Fft::heap_bytes_breakdown).
+///
+/// ~~~
+/// // Find out how much memory a FFT of size 1200 holds
+/// use rustfft::FftPlanner;
+///
+/// let mut planner = FftPlanner::<f32>::new();
+/// let fft = planner.plan_fft_forward(1200);
+///
+/// let report = fft.heap_bytes_breakdown();
+/// assert_eq!(report.total_bytes, fft.heap_bytes());
+/// for inner in &report.inner_ffts {
+///     println!("inner FFT of size {} holds {} bytes", inner.len, inner.total_bytes);
+/// }
+/// ~~~
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FftMemoryReport {
+    /// The size of this FFT
+    pub len: usize,
+    /// The direction of this FFT
+    pub direction: FftDirection,
+    /// The heap bytes owned directly by this FFT instance, as returned by [`Fft::own_heap_bytes`](crate::Fft::own_heap_bytes). Zero if `repeated` is true.
+    pub own_bytes: usize,
+    /// The heap bytes owned by this FFT instance and its inner FFTs, counting each instance once. Zero if `repeated` is true.
+    pub total_bytes: usize,
+    /// True if this FFT instance already appeared earlier in the breakdown, where its bytes were counted.
+    pub repeated: bool,
+    /// Breakdowns of this FFT's inner FFTs. Empty if `repeated` is true.
+    pub inner_ffts: Vec<FftMemoryReport>,
+}
+impl FftMemoryReport {
+    fn build<T: FftNum>(
+        len: usize,
+        direction: FftDirection,
+        own_bytes: usize,
+        inner_ffts: Vec<Arc<dyn Fft<T>>>,
+        visited: &mut HashSet<usize>,
+    ) -> Self {
+        let inner_ffts: Vec<Self> = inner_ffts
+            .iter()
+            .map(|inner| {
+                // Clones of an Arc all point to the same instance, so the instance's address identifies it
+                let address = &**inner as *const dyn Fft<T> as *const u8 as usize;
+                if visited.insert(address) {
+                    Self::build(
+                        inner.len(),
+                        inner.fft_direction(),
+                        inner.own_heap_bytes(),
+                        inner.inner_ffts(),
+                        visited,
+                    )
+                } else {
+                    Self {
+                        len: inner.len(),
+                        direction: inner.fft_direction(),
+                        own_bytes: 0,
+                        total_bytes: 0,
+                        repeated: true,
+                        inner_ffts: Vec::new(),
+                    }
+                }
+            })
+            .collect();
+
+        Self {
+            len,
+            direction,
+            own_bytes,
+            total_bytes: own_bytes
+                + inner_ffts
+                    .iter()
+                    .map(|inner| inner.total_bytes)
+                    .sum::<usize>(),
+            repeated: false,
+            inner_ffts,
+        }
+    }
+}
+
+/// Trait for algorithms that compute only a subset of a FFT's outputs.
+///
+/// When only some outputs of a FFT are needed, IE a band of bins in a channelizer, an output-pruned FFT skips the butterflies
+/// whose results never reach any of the needed outputs. Output-pruned FFTs are created by
+/// [`FftPlanner::plan_fft_output_pruned`](crate::FftPlanner::plan_fft_output_pruned) and [`FftPlanner::plan_fft_output_range`](crate::FftPlanner::plan_fft_output_range).
+///
+/// An output-pruned FFT may compute more outputs than were requested, if that's what its algorithm naturally produces.
+/// After processing, each output for which [`computes_output`](crate::OutputPrunedFft::computes_output) returns true
+/// has the same value that [`Fft::process`](crate::Fft::process) would have computed. The contents of every other output are unspecified.
+///
+/// ~~~
+/// // Compute bins 100..164 of a forward FFT of size 4096
+/// use rustfft::{FftDirection, FftPlanner};
+/// use rustfft::num_complex::Complex;
+///
+/// let mut buffer = vec![Complex{ re: 0.0f32, im: 0.0f32 }; 4096];
+///
+/// let mut planner = FftPlanner::new();
+/// let fft = planner.plan_fft_output_range(4096, FftDirection::Forward, 100..164);
+/// fft.process(&mut buffer);
+///
+/// let band = &buffer[100..164];
+/// ~~~
+pub trait OutputPrunedFft<T: FftNum>: Length + Direction + Sync + Send {
+    /// Returns true if `process` computes the output at `index`, and false if the contents of that output are unspecified after processing.
+    fn computes_output(&self, index: usize) -> bool;
+
+    /// Computes a FFT in-place, computing at least the outputs for which `computes_output` returns true.
+    ///
+    /// Convenience method that allocates a `Vec` with the required scratch space and calls `self.process_with_scratch`.
+    /// If you want to re-use that allocation across multiple FFT computations, consider calling `process_with_scratch` instead.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if:
+    /// - `buffer.len() % self.len() > 0`
+    /// - `buffer.len() < self.len()`
+    fn process(&self, buffer: &mut [Complex<T>]) {
+        let mut scratch = vec![Complex::zero(); self.get_inplace_scratch_len()];
+        self.process_with_scratch(buffer, &mut scratch);
+    }
+
+    /// Divides `buffer` into chunks of size `self.len()`, and computes a FFT on each chunk,
+    /// computing at least the outputs for which `computes_output` returns true.
+    ///
+    /// Uses the `scratch` buffer as scratch space, so the contents of `scratch` should be considered garbage
+    /// after calling.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if:
+    /// - `buffer.len() % self.len() > 0`
+    /// - `buffer.len() < self.len()`
+    /// - `scratch.len() < self.get_inplace_scratch_len()`
+    fn process_with_scratch(&self, buffer: &mut [Complex<T>], scratch: &mut [Complex<T>]);
+
+    /// Returns the size of the scratch buffer required by `process_with_scratch`
+    fn get_inplace_scratch_len(&self) -> usize;
 }
 
 // Algorithms implemented to use AVX instructions. Only compiled on x86_64, and only compiled if the "avx" feature flag is set.
@@ -224,7 +596,7 @@ mod avx;
 #[cfg(not(all(target_arch = "x86_64", feature = "avx")))]
 mod avx {
     pub mod avx_planner {
-        use crate::{Fft, FftDirection, FftNum};
+        use crate::{CachePolicy, Fft, FftDirection, FftEstimate, FftNum, PlannerOptions};
         use std::sync::Arc;
 
         /// The AVX FFT planner creates new FFT algorithm instances which take advantage of the AVX instruction set.
@@ -271,6 +643,19 @@ mod avx {
             pub fn new() -> Result<Self, ()> {
                 Err(())
             }
+            /// Constructs a new `FftPlannerAvx` instance, which chooses algorithms according to `options`.
+            ///
+            /// The AVX planner only takes the allow and deny lists for Rader's and Bluestein's algorithms into account. See [`PlannerOptions`](crate::PlannerOptions) for details.
+            ///
+            /// Returns `Ok(planner_instance)` if this machine has the required instruction sets and the `avx` feature flag is set.
+            /// Returns `Err(())` if some instruction sets are missing, or if the `avx` feature flag is not set.
+            ///
+            /// # Panics
+            /// Panics if the options set `min_radix4_bits` larger than `max_radix4_bits`.
+            pub fn with_options(options: PlannerOptions) -> Result<Self, ()> {
+                options.validate();
+                Err(())
+            }
             /// Returns a `Fft` instance which uses AVX instructions to compute FFTs of size `len`.
             ///
             /// If the provided `direction` is `FftDirection::Forward`, the returned instance will compute forward FFTs. If it's `FftDirection::Inverse`, it will compute inverse FFTs.
@@ -291,6 +676,74 @@ mod avx {
             pub fn plan_fft_inverse(&mut self, _len: usize) -> Arc<dyn Fft<T>> {
                 unreachable!()
             }
+
+            /// Sets the policy that limits which FFT instances this planner keeps in its cache. If the cache doesn't fit the new policy,
+            /// the least recently used instances are discarded immediately.
+            ///
+            /// FFT instances that have already been returned by the planner stay valid when they're discarded from the cache.
+            pub fn set_cache_policy(&mut self, _policy: CachePolicy) {
+                unreachable!()
+            }
+            /// Returns the policy that limits which FFT instances this planner keeps in its cache
+            pub fn cache_policy(&self) -> CachePolicy {
+                unreachable!()
+            }
+            /// Discards every FFT instance from this planner's cache.
+            ///
+            /// FFT instances that have already been returned by the planner stay valid.
+            pub fn clear(&mut self) {
+                unreachable!()
+            }
+            /// Discards the cached FFT instances of size `len`, in both directions, from this planner's cache.
+            ///
+            /// Instances of other sizes that use a FFT of size `len` internally keep their own reference to it, so they aren't affected.
+            pub fn evict(&mut self, _len: usize) {
+                unreachable!()
+            }
+            /// Returns the number of heap bytes owned by the FFT instances in this planner's cache.
+            ///
+            /// This is the quantity limited by [`CachePolicy::MaxBytes`](crate::CachePolicy::MaxBytes). Each instance counts only the data returned by [`Fft::own_heap_bytes`](crate::Fft::own_heap_bytes),
+            /// so an inner FFT shared by several cached instances is only counted once.
+            pub fn cache_memory_bytes(&self) -> usize {
+                unreachable!()
+            }
+            /// Registers a custom FFT instance, which the planner will use whenever it needs a FFT of the same size and direction.
+            ///
+            /// The planner returns `fft` directly when asked for its size, and uses it as the base of larger sizes whenever one of the
+            /// planned steps has the same size. Registered instances are never discarded from the cache.
+            ///
+            /// FFT instances that were planned before registering won't use the registered instance, so register custom FFTs before planning.
+            pub fn register(&mut self, _fft: Arc<dyn Fft<T>>) {
+                unreachable!()
+            }
+            /// Returns the smallest FFT size that's at least `min_len`, and that this planner computes especially fast: one of the form `2^n * 3^m`.
+            ///
+            /// # Panics
+            /// Panics if no such size fits in a `usize`.
+            pub fn next_fast_len(&self, _min_len: usize) -> usize {
+                unreachable!()
+            }
+            /// Returns the largest FFT size that's at most `max_len`, and that this planner computes especially fast: one of the form `2^n * 3^m`.
+            /// If `max_len` is zero, returns zero.
+            pub fn prev_fast_len(&self, _max_len: usize) -> usize {
+                unreachable!()
+            }
+            /// Returns the estimated cost of the FFT that this planner creates for `len`, according to the [`cost_model`](crate::PlannerOptions::cost_model)
+            /// in this planner's options, or the default [`CostModel`](crate::CostModel) if the options don't have one.
+            ///
+            /// See [`estimate`](crate::FftPlannerAvx::estimate) for how the AVX algorithms are estimated.
+            pub fn estimated_cost(&self, _len: usize) -> f64 {
+                unreachable!()
+            }
+            /// Returns an estimate of the operations, memory passes, and scratch of the FFT that this planner creates for `len`, without creating it.
+            ///
+            /// The estimate follows the AVX plan for `len`. The cost model measures scalar algorithms, so each AVX step is counted as the scalar algorithm
+            /// with the same structure: the operations and cost are comparable between sizes, but they overestimate the AVX instances. The scratch lengths are exact.
+            ///
+            /// The estimate describes the FFT that would be planned without any cached or registered instances, so it doesn't depend on what was planned before.
+            pub fn estimate(&self, _len: usize) -> FftEstimate {
+                unreachable!()
+            }
         }
     }
 }
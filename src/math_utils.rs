@@ -1,4 +1,6 @@
+use num_integer::div_ceil;
 use num_traits::{One, PrimInt, Zero};
+use std::cmp::{max, min};
 
 pub fn primitive_root(prime: u64) -> Option<u64> {
     let test_exponents: Vec<u64> = distinct_prime_factors(prime - 1)
@@ -7,8 +9,9 @@ pub fn primitive_root(prime: u64) -> Option<u64> {
         .collect();
     'next: for potential_root in 2..prime {
         // for each distinct factor, if potential_root^(p-1)/factor mod p is 1, reject it
+        // compute in u128, so that squaring can't overflow for primes above 2^32
         for exp in &test_exponents {
-            if modular_exponent(potential_root, *exp, prime) == 1 {
+            if modular_exponent(potential_root as u128, *exp as u128, prime as u128) == 1 {
                 continue 'next;
             }
         }
@@ -73,6 +76,79 @@ pub fn distinct_prime_factors(mut n: u64) -> Vec<u64> {
     result
 }
 
+// Returns the smallest number that's at least `min_len`, and whose only prime factors are 2 and the primes in `odd_primes`.
+// Returns None if that number doesn't fit in a usize. Zero and one are returned unchanged
+pub fn next_smooth_number(min_len: usize, odd_primes: &[usize]) -> Option<usize> {
+    if min_len <= 1 {
+        Some(min_len)
+    } else {
+        next_smooth_number_recursive(min_len, odd_primes)
+    }
+}
+
+fn next_smooth_number_recursive(min_len: usize, odd_primes: &[usize]) -> Option<usize> {
+    match odd_primes.split_first() {
+        None => min_len.checked_next_power_of_two(),
+        Some((&prime, smaller_primes)) => {
+            // Try each power of this prime, and fill in the rest with the remaining primes
+            let mut best = next_smooth_number_recursive(min_len, smaller_primes);
+            let mut power = prime;
+            loop {
+                let candidate =
+                    next_smooth_number_recursive(div_ceil(min_len, power), smaller_primes)
+                        .and_then(|remainder| remainder.checked_mul(power));
+                best = match (best, candidate) {
+                    (Some(best), Some(candidate)) => Some(min(best, candidate)),
+                    (best, candidate) => best.or(candidate),
+                };
+
+                // Once the power alone reaches min_len, higher powers can only be larger
+                if power >= min_len {
+                    break;
+                }
+                match power.checked_mul(prime) {
+                    Some(next_power) => power = next_power,
+                    None => break,
+                }
+            }
+            best
+        }
+    }
+}
+
+// Returns the largest number that's at most `max_len`, and whose only prime factors are 2 and the primes in `odd_primes`. Zero is returned unchanged
+pub fn prev_smooth_number(max_len: usize, odd_primes: &[usize]) -> usize {
+    if max_len == 0 {
+        0
+    } else {
+        prev_smooth_number_recursive(max_len, odd_primes)
+    }
+}
+
+fn prev_smooth_number_recursive(max_len: usize, odd_primes: &[usize]) -> usize {
+    match odd_primes.split_first() {
+        None => {
+            let usize_bits = std::mem::size_of::<usize>() as u32 * 8;
+            1 << (usize_bits - 1 - max_len.leading_zeros())
+        }
+        Some((&prime, smaller_primes)) => {
+            let mut best = prev_smooth_number_recursive(max_len, smaller_primes);
+            let mut power = prime;
+            while power <= max_len {
+                best = max(
+                    best,
+                    power * prev_smooth_number_recursive(max_len / power, smaller_primes),
+                );
+                match power.checked_mul(prime) {
+                    Some(next_power) => power = next_power,
+                    None => break,
+                }
+            }
+            best
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
 pub struct PrimeFactor {
     pub value: usize,
@@ -186,6 +262,32 @@ impl PrimeFactors {
         &self.other_factors
     }
 
+    // Returns every divisor of the number, including 1 and the number itself, in ascending order
+    pub fn divisors(&self) -> Vec<usize> {
+        let mut divisors = vec![1];
+        let two = PrimeFactor {
+            value: 2,
+            count: self.power_two,
+        };
+        let three = PrimeFactor {
+            value: 3,
+            count: self.power_three,
+        };
+        for factor in [two, three].iter().chain(self.other_factors.iter()) {
+            // multiply every divisor found so far by each power of this factor
+            let previous_count = divisors.len();
+            let mut power = 1;
+            for _ in 0..factor.count {
+                power *= factor.value;
+                for i in 0..previous_count {
+                    divisors.push(divisors[i] * power);
+                }
+            }
+        }
+        divisors.sort_unstable();
+        divisors
+    }
+
     // Divides the number by the given prime factor. Returns None if the resulting number is one.
     pub fn remove_factors(mut self, factor: PrimeFactor) -> Option<Self> {
         if factor.count == 0 {
@@ -665,6 +767,65 @@ mod unit_tests {
         }
     }
 
+    #[test]
+    fn test_divisors() {
+        assert_eq!(PrimeFactors::compute(1).divisors(), vec![1]);
+        assert_eq!(PrimeFactors::compute(7).divisors(), vec![1, 7]);
+        assert_eq!(
+            PrimeFactors::compute(360).divisors(),
+            vec![
+                1, 2, 3, 4, 5, 6, 8, 9, 10, 12, 15, 18, 20, 24, 30, 36, 40, 45, 60, 72, 90, 120,
+                180, 360
+            ]
+        );
+
+        for n in 1..200 {
+            let expected: Vec<usize> = (1..=n).filter(|d| n % d == 0).collect();
+            assert_eq!(PrimeFactors::compute(n).divisors(), expected);
+        }
+    }
+
+    #[test]
+    fn test_smooth_numbers() {
+        let is_3_smooth =
+            |n: usize| n > 0 && PartialFactors::compute(n).product_power2power3() == n;
+        let is_11_smooth = |n: usize| n > 0 && PartialFactors::compute(n).get_other_factors() == 1;
+
+        for n in 1..2000 {
+            let expected_next_3 = (n..).find(|&m| is_3_smooth(m)).unwrap();
+            let expected_next_11 = (n..).find(|&m| is_11_smooth(m)).unwrap();
+            let expected_prev_3 = (1..=n).rev().find(|&m| is_3_smooth(m)).unwrap();
+            let expected_prev_11 = (1..=n).rev().find(|&m| is_11_smooth(m)).unwrap();
+            assert_eq!(next_smooth_number(n, &[3]), Some(expected_next_3));
+            assert_eq!(
+                next_smooth_number(n, &[3, 5, 7, 11]),
+                Some(expected_next_11)
+            );
+            assert_eq!(prev_smooth_number(n, &[3]), expected_prev_3);
+            assert_eq!(prev_smooth_number(n, &[3, 5, 7, 11]), expected_prev_11);
+        }
+
+        // Large inputs shouldn't overflow
+        let largest_power_of_two = 1 << (std::mem::size_of::<usize>() * 8 - 1);
+        assert_eq!(next_smooth_number(usize::max_value(), &[3]), None);
+        assert_eq!(
+            next_smooth_number(largest_power_of_two, &[]),
+            Some(largest_power_of_two)
+        );
+        assert_eq!(
+            prev_smooth_number(usize::max_value(), &[]),
+            largest_power_of_two
+        );
+        let prev = prev_smooth_number(usize::max_value(), &[3, 5, 7, 11]);
+        assert!(PrimeFactors::compute(prev)
+            .get_other_factors()
+            .iter()
+            .all(|factor| factor.value <= 11));
+
+        assert_eq!(next_smooth_number(0, &[3]), Some(0));
+        assert_eq!(prev_smooth_number(0, &[3]), 0);
+    }
+
     #[test]
     fn test_remove_factors() {
         // For every possible factor of a bunch of factors, they removing each and making sure the result is internally consistent
@@ -0,0 +1,774 @@
+use num_integer::Integer;
+use primal_check::miller_rabin;
+use transpose;
+
+use crate::algorithm::radix4::prepare_radix4;
+use crate::array_utils;
+use crate::common::fft_error_inplace;
+use crate::math_utils::{distinct_prime_factors, modular_exponent, primitive_root};
+use crate::{Direction, FftDirection, Length};
+
+/// The prime 119 * 2^23 + 1. Supports power-of-two lengths up to 2^23, times any divisor of 7 * 17.
+///
+/// Its elements fit in 30 bits, so it's the fastest choice when the convolution's coefficients are known to be smaller than the prime.
+pub const PRIME_998244353: u64 = 998_244_353;
+
+/// The Solinas prime 2^64 - 2^32 + 1. Supports power-of-two lengths up to 2^32, times any divisor of 3 * 5 * 17 * 257 * 65537.
+pub const SOLINAS_PRIME_64_32: u64 = 0xFFFF_FFFF_0000_0001;
+
+/// The Solinas prime 2^64 - 2^34 + 1. Supports power-of-two lengths up to 2^34, times any divisor of 3^2 * 7 * 11 * 31 * 151 * 331.
+pub const SOLINAS_PRIME_64_34: u64 = 0xFFFF_FFFC_0000_0001;
+
+/// The Solinas prime 2^64 - 2^40 + 1. Supports power-of-two lengths up to 2^40, times any divisor of 3^2 * 5 * 7 * 13 * 17 * 241.
+pub const SOLINAS_PRIME_64_40: u64 = 0xFFFF_FF00_0000_0001;
+
+/// Computes number-theoretic transforms, IE DFTs over the integers modulo a prime, for exact integer convolution.
+///
+/// A NTT of size `len` modulo the prime `p` exists if `len` divides `p - 1`. The forward transform computes
+/// `output[k] = sum(input[j] * w^(j * k))`, where `w = g^((p - 1) / len)` for the smallest primitive root `g` of `p`,
+/// and the inverse transform uses `w^-1` instead. Like the FFTs in this crate, neither transform is normalized: multiply by
+/// the inverse of `len` modulo `p` to normalize.
+///
+/// Power-of-two sizes are computed with the same structure as [`Radix4`](crate::algorithm::Radix4). Other sizes are split into
+/// smaller NTTs with the mixed radix algorithm, down to DFTs of prime size.
+///
+/// There's no Rader's or Bluestein's algorithm for the prime-size DFTs: each DFT of prime size `p` takes `O(p^2)` modular multiplications,
+/// so a NTT of size `len` takes `O(len * p)` time, where `p` is the largest prime factor of `len`. Most supported prime factors are small,
+/// but [`SOLINAS_PRIME_64_32`](crate::ntt::SOLINAS_PRIME_64_32) supports a NTT of size 65537, which is computed as a single quadratic DFT.
+/// Prefer power-of-two sizes, like [`convolve`](crate::ntt::convolve) does.
+///
+/// Every element must be less than the modulus.
+///
+/// ~~~
+/// // Computes a forward NTT of size 4096, modulo 998244353
+/// use rustfft::ntt::{Ntt, PRIME_998244353};
+/// use rustfft::FftDirection;
+///
+/// let mut buffer = vec![1u64; 4096];
+///
+/// let ntt = Ntt::new(4096, PRIME_998244353, FftDirection::Forward);
+/// ntt.process(&mut buffer);
+/// ~~~
+pub struct Ntt {
+    algorithm: NttAlgorithm,
+    len: usize,
+    modulus: u64,
+    direction: FftDirection,
+}
+
+enum NttAlgorithm {
+    Dft {
+        // powers of the root of unity, w^0 through w^(len - 1)
+        twiddles: Box<[u64]>,
+    },
+    Radix4 {
+        // packed twiddle factors for each layer, starting with the bottom layer, exactly like Radix4
+        twiddles: Box<[u64]>,
+        base_len: usize,
+        // w^(len / 4), which takes the place of multiplying by i in the size-4 butterflies
+        fourth_root: u64,
+    },
+    MixedRadix {
+        twiddles: Box<[u64]>,
+        width_ntt: Box<Ntt>,
+        height_ntt: Box<Ntt>,
+        inplace_scratch_len: usize,
+    },
+}
+
+impl Ntt {
+    /// Preallocates necessary arrays and precomputes necessary data to efficiently compute NTTs of size `len` modulo `modulus`
+    ///
+    /// # Panics
+    /// Panics if `modulus` isn't a prime, or if `len` doesn't divide `modulus - 1`.
+    pub fn new(len: usize, modulus: u64, direction: FftDirection) -> Self {
+        assert!(
+            modulus >= 2 && miller_rabin(modulus),
+            "The NTT modulus must be a prime. Got {}",
+            modulus
+        );
+        assert!(len > 0, "NTT length must be at least 1");
+        assert_eq!(
+            (modulus - 1) % len as u64,
+            0,
+            "NTT length must divide modulus - 1. Got len = {}, modulus = {}",
+            len,
+            modulus
+        );
+        // 1 is the only nonzero residue modulo 2, so it's the primitive root of 2. Every other prime has a primitive root of at least 2
+        let generator = if modulus == 2 {
+            1
+        } else {
+            primitive_root(modulus).unwrap()
+        };
+        Self::with_generator(len, modulus, generator, direction)
+    }
+
+    // All sub-NTTs use the same generator, so that their roots of unity are powers of each other
+    fn with_generator(len: usize, modulus: u64, generator: u64, direction: FftDirection) -> Self {
+        let mut root = pow_mod(generator, (modulus - 1) / len as u64, modulus);
+        if direction == FftDirection::Inverse {
+            root = inverse_mod(root, modulus);
+        }
+
+        // precompute every power of the root, so that each twiddle factor is a table lookup
+        let mut powers = Vec::with_capacity(len);
+        let mut power = 1;
+        for _ in 0..len {
+            powers.push(power);
+            power = mul_mod(power, root, modulus);
+        }
+
+        let power_of_two = 1 << len.trailing_zeros();
+        let algorithm = if len == power_of_two {
+            Self::design_radix4(len, &powers)
+        } else if power_of_two > 1 {
+            Self::design_mixed_radix(
+                power_of_two,
+                len / power_of_two,
+                modulus,
+                generator,
+                direction,
+                &powers,
+            )
+        } else {
+            let smallest_factor = distinct_prime_factors(len as u64)[0] as usize;
+            if smallest_factor == len {
+                NttAlgorithm::Dft {
+                    twiddles: powers.into_boxed_slice(),
+                }
+            } else {
+                Self::design_mixed_radix(
+                    smallest_factor,
+                    len / smallest_factor,
+                    modulus,
+                    generator,
+                    direction,
+                    &powers,
+                )
+            }
+        };
+
+        Self {
+            algorithm,
+            len,
+            modulus,
+            direction,
+        }
+    }
+
+    fn design_radix4(len: usize, powers: &[u64]) -> NttAlgorithm {
+        // Without a hardcoded butterfly to bottom out on, the base layer is either single elements or size-2 butterflies
+        let base_len = if len.trailing_zeros() % 2 == 1 { 2 } else { 1 };
+
+        // same layout as Radix4: the twiddle factors of each layer, starting with the bottom layer and going up
+        let mut twiddle_stride = len / (base_len * 4);
+        let mut twiddle_factors = Vec::with_capacity(len * 2);
+        while twiddle_stride > 0 {
+            let num_rows = len / (twiddle_stride * 4);
+            for i in 0..num_rows {
+                for k in 1..4 {
+                    twiddle_factors.push(powers[i * k * twiddle_stride]);
+                }
+            }
+            twiddle_stride >>= 2;
+        }
+
+        NttAlgorithm::Radix4 {
+            twiddles: twiddle_factors.into_boxed_slice(),
+            base_len,
+            fourth_root: powers[len / 4],
+        }
+    }
+
+    fn design_mixed_radix(
+        width: usize,
+        height: usize,
+        modulus: u64,
+        generator: u64,
+        direction: FftDirection,
+        powers: &[u64],
+    ) -> NttAlgorithm {
+        let width_ntt = Self::with_generator(width, modulus, generator, direction);
+        let height_ntt = Self::with_generator(height, modulus, generator, direction);
+
+        let mut twiddles = Vec::with_capacity(width * height);
+        for x in 0..width {
+            for y in 0..height {
+                twiddles.push(powers[x * y]);
+            }
+        }
+
+        let inplace_scratch_len = width * height
+            + std::cmp::max(
+                width_ntt.get_inplace_scratch_len(),
+                height_ntt.get_inplace_scratch_len(),
+            );
+
+        NttAlgorithm::MixedRadix {
+            twiddles: twiddles.into_boxed_slice(),
+            width_ntt: Box::new(width_ntt),
+            height_ntt: Box::new(height_ntt),
+            inplace_scratch_len,
+        }
+    }
+
+    /// Returns the modulus of this NTT's arithmetic
+    pub fn modulus(&self) -> u64 {
+        self.modulus
+    }
+
+    /// Returns the size of the scratch buffer required by `process_with_scratch`
+    pub fn get_inplace_scratch_len(&self) -> usize {
+        match &self.algorithm {
+            NttAlgorithm::Dft { .. } | NttAlgorithm::Radix4 { .. } => self.len,
+            NttAlgorithm::MixedRadix {
+                inplace_scratch_len,
+                ..
+            } => *inplace_scratch_len,
+        }
+    }
+
+    /// Computes a NTT in-place.
+    ///
+    /// Convenience method that allocates a scratch buffer of the size returned by `get_inplace_scratch_len()`.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `buffer.len() % self.len() > 0`, or if `buffer.len() < self.len()`
+    pub fn process(&self, buffer: &mut [u64]) {
+        let mut scratch = vec![0; self.get_inplace_scratch_len()];
+        self.process_with_scratch(buffer, &mut scratch);
+    }
+
+    /// Divides `buffer` into chunks of size `self.len()`, and computes a NTT on each chunk.
+    ///
+    /// Uses the `scratch` buffer as scratch space, so the contents of `scratch` should be considered garbage
+    /// after calling.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if:
+    /// - `buffer.len() % self.len() > 0`
+    /// - `buffer.len() < self.len()`
+    /// - `scratch.len() < self.get_inplace_scratch_len()`
+    pub fn process_with_scratch(&self, buffer: &mut [u64], scratch: &mut [u64]) {
+        let required_scratch = self.get_inplace_scratch_len();
+        if scratch.len() < required_scratch || buffer.len() < self.len {
+            fft_error_inplace(self.len, buffer.len(), required_scratch, scratch.len());
+            return; // Unreachable, because fft_error_inplace asserts, but it helps codegen to put it here
+        }
+
+        let scratch = &mut scratch[..required_scratch];
+        let result = array_utils::iter_chunks(buffer, self.len, |chunk| {
+            self.perform_ntt_inplace(chunk, scratch)
+        });
+
+        if result.is_err() {
+            // We want to trigger a panic, but we want to avoid doing it in this function to reduce code size, so call a function marked cold and inline(never) that will do it for us
+            fft_error_inplace(self.len, buffer.len(), required_scratch, scratch.len());
+        }
+    }
+
+    fn perform_ntt_inplace(&self, buffer: &mut [u64], scratch: &mut [u64]) {
+        let modulus = self.modulus;
+        match &self.algorithm {
+            NttAlgorithm::Dft { twiddles } => {
+                for (k, output) in scratch.iter_mut().enumerate() {
+                    let mut sum = 0;
+                    let mut twiddle_index = 0;
+                    for &input in buffer.iter() {
+                        sum = add_mod(
+                            sum,
+                            mul_mod(input, twiddles[twiddle_index], modulus),
+                            modulus,
+                        );
+                        twiddle_index += k;
+                        if twiddle_index >= self.len {
+                            twiddle_index -= self.len;
+                        }
+                    }
+                    *output = sum;
+                }
+                buffer.copy_from_slice(scratch);
+            }
+            NttAlgorithm::Radix4 {
+                twiddles,
+                base_len,
+                fourth_root,
+            } => {
+                // copy the data into the scratch vector, in the order the base-level butterflies want it
+                prepare_radix4(self.len, *base_len, buffer, scratch, 1);
+
+                // Base-level butterflies
+                if *base_len == 2 {
+                    for pair in scratch.chunks_exact_mut(2) {
+                        let (a, b) = (pair[0], pair[1]);
+                        pair[0] = add_mod(a, b, modulus);
+                        pair[1] = sub_mod(a, b, modulus);
+                    }
+                }
+
+                // cross-NTTs
+                let mut current_size = base_len * 4;
+                let mut layer_twiddles: &[u64] = twiddles;
+
+                while current_size <= self.len {
+                    for row in scratch.chunks_exact_mut(current_size) {
+                        butterfly_4(row, layer_twiddles, current_size / 4, *fourth_root, modulus);
+                    }
+
+                    //skip past all the twiddle factors used in this layer
+                    let twiddle_offset = (current_size * 3) / 4;
+                    layer_twiddles = &layer_twiddles[twiddle_offset..];
+
+                    current_size *= 4;
+                }
+
+                buffer.copy_from_slice(scratch);
+            }
+            NttAlgorithm::MixedRadix {
+                twiddles,
+                width_ntt,
+                height_ntt,
+                ..
+            } => {
+                // SIX STEP NTT, exactly like MixedRadix:
+                let (scratch, inner_scratch) = scratch.split_at_mut(self.len);
+                let width = width_ntt.len();
+                let height = height_ntt.len();
+
+                // STEP 1: transpose
+                transpose::transpose(buffer, scratch, width, height);
+
+                // STEP 2: perform NTTs of size `height`
+                height_ntt.process_with_scratch(scratch, inner_scratch);
+
+                // STEP 3: Apply twiddle factors
+                for (element, &twiddle) in scratch.iter_mut().zip(twiddles.iter()) {
+                    *element = mul_mod(*element, twiddle, modulus);
+                }
+
+                // STEP 4: transpose again
+                transpose::transpose(scratch, buffer, height, width);
+
+                // STEP 5: perform NTTs of size `width`
+                width_ntt.process_with_scratch(buffer, inner_scratch);
+
+                // STEP 6: transpose again
+                transpose::transpose(buffer, scratch, width, height);
+                buffer.copy_from_slice(scratch);
+            }
+        }
+    }
+}
+impl Length for Ntt {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+impl Direction for Ntt {
+    fn fft_direction(&self) -> FftDirection {
+        self.direction
+    }
+}
+
+fn butterfly_4(
+    data: &mut [u64],
+    twiddles: &[u64],
+    num_ntts: usize,
+    fourth_root: u64,
+    modulus: u64,
+) {
+    for i in 0..num_ntts {
+        let a = data[i];
+        let b = mul_mod(data[i + num_ntts], twiddles[3 * i], modulus);
+        let c = mul_mod(data[i + 2 * num_ntts], twiddles[3 * i + 1], modulus);
+        let d = mul_mod(data[i + 3 * num_ntts], twiddles[3 * i + 2], modulus);
+
+        let a_plus_c = add_mod(a, c, modulus);
+        let a_minus_c = sub_mod(a, c, modulus);
+        let b_plus_d = add_mod(b, d, modulus);
+        let b_minus_d_rotated = mul_mod(sub_mod(b, d, modulus), fourth_root, modulus);
+
+        data[i] = add_mod(a_plus_c, b_plus_d, modulus);
+        data[i + num_ntts] = add_mod(a_minus_c, b_minus_d_rotated, modulus);
+        data[i + 2 * num_ntts] = sub_mod(a_plus_c, b_plus_d, modulus);
+        data[i + 3 * num_ntts] = sub_mod(a_minus_c, b_minus_d_rotated, modulus);
+    }
+}
+
+/// Computes the linear convolution of `a` and `b` modulo the prime `modulus`, IE the coefficients of the product of the polynomials `a` and `b`.
+///
+/// Inputs are reduced modulo `modulus` first. The output has `a.len() + b.len() - 1` elements, or none if either input is empty.
+/// If the true coefficients may exceed the modulus, convolve modulo several primes and combine the results with a [`CrtBasis`](crate::ntt::CrtBasis).
+///
+/// ~~~
+/// // (1 + 2x) * (3 + 4x) = 3 + 10x + 8x^2
+/// use rustfft::ntt::{convolve, PRIME_998244353};
+///
+/// assert_eq!(convolve(&[1, 2], &[3, 4], PRIME_998244353), vec![3, 10, 8]);
+/// ~~~
+///
+/// # Panics
+/// Panics if `modulus` isn't a prime, or if it doesn't support NTTs of the next power of two at least `a.len() + b.len() - 1`.
+pub fn convolve(a: &[u64], b: &[u64], modulus: u64) -> Vec<u64> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let output_len = a.len() + b.len() - 1;
+    let len = output_len.next_power_of_two();
+
+    let forward = Ntt::new(len, modulus, FftDirection::Forward);
+    let inverse = Ntt::new(len, modulus, FftDirection::Inverse);
+    let mut scratch = vec![0; forward.get_inplace_scratch_len()];
+
+    let mut a_spectrum = vec![0; len];
+    for (element, &value) in a_spectrum.iter_mut().zip(a.iter()) {
+        *element = value % modulus;
+    }
+    let mut b_spectrum = vec![0; len];
+    for (element, &value) in b_spectrum.iter_mut().zip(b.iter()) {
+        *element = value % modulus;
+    }
+    forward.process_with_scratch(&mut a_spectrum, &mut scratch);
+    forward.process_with_scratch(&mut b_spectrum, &mut scratch);
+
+    // multiply pointwise, folding in the normalization of the inverse NTT
+    let scale = inverse_mod(len as u64 % modulus, modulus);
+    for (a, &b) in a_spectrum.iter_mut().zip(b_spectrum.iter()) {
+        *a = mul_mod(mul_mod(*a, b, modulus), scale, modulus);
+    }
+    inverse.process_with_scratch(&mut a_spectrum, &mut scratch);
+
+    a_spectrum.truncate(output_len);
+    a_spectrum
+}
+
+/// Reconstructs integers from their residues modulo several pairwise coprime moduli, using the Chinese remainder theorem.
+///
+/// Convolving modulo several primes and combining each coefficient's residues gives the exact coefficients,
+/// as long as they're less than the product of the primes.
+///
+/// ~~~
+/// // Multiply polynomials whose coefficients are too large for any single 64-bit prime
+/// use rustfft::ntt::{convolve, CrtBasis, SOLINAS_PRIME_64_32, SOLINAS_PRIME_64_34, SOLINAS_PRIME_64_40};
+///
+/// let a = [u64::max_value(), 3];
+/// let b = [u64::max_value(), 5];
+///
+/// // The coefficients can approach 2 * 2^128, so use three 64-bit primes
+/// let moduli = [SOLINAS_PRIME_64_32, SOLINAS_PRIME_64_34, SOLINAS_PRIME_64_40];
+/// let residues: Vec<Vec<u64>> = moduli.iter().map(|&modulus| convolve(&a, &b, modulus)).collect();
+///
+/// let basis = CrtBasis::new(&moduli);
+/// let first_coefficient = basis.combine(&[residues[0][0], residues[1][0], residues[2][0]]);
+/// assert_eq!(first_coefficient, Some(u64::max_value() as u128 * u64::max_value() as u128));
+/// ~~~
+pub struct CrtBasis {
+    moduli: Box<[u64]>,
+
+    // For each modulus m_i, the inverse of m_0 * m_1 * ... * m_(i-1) modulo m_i
+    inverses: Box<[u64]>,
+}
+
+impl CrtBasis {
+    /// Precomputes the data needed to combine residues modulo each of `moduli`
+    ///
+    /// # Panics
+    /// Panics if `moduli` is empty, if any modulus is less than 2, or if the moduli aren't pairwise coprime.
+    pub fn new(moduli: &[u64]) -> Self {
+        assert!(!moduli.is_empty(), "CrtBasis requires at least one modulus");
+        for (i, &modulus) in moduli.iter().enumerate() {
+            assert!(
+                modulus >= 2,
+                "CRT moduli must be at least 2. Got {}",
+                modulus
+            );
+            for &previous in &moduli[..i] {
+                assert_eq!(
+                    previous.gcd(&modulus),
+                    1,
+                    "CRT moduli must be pairwise coprime. Got {} and {}",
+                    previous,
+                    modulus
+                );
+            }
+        }
+
+        let inverses = moduli
+            .iter()
+            .enumerate()
+            .map(|(i, &modulus)| {
+                let prefix_product = moduli[..i]
+                    .iter()
+                    .fold(1, |product, &previous| mul_mod(product, previous, modulus));
+                inverse_mod(prefix_product, modulus)
+            })
+            .collect();
+
+        Self {
+            moduli: moduli.to_vec().into_boxed_slice(),
+            inverses,
+        }
+    }
+
+    /// Returns the moduli of this basis
+    pub fn moduli(&self) -> &[u64] {
+        &self.moduli
+    }
+
+    /// Computes the mixed-radix digits of the smallest non-negative integer `x` whose residue modulo `moduli[i]` is `residues[i]`.
+    ///
+    /// The digits satisfy `x = digits[0] + digits[1] * moduli[0] + digits[2] * moduli[0] * moduli[1] + ...`, with `digits[i] < moduli[i]`,
+    /// so they're exact no matter how large `x` is, and they can be accumulated into any big integer representation.
+    ///
+    /// # Panics
+    /// Panics if `residues.len()` isn't the number of moduli.
+    pub fn mixed_radix_digits(&self, residues: &[u64]) -> Vec<u64> {
+        assert_eq!(
+            residues.len(),
+            self.moduli.len(),
+            "Expected one residue per modulus. Expected {}, got {}",
+            self.moduli.len(),
+            residues.len()
+        );
+
+        // Garner's algorithm: each digit cancels out the previous digits' contribution modulo the next modulus
+        let mut digits: Vec<u64> = Vec::with_capacity(residues.len());
+        for (i, (&residue, &modulus)) in residues.iter().zip(self.moduli.iter()).enumerate() {
+            let mut partial = 0;
+            for (&digit, &previous) in digits.iter().zip(self.moduli[..i].iter()).rev() {
+                partial = add_mod(
+                    mul_mod(partial, previous, modulus),
+                    digit % modulus,
+                    modulus,
+                );
+            }
+            let difference = sub_mod(residue % modulus, partial, modulus);
+            digits.push(mul_mod(difference, self.inverses[i], modulus));
+        }
+        digits
+    }
+
+    /// Computes the smallest non-negative integer whose residue modulo `moduli[i]` is `residues[i]`, or `None` if it doesn't fit in a `u128`.
+    ///
+    /// # Panics
+    /// Panics if `residues.len()` isn't the number of moduli.
+    pub fn combine(&self, residues: &[u64]) -> Option<u128> {
+        let digits = self.mixed_radix_digits(residues);
+
+        // Evaluate x = digits[0] + moduli[0] * (digits[1] + moduli[1] * (digits[2] + ...)) from the inside out
+        let mut result = *digits.last().unwrap() as u128;
+        for (&digit, &modulus) in digits.iter().zip(self.moduli.iter()).rev().skip(1) {
+            result = result
+                .checked_mul(modulus as u128)?
+                .checked_add(digit as u128)?;
+        }
+        Some(result)
+    }
+}
+
+fn add_mod(a: u64, b: u64, modulus: u64) -> u64 {
+    // the sum can overflow when the modulus is close to 2^64, so subtract the modulus with wrapping arithmetic
+    let (sum, overflowed) = a.overflowing_add(b);
+    if overflowed || sum >= modulus {
+        sum.wrapping_sub(modulus)
+    } else {
+        sum
+    }
+}
+
+fn sub_mod(a: u64, b: u64, modulus: u64) -> u64 {
+    if a >= b {
+        a - b
+    } else {
+        modulus - (b - a)
+    }
+}
+
+fn mul_mod(a: u64, b: u64, modulus: u64) -> u64 {
+    ((a as u128 * b as u128) % modulus as u128) as u64
+}
+
+fn pow_mod(base: u64, exponent: u64, modulus: u64) -> u64 {
+    modular_exponent(base as u128, exponent as u128, modulus as u128) as u64
+}
+
+fn inverse_mod(value: u64, modulus: u64) -> u64 {
+    let gcd_data = i128::extended_gcd(&(value as i128), &(modulus as i128));
+    assert_eq!(
+        gcd_data.gcd, 1,
+        "{} has no inverse modulo {}",
+        value, modulus
+    );
+    if gcd_data.x >= 0 {
+        gcd_data.x as u64
+    } else {
+        (gcd_data.x + modulus as i128) as u64
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn random_residues(len: usize, modulus: u64, rng: &mut StdRng) -> Vec<u64> {
+        (0..len).map(|_| rng.gen_range(0, modulus)).collect()
+    }
+
+    fn naive_ntt(input: &[u64], modulus: u64, direction: FftDirection) -> Vec<u64> {
+        let len = input.len();
+        let generator = primitive_root(modulus).unwrap();
+        let mut root = pow_mod(generator, (modulus - 1) / len as u64, modulus);
+        if direction == FftDirection::Inverse {
+            root = inverse_mod(root, modulus);
+        }
+        let powers: Vec<u64> = (0..len as u64)
+            .map(|exponent| pow_mod(root, exponent, modulus))
+            .collect();
+        (0..len)
+            .map(|k| {
+                input.iter().enumerate().fold(0, |sum, (j, &x)| {
+                    add_mod(sum, mul_mod(x, powers[j * k % len], modulus), modulus)
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_ntt_matches_naive() {
+        let mut rng = StdRng::seed_from_u64(1234);
+        let test_cases: &[(u64, &[usize])] = &[
+            (
+                PRIME_998244353,
+                &[1, 2, 4, 8, 16, 32, 64, 256, 7, 17, 14, 28, 119, 7 * 17 * 16],
+            ),
+            (
+                SOLINAS_PRIME_64_32,
+                &[1, 2, 64, 512, 3, 15, 5 * 17, 3 * 128, 257],
+            ),
+            (SOLINAS_PRIME_64_34, &[128, 9 * 7, 11 * 32]),
+            (SOLINAS_PRIME_64_40, &[1024, 13 * 4, 3 * 5 * 7]),
+        ];
+        for &(modulus, lengths) in test_cases {
+            for &len in lengths {
+                for &direction in &[FftDirection::Forward, FftDirection::Inverse] {
+                    let ntt = Ntt::new(len, modulus, direction);
+                    assert_eq!(ntt.len(), len);
+                    assert_eq!(ntt.fft_direction(), direction);
+                    assert_eq!(ntt.modulus(), modulus);
+
+                    // process a couple chunks at once, with dirty scratch
+                    let input = random_residues(len * 2, modulus, &mut rng);
+                    let mut buffer = input.clone();
+                    let mut scratch = vec![modulus - 1; ntt.get_inplace_scratch_len()];
+                    ntt.process_with_scratch(&mut buffer, &mut scratch);
+
+                    for (input_chunk, output_chunk) in input.chunks(len).zip(buffer.chunks(len)) {
+                        assert_eq!(
+                            naive_ntt(input_chunk, modulus, direction),
+                            output_chunk,
+                            "len = {}, modulus = {}, direction = {}",
+                            len,
+                            modulus,
+                            direction
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_ntt_roundtrip() {
+        let mut rng = StdRng::seed_from_u64(5678);
+        for &(modulus, len) in &[(PRIME_998244353, 1 << 12), (SOLINAS_PRIME_64_32, 3 << 10)] {
+            let forward = Ntt::new(len, modulus, FftDirection::Forward);
+            let inverse = Ntt::new(len, modulus, FftDirection::Inverse);
+
+            let input = random_residues(len, modulus, &mut rng);
+            let mut buffer = input.clone();
+            forward.process(&mut buffer);
+            inverse.process(&mut buffer);
+
+            let scale = inverse_mod(len as u64, modulus);
+            let normalized: Vec<u64> = buffer.iter().map(|&x| mul_mod(x, scale, modulus)).collect();
+            assert_eq!(input, normalized, "len = {}, modulus = {}", len, modulus);
+        }
+    }
+
+    #[test]
+    fn test_ntt_modulus_two() {
+        // Modulo 2, the only NTT size is 1, which leaves its input unchanged
+        let mut buffer = vec![1, 0, 1];
+        Ntt::new(1, 2, FftDirection::Forward).process(&mut buffer);
+        assert_eq!(buffer, vec![1, 0, 1]);
+        assert_eq!(convolve(&[1], &[3], 2), vec![1]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_ntt_composite_modulus() {
+        // 9 - 1 is divisible by 2, and 2 passes the primitive root search modulo 9, but 9 isn't a prime
+        Ntt::new(2, 9, FftDirection::Forward);
+    }
+
+    #[test]
+    fn test_convolve() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for &(a_len, b_len) in &[(0, 5), (1, 1), (3, 7), (100, 37), (64, 65)] {
+            for &modulus in &[PRIME_998244353, SOLINAS_PRIME_64_40] {
+                let a = random_residues(a_len, modulus, &mut rng);
+                let b = random_residues(b_len, modulus, &mut rng);
+
+                let mut expected = vec![0; (a_len + b_len).saturating_sub(1)];
+                if a_len == 0 || b_len == 0 {
+                    expected.clear();
+                }
+                for (i, &x) in a.iter().enumerate() {
+                    for (j, &y) in b.iter().enumerate() {
+                        expected[i + j] = add_mod(expected[i + j], mul_mod(x, y, modulus), modulus);
+                    }
+                }
+                assert_eq!(convolve(&a, &b, modulus), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_crt_convolution() {
+        // coefficients up to 2^60 with 8 terms, so the products need up to 123 bits, more than any one prime can hold
+        let mut rng = StdRng::seed_from_u64(99);
+        let a: Vec<u64> = (0..8).map(|_| rng.gen_range(0, 1 << 60)).collect();
+        let b: Vec<u64> = (0..8).map(|_| rng.gen_range(0, 1 << 60)).collect();
+
+        let moduli = [
+            SOLINAS_PRIME_64_32,
+            SOLINAS_PRIME_64_34,
+            SOLINAS_PRIME_64_40,
+        ];
+        let basis = CrtBasis::new(&moduli);
+        assert_eq!(basis.moduli(), &moduli);
+        let residues: Vec<Vec<u64>> = moduli
+            .iter()
+            .map(|&modulus| convolve(&a, &b, modulus))
+            .collect();
+
+        for k in 0..15 {
+            let expected: u128 = (0..8)
+                .filter(|&i| k >= i && k - i < 8)
+                .map(|i| a[i] as u128 * b[k - i] as u128)
+                .sum();
+            let coefficient_residues: Vec<u64> = residues.iter().map(|r| r[k]).collect();
+            assert_eq!(basis.combine(&coefficient_residues), Some(expected));
+        }
+
+        // The combined value can need more than 128 bits, in which case only the digits are exact
+        let residues = [moduli[0] - 1, moduli[1] - 1, moduli[2] - 1];
+        assert_eq!(basis.combine(&residues), None);
+        let digits = basis.mixed_radix_digits(&residues);
+        assert_eq!(digits, vec![moduli[0] - 1, moduli[1] - 1, moduli[2] - 1]);
+    }
+}
@@ -0,0 +1,676 @@
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+use num_complex::Complex;
+use num_traits::Zero;
+
+use crate::{common::FftNum, Direction, Fft, FftDirection, FftPlanner, Length};
+
+// Spreading onto a grid twice the size of the output keeps the kernel narrow. The kernel width and shape parameter for a
+// given tolerance come from Barnett et al., "A parallel non-uniform fast Fourier transform library based on an 'exponential of semicircle' kernel"
+const OVERSAMPLING: usize = 2;
+const MAX_KERNEL_WIDTH: usize = 16;
+const BETA_PER_WIDTH: f64 = 2.30;
+
+// Computes the nodes and weights of `count`-point Gauss-Legendre quadrature on [-1, 1]
+fn gauss_legendre(count: usize) -> Vec<(f64, f64)> {
+    (0..count)
+        .map(|i| {
+            // Newton's method on the Legendre polynomial of degree `count`, starting from an asymptotic estimate of the root
+            let mut x = (PI * (i as f64 + 0.75) / (count as f64 + 0.5)).cos();
+            let mut derivative = 0f64;
+            for _ in 0..100 {
+                let mut previous = 1f64;
+                let mut current = x;
+                for degree in 2..=count {
+                    let degree = degree as f64;
+                    let next = ((2f64 * degree - 1f64) * x * current - (degree - 1f64) * previous)
+                        / degree;
+                    previous = current;
+                    current = next;
+                }
+                derivative = count as f64 * (x * current - previous) / (x * x - 1f64);
+                let step = current / derivative;
+                x -= step;
+                if step.abs() < 1e-15 {
+                    break;
+                }
+            }
+            (x, 2f64 / ((1f64 - x * x) * derivative * derivative))
+        })
+        .collect()
+}
+
+// Everything the type 1 and type 2 NUFFTs share: The oversampled grid, the FFT over it,
+// the spreading kernel evaluated at each non-uniform point, and the deconvolution factor for each uniform frequency
+struct Spreader<T> {
+    fft: Arc<dyn Fft<T>>,
+
+    // For each point, the first grid index that the point spreads onto, and the kernel's value at each of the next `kernel_width` grid indexes
+    grid_starts: Box<[usize]>,
+    kernel_values: Box<[T]>,
+    kernel_width: usize,
+
+    // For each uniform frequency, its grid index and the factor that undoes the spreading kernel's effect on that frequency
+    frequency_indexes: Box<[usize]>,
+    corrections: Box<[T]>,
+}
+
+impl<T: FftNum> Spreader<T> {
+    fn new(
+        points: &[f64],
+        frequency_count: usize,
+        tolerance: f64,
+        direction: FftDirection,
+        planner: &mut FftPlanner<T>,
+    ) -> Self {
+        assert!(
+            frequency_count > 0,
+            "The NUFFT requires at least one uniform frequency"
+        );
+        assert!(
+            tolerance > 0f64 && tolerance < 1f64,
+            "The NUFFT tolerance must be between 0 and 1. Got {}",
+            tolerance
+        );
+        for &point in points {
+            assert!(
+                point.is_finite(),
+                "NUFFT points must be finite. Got {}",
+                point
+            );
+        }
+
+        // Each digit of accuracy needs roughly one more grid point of kernel width. The paper's estimate of the width can overshoot
+        // the tolerance by a small factor, so add one more point to make the tolerance a bound
+        let kernel_width = ((1f64 / tolerance).log10().ceil() as usize + 2).min(MAX_KERNEL_WIDTH);
+        let beta = BETA_PER_WIDTH * kernel_width as f64;
+        let half_width = kernel_width as f64 / 2f64;
+        let kernel = |z: f64| {
+            if z.abs() < 1f64 {
+                (beta * ((1f64 - z * z).sqrt() - 1f64)).exp()
+            } else {
+                0f64
+            }
+        };
+
+        let grid_len = std::cmp::max(OVERSAMPLING * frequency_count, 2 * kernel_width);
+        let grid_len_float = grid_len as f64;
+        let grid_spacing = 2f64 * PI / grid_len_float;
+
+        // Find the first grid index within the kernel's reach of each point, and evaluate the kernel at that index and the ones after it
+        let mut grid_starts = Vec::with_capacity(points.len());
+        let mut kernel_values = Vec::with_capacity(points.len() * kernel_width);
+        for &point in points {
+            let mut position = point / grid_spacing;
+            position -= (position / grid_len_float).floor() * grid_len_float;
+
+            let start = (position - half_width).ceil();
+            for i in 0..kernel_width {
+                let z = (position - (start + i as f64)) / half_width;
+                kernel_values.push(T::from_f64(kernel(z)).unwrap());
+            }
+
+            let start = start as isize % grid_len as isize;
+            grid_starts.push(if start < 0 {
+                (start + grid_len as isize) as usize
+            } else {
+                start as usize
+            });
+        }
+
+        // Spreading convolves with the kernel, which multiplies each frequency by the kernel's continuous Fourier transform.
+        // Compute that transform by quadrature, and divide it back out. The kernel is even, so its transform is a cosine transform
+        let kernel_scale = half_width * grid_spacing;
+        let quadrature = gauss_legendre(4 + 3 * kernel_width);
+        let frequency_of = |i: usize| {
+            if i < frequency_count - frequency_count / 2 {
+                i as isize
+            } else {
+                i as isize - frequency_count as isize
+            }
+        };
+        let frequency_indexes = (0..frequency_count)
+            .map(|i| {
+                let frequency = frequency_of(i);
+                if frequency < 0 {
+                    (frequency + grid_len as isize) as usize
+                } else {
+                    frequency as usize
+                }
+            })
+            .collect();
+        let corrections = (0..frequency_count)
+            .map(|i| {
+                let frequency = frequency_of(i) as f64;
+                let transform: f64 = quadrature
+                    .iter()
+                    .map(|&(node, weight)| {
+                        weight * kernel(node) * (frequency * kernel_scale * node).cos()
+                    })
+                    .sum::<f64>()
+                    * kernel_scale;
+                T::from_f64(grid_spacing / transform).unwrap()
+            })
+            .collect();
+
+        Self {
+            fft: planner.plan_fft(grid_len, direction),
+
+            grid_starts: grid_starts.into_boxed_slice(),
+            kernel_values: kernel_values.into_boxed_slice(),
+            kernel_width,
+
+            frequency_indexes,
+            corrections,
+        }
+    }
+
+    fn point_count(&self) -> usize {
+        self.grid_starts.len()
+    }
+
+    fn frequency_count(&self) -> usize {
+        self.frequency_indexes.len()
+    }
+
+    fn get_scratch_len(&self) -> usize {
+        self.fft.len() + self.fft.get_inplace_scratch_len()
+    }
+
+    // Adds each point's value onto the grid, weighted by the kernel
+    fn spread(&self, values: &[Complex<T>], grid: &mut [Complex<T>]) {
+        for element in grid.iter_mut() {
+            *element = Complex::zero();
+        }
+        for ((&value, &start), weights) in values
+            .iter()
+            .zip(self.grid_starts.iter())
+            .zip(self.kernel_values.chunks_exact(self.kernel_width))
+        {
+            let mut index = start;
+            for &weight in weights {
+                grid[index] = grid[index] + value * weight;
+                index += 1;
+                if index == grid.len() {
+                    index = 0;
+                }
+            }
+        }
+    }
+
+    // Computes each point's value as the kernel-weighted sum of the grid around it
+    fn interpolate(&self, grid: &[Complex<T>], values: &mut [Complex<T>]) {
+        for ((value, &start), weights) in values
+            .iter_mut()
+            .zip(self.grid_starts.iter())
+            .zip(self.kernel_values.chunks_exact(self.kernel_width))
+        {
+            let mut sum = Complex::zero();
+            let mut index = start;
+            for &weight in weights {
+                sum = sum + grid[index] * weight;
+                index += 1;
+                if index == grid.len() {
+                    index = 0;
+                }
+            }
+            *value = sum;
+        }
+    }
+}
+
+fn assert_chunks(
+    name: &str,
+    input: &[Complex<impl Copy>],
+    output: &[Complex<impl Copy>],
+    len: usize,
+    output_len: usize,
+) {
+    let chunk_count = input.len().checked_div(len).unwrap_or(0);
+    assert!(
+        chunk_count * len == input.len() && chunk_count * output_len == output.len(),
+        "{} buffers must contain the same number of chunks. Expected input len multiple of {} and output len = input len / {} * {}, got input len = {}, output len = {}",
+        name,
+        len,
+        len,
+        output_len,
+        input.len(),
+        output.len()
+    );
+}
+
+/// Type 1 non-uniform FFT: Computes uniformly spaced frequencies of a signal sampled at non-uniformly spaced points
+///
+/// Given M values `input[j]` at points `x[j]`, computes N frequencies:
+///
+/// `output[i] = sum(input[j] * exp(-i * k * x[j]))`, where `k` is the frequency of output `i`
+///
+/// for a forward transform, or with `+i` in the exponent for an inverse transform. Points are in radians, so the input is
+/// periodic with period `2 * pi`. Frequencies are in the same order as a FFT's output: `k = i` for `i < N - N / 2`, and `k = i - N` after that.
+/// So if the points are `x[j] = 2 * pi * j / N`, the type 1 NUFFT computes a FFT of size N.
+///
+/// Each point is spread onto an oversampled uniform grid using an "exponential of semicircle" kernel, the grid is transformed with a planned FFT,
+/// and then the kernel's effect is divided out of each frequency. The kernel's width is chosen to meet the requested tolerance, which
+/// bounds the error relative to the size of the output. The achievable tolerance is limited by the precision of `T`:
+/// About `1e-6` for `f32`, and about `1e-14` for `f64`.
+///
+/// ~~~
+/// // Compute 64 frequencies from 1000 randomly spaced samples
+/// use rustfft::nufft::NufftType1;
+/// use rustfft::{FftDirection, FftPlanner};
+/// use rustfft::num_complex::Complex;
+///
+/// let points: Vec<f64> = (0..1000).map(|i| (i as f64 * 0.618).sin() * 3.0).collect();
+/// let input = vec![Complex{ re: 0.0f32, im: 0.0f32 }; 1000];
+/// let mut output = vec![Complex{ re: 0.0f32, im: 0.0f32 }; 64];
+///
+/// let mut planner = FftPlanner::new();
+/// let nufft = NufftType1::new(&points, 64, 1e-5, FftDirection::Forward, &mut planner);
+/// nufft.process(&input, &mut output);
+/// ~~~
+pub struct NufftType1<T> {
+    spreader: Spreader<T>,
+    direction: FftDirection,
+}
+
+impl<T: FftNum> NufftType1<T> {
+    /// Creates a type 1 NUFFT which will process values at `points`, and compute `output_len` uniformly spaced frequencies with
+    /// a relative error of at most `tolerance`. The grid FFT is planned with `planner`.
+    ///
+    /// # Panics
+    /// Panics if `output_len == 0`, if `tolerance` isn't between 0 and 1, or if any element of `points` isn't finite.
+    pub fn new(
+        points: &[f64],
+        output_len: usize,
+        tolerance: f64,
+        direction: FftDirection,
+        planner: &mut FftPlanner<T>,
+    ) -> Self {
+        Self {
+            spreader: Spreader::new(points, output_len, tolerance, direction, planner),
+            direction,
+        }
+    }
+
+    /// Returns the number of frequencies computed for each input chunk
+    pub fn output_len(&self) -> usize {
+        self.spreader.frequency_count()
+    }
+
+    /// Returns the size of the scratch buffer required by `process_with_scratch`
+    pub fn get_scratch_len(&self) -> usize {
+        self.spreader.get_scratch_len()
+    }
+
+    /// Divides `input` into chunks of size `self.len()`, and `output` into chunks of size `self.output_len()`,
+    /// and computes the frequencies of each input chunk into the corresponding output chunk.
+    ///
+    /// Convenience method that allocates a `Vec` with the required scratch space and calls `self.process_with_scratch`.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if:
+    /// - `input.len() % self.len() > 0`
+    /// - `input.len() / self.len() != output.len() / self.output_len()`
+    /// - `output.len() % self.output_len() > 0`
+    pub fn process(&self, input: &[Complex<T>], output: &mut [Complex<T>]) {
+        let mut scratch = vec![Complex::zero(); self.get_scratch_len()];
+        self.process_with_scratch(input, output, &mut scratch);
+    }
+
+    /// Divides `input` into chunks of size `self.len()`, and `output` into chunks of size `self.output_len()`,
+    /// and computes the frequencies of each input chunk into the corresponding output chunk.
+    ///
+    /// Uses the `scratch` buffer as scratch space, so the contents of `scratch` should be considered garbage
+    /// after calling.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if:
+    /// - `input.len() % self.len() > 0`
+    /// - `input.len() / self.len() != output.len() / self.output_len()`
+    /// - `output.len() % self.output_len() > 0`
+    /// - `scratch.len() < self.get_scratch_len()`
+    pub fn process_with_scratch(
+        &self,
+        input: &[Complex<T>],
+        output: &mut [Complex<T>],
+        scratch: &mut [Complex<T>],
+    ) {
+        let len = self.len();
+        let output_len = self.output_len();
+        assert_chunks("NUFFT", input, output, len, output_len);
+        assert!(
+            scratch.len() >= self.get_scratch_len(),
+            "Not enough scratch space was provided. Expected scratch len >= {}, got scratch len = {}",
+            self.get_scratch_len(),
+            scratch.len()
+        );
+
+        // With no points, every frequency is an empty sum
+        if len == 0 {
+            for element in output.iter_mut() {
+                *element = Complex::zero();
+            }
+            return;
+        }
+
+        let fft = &self.spreader.fft;
+        let (grid, fft_scratch) = scratch.split_at_mut(fft.len());
+        let fft_scratch = &mut fft_scratch[..fft.get_inplace_scratch_len()];
+        for (in_chunk, out_chunk) in input
+            .chunks_exact(len)
+            .zip(output.chunks_exact_mut(output_len))
+        {
+            self.spreader.spread(in_chunk, grid);
+            fft.process_with_scratch(grid, fft_scratch);
+
+            for ((out, &index), &correction) in out_chunk
+                .iter_mut()
+                .zip(self.spreader.frequency_indexes.iter())
+                .zip(self.spreader.corrections.iter())
+            {
+                *out = grid[index] * correction;
+            }
+        }
+    }
+}
+impl<T> Length for NufftType1<T> {
+    fn len(&self) -> usize {
+        self.spreader.grid_starts.len()
+    }
+}
+impl<T> Direction for NufftType1<T> {
+    fn fft_direction(&self) -> FftDirection {
+        self.direction
+    }
+}
+
+/// Type 2 non-uniform FFT: Evaluates a signal at non-uniformly spaced points, given its uniformly spaced frequencies
+///
+/// Given N frequency coefficients `input[i]`, computes M values at points `x[j]`:
+///
+/// `output[j] = sum(input[i] * exp(-i * k * x[j]))`, where `k` is the frequency of input `i`
+///
+/// for a forward transform, or with `+i` in the exponent for an inverse transform. This is the adjoint of [`NufftType1`] with the opposite direction,
+/// and it uses the same conventions: Points are in radians, and frequencies are in the same order as a FFT's output.
+/// So an inverse type 2 NUFFT evaluates the signal whose FFT is `input` at arbitrary points, up to a factor of N.
+///
+/// The kernel's effect is divided out of each frequency, the frequencies are placed on an oversampled uniform grid and transformed
+/// with a planned FFT, and then each point's value is interpolated from the grid using an "exponential of semicircle" kernel.
+/// The kernel's width is chosen to meet the requested tolerance, which bounds the error relative to the size of the output.
+///
+/// ~~~
+/// // Evaluate a signal with 64 frequencies at 1000 randomly spaced points
+/// use rustfft::nufft::NufftType2;
+/// use rustfft::{FftDirection, FftPlanner};
+/// use rustfft::num_complex::Complex;
+///
+/// let points: Vec<f64> = (0..1000).map(|i| (i as f64 * 0.618).sin() * 3.0).collect();
+/// let input = vec![Complex{ re: 0.0f32, im: 0.0f32 }; 64];
+/// let mut output = vec![Complex{ re: 0.0f32, im: 0.0f32 }; 1000];
+///
+/// let mut planner = FftPlanner::new();
+/// let nufft = NufftType2::new(&points, 64, 1e-5, FftDirection::Inverse, &mut planner);
+/// nufft.process(&input, &mut output);
+/// ~~~
+pub struct NufftType2<T> {
+    spreader: Spreader<T>,
+    direction: FftDirection,
+}
+
+impl<T: FftNum> NufftType2<T> {
+    /// Creates a type 2 NUFFT which will process `len` uniformly spaced frequencies, and evaluate them at `points` with
+    /// a relative error of at most `tolerance`. The grid FFT is planned with `planner`.
+    ///
+    /// # Panics
+    /// Panics if `len == 0`, if `tolerance` isn't between 0 and 1, or if any element of `points` isn't finite.
+    pub fn new(
+        points: &[f64],
+        len: usize,
+        tolerance: f64,
+        direction: FftDirection,
+        planner: &mut FftPlanner<T>,
+    ) -> Self {
+        Self {
+            spreader: Spreader::new(points, len, tolerance, direction, planner),
+            direction,
+        }
+    }
+
+    /// Returns the number of points evaluated for each input chunk
+    pub fn output_len(&self) -> usize {
+        self.spreader.point_count()
+    }
+
+    /// Returns the size of the scratch buffer required by `process_with_scratch`
+    pub fn get_scratch_len(&self) -> usize {
+        self.spreader.get_scratch_len()
+    }
+
+    /// Divides `input` into chunks of size `self.len()`, and `output` into chunks of size `self.output_len()`,
+    /// and evaluates each input chunk at the points into the corresponding output chunk.
+    ///
+    /// Convenience method that allocates a `Vec` with the required scratch space and calls `self.process_with_scratch`.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if:
+    /// - `input.len() % self.len() > 0`
+    /// - `input.len() / self.len() != output.len() / self.output_len()`
+    /// - `output.len() % self.output_len() > 0`
+    pub fn process(&self, input: &[Complex<T>], output: &mut [Complex<T>]) {
+        let mut scratch = vec![Complex::zero(); self.get_scratch_len()];
+        self.process_with_scratch(input, output, &mut scratch);
+    }
+
+    /// Divides `input` into chunks of size `self.len()`, and `output` into chunks of size `self.output_len()`,
+    /// and evaluates each input chunk at the points into the corresponding output chunk.
+    ///
+    /// Uses the `scratch` buffer as scratch space, so the contents of `scratch` should be considered garbage
+    /// after calling.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if:
+    /// - `input.len() % self.len() > 0`
+    /// - `input.len() / self.len() != output.len() / self.output_len()`
+    /// - `output.len() % self.output_len() > 0`
+    /// - `scratch.len() < self.get_scratch_len()`
+    pub fn process_with_scratch(
+        &self,
+        input: &[Complex<T>],
+        output: &mut [Complex<T>],
+        scratch: &mut [Complex<T>],
+    ) {
+        let len = self.len();
+        let output_len = self.output_len();
+        assert_chunks("NUFFT", input, output, len, output_len);
+        assert!(
+            scratch.len() >= self.get_scratch_len(),
+            "Not enough scratch space was provided. Expected scratch len >= {}, got scratch len = {}",
+            self.get_scratch_len(),
+            scratch.len()
+        );
+
+        // With no points, there's nothing to evaluate
+        if output_len == 0 {
+            return;
+        }
+
+        let fft = &self.spreader.fft;
+        let (grid, fft_scratch) = scratch.split_at_mut(fft.len());
+        let fft_scratch = &mut fft_scratch[..fft.get_inplace_scratch_len()];
+        for (in_chunk, out_chunk) in input
+            .chunks_exact(len)
+            .zip(output.chunks_exact_mut(output_len))
+        {
+            for element in grid.iter_mut() {
+                *element = Complex::zero();
+            }
+            for ((&value, &index), &correction) in in_chunk
+                .iter()
+                .zip(self.spreader.frequency_indexes.iter())
+                .zip(self.spreader.corrections.iter())
+            {
+                grid[index] = value * correction;
+            }
+
+            fft.process_with_scratch(grid, fft_scratch);
+            self.spreader.interpolate(grid, out_chunk);
+        }
+    }
+}
+impl<T> Length for NufftType2<T> {
+    fn len(&self) -> usize {
+        self.spreader.frequency_indexes.len()
+    }
+}
+impl<T> Direction for NufftType2<T> {
+    fn fft_direction(&self) -> FftDirection {
+        self.direction
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::algorithm::Dft;
+    use crate::test_utils::random_signal;
+
+    fn random_points(count: usize) -> Vec<f64> {
+        // Deliberately spread the points beyond a single period, to exercise the wraparound
+        random_signal::<f64>(count)
+            .iter()
+            .map(|c| c.re * 4f64 - 2f64 * PI)
+            .collect()
+    }
+
+    fn frequency(i: usize, len: usize) -> f64 {
+        if i < len - len / 2 {
+            i as f64
+        } else {
+            i as f64 - len as f64
+        }
+    }
+
+    fn sign(direction: FftDirection) -> f64 {
+        match direction {
+            FftDirection::Forward => -1f64,
+            FftDirection::Inverse => 1f64,
+        }
+    }
+
+    // Returns the l2 norm of the error, relative to the l2 norm of `expected`
+    fn relative_error(expected: &[Complex<f64>], actual: &[Complex<f64>]) -> f64 {
+        let error: f64 = expected
+            .iter()
+            .zip(actual.iter())
+            .map(|(e, a)| (e - a).norm_sqr())
+            .sum();
+        let magnitude: f64 = expected.iter().map(|e| e.norm_sqr()).sum();
+        (error / magnitude).sqrt()
+    }
+
+    #[test]
+    fn test_nufft_type1() {
+        let mut planner = FftPlanner::new();
+        for &(point_count, output_len) in &[(1, 1), (50, 33), (300, 64), (17, 100)] {
+            for &tolerance in &[1e-3, 1e-6, 1e-9, 1e-12] {
+                for &direction in &[FftDirection::Forward, FftDirection::Inverse] {
+                    let points = random_points(point_count);
+                    let input: Vec<Complex<f64>> = random_signal(point_count);
+
+                    let expected: Vec<Complex<f64>> = (0..output_len)
+                        .map(|i| {
+                            let k = frequency(i, output_len);
+                            points
+                                .iter()
+                                .zip(input.iter())
+                                .map(|(&x, c)| {
+                                    c * Complex::from_polar(1f64, sign(direction) * k * x)
+                                })
+                                .sum()
+                        })
+                        .collect();
+
+                    let nufft =
+                        NufftType1::new(&points, output_len, tolerance, direction, &mut planner);
+                    let mut actual = vec![Complex::zero(); output_len];
+                    nufft.process(&input, &mut actual);
+
+                    let error = relative_error(&expected, &actual);
+                    assert!(
+                        error < tolerance,
+                        "points = {}, output_len = {}, tolerance = {}, error = {}",
+                        point_count,
+                        output_len,
+                        tolerance,
+                        error
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_nufft_type2() {
+        let mut planner = FftPlanner::new();
+        for &(len, point_count) in &[(1, 1), (33, 50), (64, 300), (100, 17)] {
+            for &tolerance in &[1e-3, 1e-6, 1e-9, 1e-12] {
+                for &direction in &[FftDirection::Forward, FftDirection::Inverse] {
+                    let points = random_points(point_count);
+                    let input: Vec<Complex<f64>> = random_signal(len);
+
+                    let expected: Vec<Complex<f64>> = points
+                        .iter()
+                        .map(|&x| {
+                            input
+                                .iter()
+                                .enumerate()
+                                .map(|(i, f)| {
+                                    let k = frequency(i, len);
+                                    f * Complex::from_polar(1f64, sign(direction) * k * x)
+                                })
+                                .sum()
+                        })
+                        .collect();
+
+                    let nufft = NufftType2::new(&points, len, tolerance, direction, &mut planner);
+                    let mut actual = vec![Complex::zero(); point_count];
+                    nufft.process(&input, &mut actual);
+
+                    let error = relative_error(&expected, &actual);
+                    assert!(
+                        error < tolerance,
+                        "len = {}, points = {}, tolerance = {}, error = {}",
+                        len,
+                        point_count,
+                        tolerance,
+                        error
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_nufft_uniform_points() {
+        // With uniformly spaced points, the type 1 NUFFT is a FFT
+        let len = 40;
+        let points: Vec<f64> = (0..len)
+            .map(|i| 2f64 * PI * i as f64 / len as f64)
+            .collect();
+        let input: Vec<Complex<f64>> = random_signal(len * 3);
+
+        let mut planner = FftPlanner::new();
+        let nufft = NufftType1::new(&points, len, 1e-10, FftDirection::Forward, &mut planner);
+        let mut actual = vec![Complex::zero(); len * 3];
+        nufft.process(&input, &mut actual);
+
+        let mut expected = input.clone();
+        Dft::new(len, FftDirection::Forward).process(&mut expected);
+        assert!(relative_error(&expected, &actual) < 1e-10);
+    }
+}
@@ -1,18 +1,26 @@
-use num_integer::gcd;
+use num_integer::{div_ceil, gcd, Integer};
+use std::cmp::max;
 use std::collections::HashMap;
-use std::rc::Rc;
-use std::sync::Arc;
+use std::ops::Range;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
 
-use crate::{common::FftNum, fft_cache::FftCache, FftDirection};
+use crate::cost_model::{CostModel, FftEstimate};
+use crate::fft_cache::{CachePolicy, FftCache};
+use crate::{common::FftNum, FftDirection};
 
 use crate::algorithm::butterflies::*;
 use crate::algorithm::*;
-use crate::Fft;
+use crate::{Fft, OutputPrunedFft};
 
 use crate::FftPlannerAvx;
 
-use crate::math_utils::{PrimeFactor, PrimeFactors};
+#[cfg(feature = "half")]
+use crate::half_precision::{HalfFloat, HalfPrecisionFft};
 
+use crate::math_utils::{next_smooth_number, prev_smooth_number, PrimeFactor, PrimeFactors};
+
+// There's only ever one of these per planner, so there's no point boxing the larger variant
+#[allow(clippy::large_enum_variant)]
 enum ChosenFftPlanner<T: FftNum> {
     Scalar(FftPlannerScalar<T>),
     Avx(FftPlannerAvx<T>),
@@ -60,13 +68,23 @@ pub struct FftPlanner<T: FftNum> {
 impl<T: FftNum> FftPlanner<T> {
     /// Creates a new `FftPlanner` instance.
     pub fn new() -> Self {
-        if let Ok(avx_planner) = FftPlannerAvx::new() {
+        Self::with_options(PlannerOptions::new())
+    }
+
+    /// Creates a new `FftPlanner` instance, which chooses algorithms according to `options`.
+    ///
+    /// See [`PlannerOptions`](crate::PlannerOptions) for which options the AVX planner takes into account.
+    ///
+    /// # Panics
+    /// Panics if the options set `min_radix4_bits` larger than `max_radix4_bits`.
+    pub fn with_options(options: PlannerOptions) -> Self {
+        if let Ok(avx_planner) = FftPlannerAvx::with_options(options.clone()) {
             Self {
                 chosen_planner: ChosenFftPlanner::Avx(avx_planner),
             }
         } else {
             Self {
-                chosen_planner: ChosenFftPlanner::Scalar(FftPlannerScalar::new()),
+                chosen_planner: ChosenFftPlanner::Scalar(FftPlannerScalar::with_options(options)),
             }
         }
     }
@@ -96,12 +114,673 @@ impl<T: FftNum> FftPlanner<T> {
     pub fn plan_fft_inverse(&mut self, len: usize) -> Arc<dyn Fft<T>> {
         self.plan_fft(len, FftDirection::Inverse)
     }
+
+    /// Returns a `OutputPrunedFft` instance which computes FFTs of size `len`, but only guarantees the outputs whose element of `output_mask` is true.
+    ///
+    /// Work that doesn't contribute to any selected output is skipped. When AVX is available, the planner returns an AVX FFT
+    /// that computes every output instead, because that's faster than a scalar pruned FFT.
+    ///
+    /// # Panics
+    /// Panics if `output_mask.len() != len`.
+    pub fn plan_fft_output_pruned(
+        &mut self,
+        len: usize,
+        direction: FftDirection,
+        output_mask: &[bool],
+    ) -> Arc<dyn OutputPrunedFft<T>> {
+        match &mut self.chosen_planner {
+            ChosenFftPlanner::Scalar(scalar_planner) => {
+                scalar_planner.plan_fft_output_pruned(len, direction, output_mask)
+            }
+            ChosenFftPlanner::Avx(avx_planner) => {
+                assert_output_mask_len(len, output_mask);
+                Arc::new(FullOutput::new(avx_planner.plan_fft(len, direction)))
+            }
+        }
+    }
+
+    /// Returns a `OutputPrunedFft` instance which computes FFTs of size `len`, but only guarantees the outputs in `output_range`.
+    ///
+    /// See [`plan_fft_output_pruned`](crate::FftPlanner::plan_fft_output_pruned) for details.
+    ///
+    /// # Panics
+    /// Panics if `output_range.end > len`.
+    pub fn plan_fft_output_range(
+        &mut self,
+        len: usize,
+        direction: FftDirection,
+        output_range: Range<usize>,
+    ) -> Arc<dyn OutputPrunedFft<T>> {
+        self.plan_fft_output_pruned(len, direction, &range_to_mask(len, output_range))
+    }
+
+    /// Returns a `Fft` instance which computes FFTs of size `len`, for inputs whose first `nonzero_len` elements are the only non-zero elements.
+    ///
+    /// The elements after the first `nonzero_len` must be zero, and the returned FFT computes the same output as `plan_fft`, but skips work
+    /// on those known-zero inputs. When AVX is available, the planner returns an AVX FFT that processes every input instead.
+    ///
+    /// # Panics
+    /// Panics if `nonzero_len > len`.
+    pub fn plan_fft_input_pruned(
+        &mut self,
+        len: usize,
+        direction: FftDirection,
+        nonzero_len: usize,
+    ) -> Arc<dyn Fft<T>> {
+        match &mut self.chosen_planner {
+            ChosenFftPlanner::Scalar(scalar_planner) => {
+                scalar_planner.plan_fft_input_pruned(len, direction, nonzero_len)
+            }
+            ChosenFftPlanner::Avx(avx_planner) => {
+                assert_nonzero_len(len, nonzero_len);
+                avx_planner.plan_fft(len, direction)
+            }
+        }
+    }
+
+    /// Sets the policy that limits which FFT instances this planner keeps in its cache. If the cache doesn't fit the new policy,
+    /// the least recently used instances are discarded immediately.
+    ///
+    /// FFT instances that have already been returned by the planner stay valid when they're discarded from the cache.
+    pub fn set_cache_policy(&mut self, policy: CachePolicy) {
+        match &mut self.chosen_planner {
+            ChosenFftPlanner::Scalar(scalar_planner) => scalar_planner.set_cache_policy(policy),
+            ChosenFftPlanner::Avx(avx_planner) => avx_planner.set_cache_policy(policy),
+        }
+    }
+
+    /// Returns the policy that limits which FFT instances this planner keeps in its cache
+    pub fn cache_policy(&self) -> CachePolicy {
+        match &self.chosen_planner {
+            ChosenFftPlanner::Scalar(scalar_planner) => scalar_planner.cache_policy(),
+            ChosenFftPlanner::Avx(avx_planner) => avx_planner.cache_policy(),
+        }
+    }
+
+    /// Discards every FFT instance and every plan from this planner's caches.
+    ///
+    /// FFT instances that have already been returned by the planner stay valid.
+    pub fn clear(&mut self) {
+        match &mut self.chosen_planner {
+            ChosenFftPlanner::Scalar(scalar_planner) => scalar_planner.clear(),
+            ChosenFftPlanner::Avx(avx_planner) => avx_planner.clear(),
+        }
+    }
+
+    /// Discards the cached FFT instances of size `len`, in both directions, from this planner's caches.
+    ///
+    /// Instances of other sizes that use a FFT of size `len` internally keep their own reference to it, so they aren't affected.
+    pub fn evict(&mut self, len: usize) {
+        match &mut self.chosen_planner {
+            ChosenFftPlanner::Scalar(scalar_planner) => scalar_planner.evict(len),
+            ChosenFftPlanner::Avx(avx_planner) => avx_planner.evict(len),
+        }
+    }
+
+    /// Returns the number of heap bytes owned by the FFT instances in this planner's cache.
+    ///
+    /// This is the quantity limited by [`CachePolicy::MaxBytes`](crate::CachePolicy::MaxBytes). Each instance counts only the data returned by [`Fft::own_heap_bytes`](crate::Fft::own_heap_bytes),
+    /// so an inner FFT shared by several cached instances is only counted once.
+    pub fn cache_memory_bytes(&self) -> usize {
+        match &self.chosen_planner {
+            ChosenFftPlanner::Scalar(scalar_planner) => scalar_planner.cache_memory_bytes(),
+            ChosenFftPlanner::Avx(avx_planner) => avx_planner.cache_memory_bytes(),
+        }
+    }
+
+    /// Registers a custom FFT instance, which the planner will use whenever it needs a FFT of the same size and direction,
+    /// including as an inner FFT of larger sizes.
+    ///
+    /// See [`FftPlannerScalar::register`](crate::FftPlannerScalar::register) for details.
+    ///
+    /// ~~~
+    /// // Use a custom FFT of size 10 for every FFT whose size is a multiple of 10
+    /// use std::sync::Arc;
+    /// use rustfft::{algorithm::Dft, Fft, FftDirection, FftPlanner};
+    ///
+    /// let mut planner = FftPlanner::<f32>::new();
+    /// planner.register(Arc::new(Dft::new(10, FftDirection::Forward)));
+    ///
+    /// let fft = planner.plan_fft_forward(1230);
+    /// ~~~
+    pub fn register(&mut self, fft: Arc<dyn Fft<T>>) {
+        match &mut self.chosen_planner {
+            ChosenFftPlanner::Scalar(scalar_planner) => scalar_planner.register(fft),
+            ChosenFftPlanner::Avx(avx_planner) => avx_planner.register(fft),
+        }
+    }
+
+    /// Returns the smallest FFT size that's at least `min_len`, and that the chosen planner computes especially fast.
+    ///
+    /// With AVX, that's a size of the form `2^n * 3^m`. Without AVX, it's a size whose prime factors are all 11 or smaller.
+    /// Padding a signal to this size can be much faster than computing a FFT of size `min_len`, if `min_len` has large prime factors.
+    ///
+    /// ~~~
+    /// // Zero-pad a signal of size 1201, which is prime, to a size that's faster to compute
+    /// use rustfft::{FftPlanner, num_complex::Complex};
+    ///
+    /// let mut planner = FftPlanner::<f32>::new();
+    /// let len = planner.next_fast_len(1201);
+    /// assert!(len >= 1201);
+    ///
+    /// let mut buffer = vec![Complex{ re: 0.0f32, im: 0.0f32 }; len];
+    /// planner.plan_fft_forward(len).process(&mut buffer);
+    /// ~~~
+    ///
+    /// # Panics
+    /// Panics if no such size fits in a `usize`.
+    pub fn next_fast_len(&self, min_len: usize) -> usize {
+        match &self.chosen_planner {
+            ChosenFftPlanner::Scalar(scalar_planner) => scalar_planner.next_fast_len(min_len),
+            ChosenFftPlanner::Avx(avx_planner) => avx_planner.next_fast_len(min_len),
+        }
+    }
+
+    /// Returns the largest FFT size that's at most `max_len`, and that the chosen planner computes especially fast.
+    /// If `max_len` is zero, returns zero.
+    ///
+    /// See [`next_fast_len`](crate::FftPlanner::next_fast_len) for which sizes are considered fast.
+    pub fn prev_fast_len(&self, max_len: usize) -> usize {
+        match &self.chosen_planner {
+            ChosenFftPlanner::Scalar(scalar_planner) => scalar_planner.prev_fast_len(max_len),
+            ChosenFftPlanner::Avx(avx_planner) => avx_planner.prev_fast_len(max_len),
+        }
+    }
+
+    /// Returns the estimated cost of the FFT of size `len`, according to the [`cost_model`](crate::PlannerOptions::cost_model) in this planner's options,
+    /// or the default [`CostModel`](crate::CostModel) if the options don't have one.
+    ///
+    /// See [`FftPlannerScalar::estimated_cost`](crate::FftPlannerScalar::estimated_cost) and [`FftPlannerAvx::estimated_cost`](crate::FftPlannerAvx::estimated_cost) for details.
+    pub fn estimated_cost(&mut self, len: usize) -> f64 {
+        match &mut self.chosen_planner {
+            ChosenFftPlanner::Scalar(scalar_planner) => scalar_planner.estimated_cost(len),
+            ChosenFftPlanner::Avx(avx_planner) => avx_planner.estimated_cost(len),
+        }
+    }
+
+    /// Returns an estimate of the operations, memory passes, and scratch of the FFT of size `len`, without creating it.
+    ///
+    /// See [`FftPlannerScalar::estimate`](crate::FftPlannerScalar::estimate) and [`FftPlannerAvx::estimate`](crate::FftPlannerAvx::estimate) for details.
+    pub fn estimate(&mut self, len: usize) -> FftEstimate {
+        match &mut self.chosen_planner {
+            ChosenFftPlanner::Scalar(scalar_planner) => scalar_planner.estimate(len),
+            ChosenFftPlanner::Avx(avx_planner) => avx_planner.estimate(len),
+        }
+    }
+}
+
+#[cfg(feature = "half")]
+impl FftPlanner<f32> {
+    /// Returns a `HalfPrecisionFft` instance which computes FFTs of size `len` on half-precision buffers, using an `f32` FFT internally.
+    ///
+    /// The first pass uses the largest butterfly among 16, 8, 7, 6, 5, 4, 3 and 2 that divides `len`, converting the input as it loads it.
+    /// The inner `f32` FFT for the rest of `len` is planned exactly like `plan_fft` would, and shares the planner's cache.
+    pub fn plan_fft_half_precision<H: HalfFloat>(
+        &mut self,
+        len: usize,
+        direction: FftDirection,
+    ) -> HalfPrecisionFft<H> {
+        let first_pass_len = [16, 8, 7, 6, 5, 4, 3, 2]
+            .iter()
+            .cloned()
+            .find(|&butterfly_len| len > 0 && len % butterfly_len == 0)
+            .unwrap_or(1);
+        HalfPrecisionFft::new(
+            first_pass_len,
+            self.plan_fft(len / first_pass_len, direction),
+        )
+    }
+
+    /// Returns a `HalfPrecisionFft` instance which computes forward FFTs of size `len` on half-precision buffers
+    ///
+    /// See [`plan_fft_half_precision`](crate::FftPlanner::plan_fft_half_precision) for details.
+    pub fn plan_fft_half_precision_forward<H: HalfFloat>(
+        &mut self,
+        len: usize,
+    ) -> HalfPrecisionFft<H> {
+        self.plan_fft_half_precision(len, FftDirection::Forward)
+    }
+
+    /// Returns a `HalfPrecisionFft` instance which computes inverse FFTs of size `len` on half-precision buffers
+    ///
+    /// See [`plan_fft_half_precision`](crate::FftPlanner::plan_fft_half_precision) for details.
+    pub fn plan_fft_half_precision_inverse<H: HalfFloat>(
+        &mut self,
+        len: usize,
+    ) -> HalfPrecisionFft<H> {
+        self.plan_fft_half_precision(len, FftDirection::Inverse)
+    }
+}
+
+/// A FFT planner which can be shared between threads, and used to plan FFTs through a shared reference.
+///
+/// `FftPlannerSync` chooses algorithms the same way [`FftPlanner`](crate::FftPlanner) does, and keeps the same caches, but those
+/// caches sit behind a [`Mutex`](std::sync::Mutex), so its planning methods take `&self`. Wrap it in an `Arc` or a `static` to plan
+/// FFTs from many threads: every thread gets the same FFT instances, and shares the same twiddle tables, instead of each thread
+/// holding its own planner.
+///
+/// Every method holds the lock until it returns, and planning methods hold it until the FFT has been built. Building a large FFT
+/// can take a while, because it computes twiddle factors, and for Bluestein's and Rader's algorithms, a FFT of the convolution multiplier.
+/// In the meantime, every other thread that uses this planner waits, even if it's asking for a size that's already cached. In exchange,
+/// if two threads request the same size at the same time, one of them waits for the other and then reuses its instance, instead of
+/// both building it. Computing FFTs doesn't involve the planner at all, so it never takes the lock.
+///
+/// If some threads need their FFTs quickly while other threads plan large sizes, plan the large sizes ahead of time, or give those
+/// threads their own planner.
+///
+/// ~~~
+/// // Plan FFTs of size 1234 from several threads, sharing one planner
+/// use std::sync::Arc;
+/// use std::thread;
+/// use rustfft::{FftPlannerSync, num_complex::Complex};
+///
+/// let planner = Arc::new(FftPlannerSync::<f32>::new());
+///
+/// let threads: Vec<_> = (0..4).map(|_| {
+///     let planner = Arc::clone(&planner);
+///     thread::spawn(move || {
+///         let fft = planner.plan_fft_forward(1234);
+///
+///         let mut buffer = vec![Complex{ re: 0.0f32, im: 0.0f32 }; 1234];
+///         fft.process(&mut buffer);
+///     })
+/// }).collect();
+///
+/// for thread in threads {
+///     thread.join().unwrap();
+/// }
+/// ~~~
+pub struct FftPlannerSync<T: FftNum> {
+    planner: Mutex<FftPlanner<T>>,
+}
+impl<T: FftNum> FftPlannerSync<T> {
+    /// Creates a new `FftPlannerSync` instance.
+    pub fn new() -> Self {
+        Self::from_planner(FftPlanner::new())
+    }
+
+    /// Creates a new `FftPlannerSync` instance which plans FFTs using `planner`, and shares its caches
+    pub fn from_planner(planner: FftPlanner<T>) -> Self {
+        Self {
+            planner: Mutex::new(planner),
+        }
+    }
+
+    /// Returns a `Fft` instance which computes FFTs of size `len`.
+    ///
+    /// If the provided `direction` is `FftDirection::Forward`, the returned instance will compute forward FFTs. If it's `FftDirection::Inverse`, it will compute inverse FFTs.
+    ///
+    /// If this is called multiple times, from any thread, the planner will attempt to re-use internal data between calls, reducing memory usage and FFT initialization time.
+    ///
+    /// Holds the planner's lock until the FFT has been built, so other threads using this planner wait until it's done.
+    pub fn plan_fft(&self, len: usize, direction: FftDirection) -> Arc<dyn Fft<T>> {
+        self.lock().plan_fft(len, direction)
+    }
+
+    /// Returns a `Fft` instance which computes forward FFTs of size `len`
+    ///
+    /// If this is called multiple times, from any thread, the planner will attempt to re-use internal data between calls, reducing memory usage and FFT initialization time.
+    pub fn plan_fft_forward(&self, len: usize) -> Arc<dyn Fft<T>> {
+        self.plan_fft(len, FftDirection::Forward)
+    }
+
+    /// Returns a `Fft` instance which computes inverse FFTs of size `len`
+    ///
+    /// If this is called multiple times, from any thread, the planner will attempt to re-use internal data between calls, reducing memory usage and FFT initialization time.
+    pub fn plan_fft_inverse(&self, len: usize) -> Arc<dyn Fft<T>> {
+        self.plan_fft(len, FftDirection::Inverse)
+    }
+
+    /// Sets the policy that limits which FFT instances this planner keeps in its cache. If the cache doesn't fit the new policy,
+    /// the least recently used instances are discarded immediately.
+    ///
+    /// FFT instances that have already been returned by the planner stay valid when they're discarded from the cache.
+    pub fn set_cache_policy(&self, policy: CachePolicy) {
+        self.lock().set_cache_policy(policy)
+    }
+
+    /// Returns the policy that limits which FFT instances this planner keeps in its cache
+    pub fn cache_policy(&self) -> CachePolicy {
+        self.lock().cache_policy()
+    }
+
+    /// Discards every FFT instance and every plan from this planner's caches.
+    ///
+    /// FFT instances that have already been returned by the planner stay valid.
+    pub fn clear(&self) {
+        self.lock().clear()
+    }
+
+    /// Discards the cached FFT instances of size `len`, in both directions, from this planner's caches.
+    ///
+    /// Instances of other sizes that use a FFT of size `len` internally keep their own reference to it, so they aren't affected.
+    pub fn evict(&self, len: usize) {
+        self.lock().evict(len)
+    }
+
+    /// Returns the number of heap bytes owned by the FFT instances in this planner's cache.
+    ///
+    /// This is the quantity limited by [`CachePolicy::MaxBytes`](crate::CachePolicy::MaxBytes). Each instance counts only the data returned by [`Fft::own_heap_bytes`](crate::Fft::own_heap_bytes),
+    /// so an inner FFT shared by several cached instances is only counted once.
+    pub fn cache_memory_bytes(&self) -> usize {
+        self.lock().cache_memory_bytes()
+    }
+
+    /// Registers a custom FFT instance, which the planner will use whenever it needs a FFT of the same size and direction,
+    /// including as an inner FFT of larger sizes.
+    ///
+    /// See [`FftPlannerScalar::register`](crate::FftPlannerScalar::register) for details.
+    pub fn register(&self, fft: Arc<dyn Fft<T>>) {
+        self.lock().register(fft)
+    }
+
+    /// Returns the smallest FFT size that's at least `min_len`, and that the chosen planner computes especially fast.
+    ///
+    /// See [`FftPlanner::next_fast_len`](crate::FftPlanner::next_fast_len) for details.
+    pub fn next_fast_len(&self, min_len: usize) -> usize {
+        self.lock().next_fast_len(min_len)
+    }
+
+    /// Returns the largest FFT size that's at most `max_len`, and that the chosen planner computes especially fast.
+    ///
+    /// See [`FftPlanner::prev_fast_len`](crate::FftPlanner::prev_fast_len) for details.
+    pub fn prev_fast_len(&self, max_len: usize) -> usize {
+        self.lock().prev_fast_len(max_len)
+    }
+
+    /// Returns the estimated cost of the FFT of size `len`, according to the cost model in this planner's options.
+    ///
+    /// See [`FftPlanner::estimated_cost`](crate::FftPlanner::estimated_cost) for details.
+    pub fn estimated_cost(&self, len: usize) -> f64 {
+        self.lock().estimated_cost(len)
+    }
+
+    /// Returns an estimate of the operations, memory passes, and scratch of the FFT of size `len`, without creating it.
+    ///
+    /// See [`FftPlanner::estimate`](crate::FftPlanner::estimate) for details.
+    pub fn estimate(&self, len: usize) -> FftEstimate {
+        self.lock().estimate(len)
+    }
+
+    /// Consumes this planner, returning the `FftPlanner` that it wraps
+    pub fn into_planner(self) -> FftPlanner<T> {
+        self.planner
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, FftPlanner<T>> {
+        // If another thread panicked while planning, the caches only contain fully built FFTs, so it's safe to keep using them
+        self.planner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+impl<T: FftNum> Default for FftPlannerSync<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn assert_output_mask_len(len: usize, output_mask: &[bool]) {
+    assert_eq!(
+        output_mask.len(),
+        len,
+        "The output mask must contain one element per FFT output. Expected {}, got {}",
+        len,
+        output_mask.len()
+    );
+}
+
+fn assert_nonzero_len(len: usize, nonzero_len: usize) {
+    assert!(
+        nonzero_len <= len,
+        "The number of non-zero inputs can't exceed the FFT length. Expected nonzero_len <= {}, got {}",
+        len,
+        nonzero_len
+    );
+}
+
+fn range_to_mask(len: usize, output_range: Range<usize>) -> Vec<bool> {
+    assert!(
+        output_range.end <= len,
+        "The output range must be within the FFT. Expected range end <= {}, got {:?}",
+        len,
+        output_range
+    );
+    (0..len).map(|i| output_range.contains(&i)).collect()
 }
 
 const MIN_RADIX4_BITS: u32 = 5; // smallest size to consider radix 4 an option is 2^5 = 32
 const MAX_RADIX4_BITS: u32 = 16; // largest size to consider radix 4 an option is 2^16 = 65536
 const MAX_RADER_PRIME_FACTOR: usize = 23; // don't use Raders if the inner fft length has prime factor larger than this
 const MIN_BLUESTEIN_MIXED_RADIX_LEN: usize = 90; // only use mixed radix for the inner fft of Bluestein if length is larger than this
+const MAX_GOOD_THOMAS_LEN: usize = 4096; // only use the large good-thomas algorithm for coprime factors if length is this or smaller
+const FAST_ODD_PRIMES: [usize; 4] = [3, 5, 7, 11]; // sizes whose prime factors are all 11 or smaller are fast with the scalar planner
+
+/// A family of FFT algorithms that a planner may choose, used with [`PlannerOptions`](crate::PlannerOptions) to allow or deny algorithms
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PlannerAlgorithm {
+    /// [`Radix4`](crate::algorithm::Radix4), for power-of-two sizes
+    Radix4,
+    /// [`MixedRadix`](crate::algorithm::MixedRadix) and [`MixedRadixSmall`](crate::algorithm::MixedRadixSmall), for composite sizes
+    MixedRadix,
+    /// [`GoodThomasAlgorithm`](crate::algorithm::GoodThomasAlgorithm) and [`GoodThomasAlgorithmSmall`](crate::algorithm::GoodThomasAlgorithmSmall), for composite sizes with coprime factors
+    GoodThomas,
+    /// [`RadersAlgorithm`](crate::algorithm::RadersAlgorithm), for prime sizes
+    Raders,
+    /// [`BluesteinsAlgorithm`](crate::algorithm::BluesteinsAlgorithm), for prime sizes
+    Bluesteins,
+}
+impl PlannerAlgorithm {
+    const ALL: [PlannerAlgorithm; 5] = [
+        PlannerAlgorithm::Radix4,
+        PlannerAlgorithm::MixedRadix,
+        PlannerAlgorithm::GoodThomas,
+        PlannerAlgorithm::Raders,
+        PlannerAlgorithm::Bluesteins,
+    ];
+}
+
+/// Options that control how a planner chooses FFT algorithms.
+///
+/// The default options are what [`FftPlannerScalar::new`](crate::FftPlannerScalar::new) and [`FftPlannerAvx::new`](crate::FftPlannerAvx::new) use.
+/// The best thresholds depend on the CPU and its cache sizes, so they're worth tuning with benchmarks on the target machine.
+///
+/// Butterflies and the naive [`Dft`](crate::algorithm::Dft) are always allowed. When none of the allowed algorithms can compute a size,
+/// the planner falls back to a `Dft`, which is correct but takes O(n^2) time.
+///
+/// The thresholds, scratch limits, and cost model only affect the scalar planner, which is also the planner used by [`FftPlanner`](crate::FftPlanner) when AVX isn't available.
+/// The AVX planner has its own heuristics, and only consults the allow and deny lists for Rader's and Bluestein's algorithms.
+///
+/// ~~~
+/// // Plan FFTs without Bluestein's algorithm, preferring Good-Thomas over mixed radix where possible
+/// use rustfft::{FftPlannerScalar, PlannerAlgorithm, PlannerOptions};
+///
+/// let options = PlannerOptions::new()
+///     .deny(PlannerAlgorithm::Bluesteins)
+///     .prefer_good_thomas(true)
+///     .max_radix4_bits(14);
+///
+/// let mut planner = FftPlannerScalar::<f32>::with_options(options);
+/// let fft = planner.plan_fft_forward(1234);
+/// ~~~
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlannerOptions {
+    min_radix4_bits: u32,
+    max_radix4_bits: u32,
+    max_rader_prime_factor: usize,
+    min_bluestein_mixed_radix_len: usize,
+    max_good_thomas_len: usize,
+    allowed: [bool; 5],
+    prefer_good_thomas: bool,
+    max_inplace_scratch_len: Option<usize>,
+    minimize_scratch: bool,
+    cost_model: Option<CostModel>,
+}
+impl PlannerOptions {
+    /// Creates the default options, which allow every algorithm
+    pub fn new() -> Self {
+        Self {
+            min_radix4_bits: MIN_RADIX4_BITS,
+            max_radix4_bits: MAX_RADIX4_BITS,
+            max_rader_prime_factor: MAX_RADER_PRIME_FACTOR,
+            min_bluestein_mixed_radix_len: MIN_BLUESTEIN_MIXED_RADIX_LEN,
+            max_good_thomas_len: MAX_GOOD_THOMAS_LEN,
+            allowed: [true; 5],
+            prefer_good_thomas: false,
+            max_inplace_scratch_len: None,
+            minimize_scratch: false,
+            cost_model: None,
+        }
+    }
+
+    /// Sets the smallest power-of-two factor, as a number of bits, that's computed with `Radix4`. Sizes whose power-of-two factor is smaller are split some other way. Defaults to 5.
+    ///
+    /// # Panics
+    /// Panics if `bits` is zero, or isn't smaller than the number of bits in a `usize`.
+    pub fn min_radix4_bits(mut self, bits: u32) -> Self {
+        assert_radix4_bits(bits);
+        self.min_radix4_bits = bits;
+        self
+    }
+
+    /// Sets the largest power-of-two factor, as a number of bits, that's computed with `Radix4`. Larger powers of two are split in half with `MixedRadix`. Defaults to 16.
+    ///
+    /// # Panics
+    /// Panics if `bits` is zero, or isn't smaller than the number of bits in a `usize`.
+    pub fn max_radix4_bits(mut self, bits: u32) -> Self {
+        assert_radix4_bits(bits);
+        self.max_radix4_bits = bits;
+        self
+    }
+
+    /// Sets the largest prime factor that the inner FFT of Rader's algorithm may have. Primes whose inner FFT has a larger prime factor use Bluestein's algorithm instead. Defaults to 23.
+    ///
+    /// # Panics
+    /// Panics if `factor < 2`. To never use Rader's algorithm, [`deny`](crate::PlannerOptions::deny) it instead.
+    pub fn max_rader_prime_factor(mut self, factor: usize) -> Self {
+        assert!(
+            factor >= 2,
+            "The maximum prime factor for Rader's algorithm must be at least 2. Got {}",
+            factor
+        );
+        self.max_rader_prime_factor = factor;
+        self
+    }
+
+    /// Sets the smallest size for which Bluestein's algorithm uses a mixed radix inner FFT, rather than a slightly longer power-of-two inner FFT. Defaults to 90.
+    pub fn min_bluestein_mixed_radix_len(mut self, len: usize) -> Self {
+        self.min_bluestein_mixed_radix_len = len;
+        self
+    }
+
+    /// Sets the largest size for which the planner uses `GoodThomasAlgorithm` rather than `MixedRadix` when a FFT splits into two coprime factors.
+    /// Good-Thomas avoids twiddle factors, but its index remapping gets slower than those twiddle factors for larger sizes. Defaults to 4096.
+    pub fn max_good_thomas_len(mut self, len: usize) -> Self {
+        self.max_good_thomas_len = len;
+        self
+    }
+
+    /// Allows the planner to choose `algorithm`. Every algorithm is allowed by default.
+    pub fn allow(mut self, algorithm: PlannerAlgorithm) -> Self {
+        self.allowed[algorithm as usize] = true;
+        self
+    }
+
+    /// Forbids the planner from choosing `algorithm`
+    pub fn deny(mut self, algorithm: PlannerAlgorithm) -> Self {
+        self.allowed[algorithm as usize] = false;
+        self
+    }
+
+    /// Allows the planner to choose the algorithms in `algorithms`, and forbids every other algorithm
+    pub fn allow_only(mut self, algorithms: &[PlannerAlgorithm]) -> Self {
+        for &algorithm in PlannerAlgorithm::ALL.iter() {
+            self.allowed[algorithm as usize] = algorithms.contains(&algorithm);
+        }
+        self
+    }
+
+    /// Returns true if the planner may choose `algorithm`
+    pub fn is_allowed(&self, algorithm: PlannerAlgorithm) -> bool {
+        self.allowed[algorithm as usize]
+    }
+
+    /// If `prefer` is true, the planner uses `GoodThomasAlgorithm` instead of `MixedRadix` whenever the two factors are coprime, regardless of
+    /// [`max_good_thomas_len`](crate::PlannerOptions::max_good_thomas_len). Defaults to false.
+    pub fn prefer_good_thomas(mut self, prefer: bool) -> Self {
+        self.prefer_good_thomas = prefer;
+        self
+    }
+
+    /// Sets a budget for the in-place scratch that planned FFTs may require, as a number of complex elements.
+    ///
+    /// Wherever the planner has a choice between algorithms, it uses the fastest one whose
+    /// [`get_inplace_scratch_len`](crate::Fft::get_inplace_scratch_len) fits within `max_len`, even if that's slower than the
+    /// algorithm it would choose otherwise. If none of them fit, it uses the one that requires the least scratch.
+    ///
+    /// The budget is best-effort: every algorithm except butterflies requires at least about `len` elements of scratch, and inner FFTs are
+    /// shared between sizes, so some sizes can't fit within a small budget. Check `get_inplace_scratch_len` on the planned FFT if the budget is a hard limit.
+    pub fn max_inplace_scratch_len(mut self, max_len: usize) -> Self {
+        self.max_inplace_scratch_len = Some(max_len);
+        self
+    }
+
+    /// If `minimize` is true, then wherever the planner has a choice between algorithms, it uses the one that requires the least in-place scratch,
+    /// even if that's slower. This usually means choosing Rader's algorithm over Bluestein's algorithm. Defaults to false.
+    pub fn minimize_scratch(mut self, minimize: bool) -> Self {
+        self.minimize_scratch = minimize;
+        self
+    }
+
+    /// Makes the planner search for the combination of algorithms with the lowest estimated cost according to `cost_model`, instead of following its built-in heuristics.
+    ///
+    /// The search tries every way of splitting a size into two factors, Rader's and Bluestein's algorithms for prime sizes, and several inner sizes for Bluestein's
+    /// algorithm, using the cheapest design for each inner size. It replaces the thresholds and [`prefer_good_thomas`](crate::PlannerOptions::prefer_good_thomas),
+    /// but still honors the allowed algorithms and the scratch limits. Searching takes longer than following the heuristics, but it's done once per size,
+    /// and the results are cached. By default, the planner follows its heuristics.
+    pub fn cost_model(mut self, cost_model: CostModel) -> Self {
+        self.cost_model = Some(cost_model);
+        self
+    }
+
+    /// Checks the options that can only be validated together, once every option has been set
+    pub(crate) fn validate(&self) {
+        assert!(
+            self.min_radix4_bits <= self.max_radix4_bits,
+            "The minimum Radix4 bits must not be larger than the maximum Radix4 bits. Got min = {}, max = {}",
+            self.min_radix4_bits,
+            self.max_radix4_bits
+        );
+    }
+
+    fn limits_scratch(&self) -> bool {
+        self.minimize_scratch || self.max_inplace_scratch_len.is_some()
+    }
+
+    // Estimates a recipe with the cost model in these options, or the default one if they don't have one
+    pub(crate) fn estimate_recipe(&self, recipe: &Recipe) -> FftEstimate {
+        match self.cost_model {
+            Some(ref cost_model) => FftEstimate::from_recipe(recipe, cost_model),
+            None => FftEstimate::from_recipe(recipe, &CostModel::new()),
+        }
+    }
+}
+
+impl Default for PlannerOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn assert_radix4_bits(bits: u32) {
+    let usize_bits = std::mem::size_of::<usize>() as u32 * 8;
+    assert!(
+        bits > 0 && bits < usize_bits,
+        "Radix4 thresholds must be between 1 and {} bits. Got {}",
+        usize_bits - 1,
+        bits
+    );
+}
 
 /// A Recipe is a structure that describes the design of a FFT, without actually creating it.
 /// It is used as a middle step in the planning process.
@@ -109,28 +788,27 @@ const MIN_BLUESTEIN_MIXED_RADIX_LEN: usize = 90; // only use mixed radix for the
 pub enum Recipe {
     Dft(usize),
     MixedRadix {
-        left_fft: Rc<Recipe>,
-        right_fft: Rc<Recipe>,
+        left_fft: Arc<Recipe>,
+        right_fft: Arc<Recipe>,
     },
-    #[allow(dead_code)]
     GoodThomasAlgorithm {
-        left_fft: Rc<Recipe>,
-        right_fft: Rc<Recipe>,
+        left_fft: Arc<Recipe>,
+        right_fft: Arc<Recipe>,
     },
     MixedRadixSmall {
-        left_fft: Rc<Recipe>,
-        right_fft: Rc<Recipe>,
+        left_fft: Arc<Recipe>,
+        right_fft: Arc<Recipe>,
     },
     GoodThomasAlgorithmSmall {
-        left_fft: Rc<Recipe>,
-        right_fft: Rc<Recipe>,
+        left_fft: Arc<Recipe>,
+        right_fft: Arc<Recipe>,
     },
     RadersAlgorithm {
-        inner_fft: Rc<Recipe>,
+        inner_fft: Arc<Recipe>,
     },
     BluesteinsAlgorithm {
         len: usize,
-        inner_fft: Rc<Recipe>,
+        inner_fft: Arc<Recipe>,
     },
     Radix4(usize),
     Butterfly2,
@@ -192,6 +870,94 @@ impl Recipe {
             Recipe::BluesteinsAlgorithm { len, .. } => *len,
         }
     }
+
+    /// Returns the in-place scratch length that the FFT built from this recipe will require, without building it.
+    /// These must be kept in sync with the scratch computations in each algorithm's constructor.
+    pub fn inplace_scratch_len(&self) -> usize {
+        match self {
+            Recipe::Dft(len) | Recipe::Radix4(len) => *len,
+            Recipe::MixedRadix {
+                left_fft,
+                right_fft,
+            } => {
+                let len = self.len();
+                let height_inplace_scratch = right_fft.inplace_scratch_len();
+                len + max(
+                    if height_inplace_scratch > len {
+                        height_inplace_scratch
+                    } else {
+                        0
+                    },
+                    left_fft.outofplace_scratch_len(),
+                )
+            }
+            Recipe::GoodThomasAlgorithm {
+                left_fft,
+                right_fft,
+            } => {
+                // GoodThomasAlgorithm swaps its inner FFTs so that the width is the smaller one
+                let len = self.len();
+                let (width_fft, height_fft) = if left_fft.len() > right_fft.len() {
+                    (right_fft, left_fft)
+                } else {
+                    (left_fft, right_fft)
+                };
+                let width_inplace_scratch = width_fft.inplace_scratch_len();
+                len + max(
+                    if width_inplace_scratch > len {
+                        width_inplace_scratch
+                    } else {
+                        0
+                    },
+                    height_fft.outofplace_scratch_len(),
+                )
+            }
+            Recipe::MixedRadixSmall { .. } | Recipe::GoodThomasAlgorithmSmall { .. } => self.len(),
+            Recipe::RadersAlgorithm { inner_fft } => {
+                inner_fft.len() + Self::raders_extra_scratch_len(inner_fft)
+            }
+            Recipe::BluesteinsAlgorithm { inner_fft, .. } => {
+                inner_fft.len() + inner_fft.inplace_scratch_len()
+            }
+            _ => 0, // butterflies
+        }
+    }
+
+    /// Returns the out-of-place scratch length that the FFT built from this recipe will require, without building it.
+    pub fn outofplace_scratch_len(&self) -> usize {
+        match self {
+            Recipe::MixedRadix {
+                left_fft,
+                right_fft,
+            }
+            | Recipe::GoodThomasAlgorithm {
+                left_fft,
+                right_fft,
+            } => {
+                let max_inner_inplace_scratch = max(
+                    left_fft.inplace_scratch_len(),
+                    right_fft.inplace_scratch_len(),
+                );
+                if max_inner_inplace_scratch > self.len() {
+                    max_inner_inplace_scratch
+                } else {
+                    0
+                }
+            }
+            Recipe::RadersAlgorithm { inner_fft } => Self::raders_extra_scratch_len(inner_fft),
+            Recipe::BluesteinsAlgorithm { .. } => self.inplace_scratch_len(),
+            _ => 0,
+        }
+    }
+
+    fn raders_extra_scratch_len(inner_fft: &Recipe) -> usize {
+        let inner_inplace_scratch = inner_fft.inplace_scratch_len();
+        if inner_inplace_scratch <= inner_fft.len() {
+            0
+        } else {
+            inner_inplace_scratch
+        }
+    }
 }
 
 /// The Scalar FFT planner creates new FFT algorithm instances using non-SIMD algorithms.
@@ -226,55 +992,218 @@ impl Recipe {
 /// safe to drop the planner after creating Fft instances.
 pub struct FftPlannerScalar<T: FftNum> {
     algorithm_cache: FftCache<T>,
-    recipe_cache: HashMap<usize, Rc<Recipe>>,
+    recipe_cache: HashMap<usize, Arc<Recipe>>,
+    options: PlannerOptions,
 }
 
 impl<T: FftNum> FftPlannerScalar<T> {
     /// Creates a new `FftPlannerScalar` instance.
     pub fn new() -> Self {
+        Self::with_options(PlannerOptions::new())
+    }
+
+    /// Creates a new `FftPlannerScalar` instance, which chooses algorithms according to `options`
+    ///
+    /// # Panics
+    /// Panics if the options set `min_radix4_bits` larger than `max_radix4_bits`.
+    pub fn with_options(options: PlannerOptions) -> Self {
+        options.validate();
         Self {
             algorithm_cache: FftCache::new(),
             recipe_cache: HashMap::new(),
+            options,
         }
     }
 
-    /// Returns a `Fft` instance which computes FFTs of size `len`.
+    /// Returns the estimated cost of the FFT that this planner creates for `len`, according to the [`cost_model`](crate::PlannerOptions::cost_model)
+    /// in this planner's options, or the default [`CostModel`](crate::CostModel) if the options don't have one.
+    ///
+    /// Comparing the estimates to benchmarks of the planned FFTs shows how to calibrate a cost model for the target machine.
+    pub fn estimated_cost(&mut self, len: usize) -> f64 {
+        let recipe = self.design_fft_for_len(len);
+        match self.options.cost_model {
+            Some(ref cost_model) => cost_model.recipe_cost(&recipe),
+            None => CostModel::new().recipe_cost(&recipe),
+        }
+    }
+
+    /// Returns an estimate of the operations, memory passes, and scratch of the FFT that this planner creates for `len`, without creating it.
+    ///
+    /// The estimate is computed from the algorithms that the planner chooses for `len`, and it's the same for both directions.
+    /// Its cost comes from the same cost model as [`estimated_cost`](crate::FftPlannerScalar::estimated_cost).
+    pub fn estimate(&mut self, len: usize) -> FftEstimate {
+        let recipe = self.design_fft_for_len(len);
+        self.options.estimate_recipe(&recipe)
+    }
+
+    /// Returns the smallest FFT size that's at least `min_len`, and that this planner computes especially fast: one whose prime factors are all 11 or smaller.
+    ///
+    /// # Panics
+    /// Panics if no such size fits in a `usize`.
+    pub fn next_fast_len(&self, min_len: usize) -> usize {
+        next_smooth_number(min_len, &FAST_ODD_PRIMES)
+            .unwrap_or_else(|| panic!("No fast FFT size of at least {} fits in a usize", min_len))
+    }
+
+    /// Returns the largest FFT size that's at most `max_len`, and that this planner computes especially fast: one whose prime factors are all 11 or smaller.
+    /// If `max_len` is zero, returns zero.
+    pub fn prev_fast_len(&self, max_len: usize) -> usize {
+        prev_smooth_number(max_len, &FAST_ODD_PRIMES)
+    }
+
+    /// Returns the options that this planner uses to choose algorithms
+    pub fn options(&self) -> &PlannerOptions {
+        &self.options
+    }
+
+    /// Returns a `Fft` instance which computes FFTs of size `len`.
+    ///
+    /// If the provided `direction` is `FftDirection::Forward`, the returned instance will compute forward FFTs. If it's `FftDirection::Inverse`, it will compute inverse FFTs.
+    ///
+    /// If this is called multiple times, the planner will attempt to re-use internal data between calls, reducing memory usage and FFT initialization time.
+    pub fn plan_fft(&mut self, len: usize, direction: FftDirection) -> Arc<dyn Fft<T>> {
+        // Step 1: Create a "recipe" for this FFT, which will tell us exactly which combination of algorithms to use
+        let recipe = self.design_fft_for_len(len);
+
+        // Step 2: Use our recipe to construct a Fft trait object
+        self.build_fft(&recipe, direction)
+    }
+
+    /// Returns a `Fft` instance which computes forward FFTs of size `len`
+    ///
+    /// If this is called multiple times, the planner will attempt to re-use internal data between calls, reducing memory usage and FFT initialization time.
+    pub fn plan_fft_forward(&mut self, len: usize) -> Arc<dyn Fft<T>> {
+        self.plan_fft(len, FftDirection::Forward)
+    }
+
+    /// Returns a `Fft` instance which computes inverse FFTs of size `len`
+    ///
+    /// If this is called multiple times, the planner will attempt to re-use internal data between calls, reducing memory usage and FFT initialization time.
+    pub fn plan_fft_inverse(&mut self, len: usize) -> Arc<dyn Fft<T>> {
+        self.plan_fft(len, FftDirection::Inverse)
+    }
+
+    /// Returns a `OutputPrunedFft` instance which computes FFTs of size `len`, but only guarantees the outputs whose element of `output_mask` is true.
+    ///
+    /// The planner designs the same FFT that `plan_fft` would, then prunes every `Radix4`, `MixedRadix`, and `GoodThomasAlgorithm` step in it, skipping butterflies
+    /// whose results don't reach any selected output. Other steps compute every output.
+    ///
+    /// # Panics
+    /// Panics if `output_mask.len() != len`.
+    pub fn plan_fft_output_pruned(
+        &mut self,
+        len: usize,
+        direction: FftDirection,
+        output_mask: &[bool],
+    ) -> Arc<dyn OutputPrunedFft<T>> {
+        assert_output_mask_len(len, output_mask);
+        let recipe = self.design_fft_for_len(len);
+        self.build_output_pruned_fft(&recipe, direction, output_mask)
+    }
+
+    /// Returns a `OutputPrunedFft` instance which computes FFTs of size `len`, but only guarantees the outputs in `output_range`.
+    ///
+    /// See [`plan_fft_output_pruned`](crate::FftPlannerScalar::plan_fft_output_pruned) for details.
+    ///
+    /// # Panics
+    /// Panics if `output_range.end > len`.
+    pub fn plan_fft_output_range(
+        &mut self,
+        len: usize,
+        direction: FftDirection,
+        output_range: Range<usize>,
+    ) -> Arc<dyn OutputPrunedFft<T>> {
+        self.plan_fft_output_pruned(len, direction, &range_to_mask(len, output_range))
+    }
+
+    /// Returns a `Fft` instance which computes FFTs of size `len`, for inputs whose first `nonzero_len` elements are the only non-zero elements.
     ///
-    /// If the provided `direction` is `FftDirection::Forward`, the returned instance will compute forward FFTs. If it's `FftDirection::Inverse`, it will compute inverse FFTs.
+    /// The planner designs the same FFT that `plan_fft` would, then prunes every `Radix4`, `MixedRadix`, and `GoodThomasAlgorithm` step in it, skipping the
+    /// first passes over inputs that are known to be zero. Other steps process every input, so the elements after the first `nonzero_len` must be zero.
     ///
-    /// If this is called multiple times, the planner will attempt to re-use internal data between calls, reducing memory usage and FFT initialization time.
-    pub fn plan_fft(&mut self, len: usize, direction: FftDirection) -> Arc<dyn Fft<T>> {
-        // Step 1: Create a "recipe" for this FFT, which will tell us exactly which combination of algorithms to use
+    /// # Panics
+    /// Panics if `nonzero_len > len`.
+    pub fn plan_fft_input_pruned(
+        &mut self,
+        len: usize,
+        direction: FftDirection,
+        nonzero_len: usize,
+    ) -> Arc<dyn Fft<T>> {
+        assert_nonzero_len(len, nonzero_len);
         let recipe = self.design_fft_for_len(len);
+        self.build_input_pruned_fft(&recipe, direction, nonzero_len)
+    }
 
-        // Step 2: Use our recipe to construct a Fft trait object
-        self.build_fft(&recipe, direction)
+    /// Sets the policy that limits which FFT instances this planner keeps in its cache. If the cache doesn't fit the new policy,
+    /// the least recently used instances are discarded immediately.
+    ///
+    /// FFT instances that have already been returned by the planner stay valid when they're discarded from the cache.
+    pub fn set_cache_policy(&mut self, policy: CachePolicy) {
+        if self.algorithm_cache.set_policy(policy) {
+            self.prune_recipe_cache();
+        }
     }
 
-    /// Returns a `Fft` instance which computes forward FFTs of size `len`
+    /// Returns the policy that limits which FFT instances this planner keeps in its cache
+    pub fn cache_policy(&self) -> CachePolicy {
+        self.algorithm_cache.policy()
+    }
+
+    /// Discards every FFT instance and every plan from this planner's caches.
     ///
-    /// If this is called multiple times, the planner will attempt to re-use internal data between calls, reducing memory usage and FFT initialization time.
-    pub fn plan_fft_forward(&mut self, len: usize) -> Arc<dyn Fft<T>> {
-        self.plan_fft(len, FftDirection::Forward)
+    /// FFT instances that have already been returned by the planner stay valid.
+    pub fn clear(&mut self) {
+        self.algorithm_cache.clear();
+        self.recipe_cache.clear();
     }
 
-    /// Returns a `Fft` instance which computes inverse FFTs of size `len`
+    /// Discards the cached FFT instances of size `len`, in both directions, from this planner's caches.
     ///
-    /// If this is called multiple times, the planner will attempt to re-use internal data between calls, reducing memory usage and FFT initialization time.
-    pub fn plan_fft_inverse(&mut self, len: usize) -> Arc<dyn Fft<T>> {
-        self.plan_fft(len, FftDirection::Inverse)
+    /// Instances of other sizes that use a FFT of size `len` internally keep their own reference to it, so they aren't affected.
+    pub fn evict(&mut self, len: usize) {
+        self.algorithm_cache.evict(len);
+        self.recipe_cache.remove(&len);
+    }
+
+    /// Returns the number of heap bytes owned by the FFT instances in this planner's cache.
+    ///
+    /// This is the quantity limited by [`CachePolicy::MaxBytes`](crate::CachePolicy::MaxBytes). Each instance counts only the data returned by [`Fft::own_heap_bytes`](crate::Fft::own_heap_bytes),
+    /// so an inner FFT shared by several cached instances is only counted once.
+    pub fn cache_memory_bytes(&self) -> usize {
+        self.algorithm_cache.memory_bytes()
+    }
+
+    /// Registers a custom FFT instance, which the planner will use whenever it needs a FFT of the same size and direction.
+    ///
+    /// The planner returns `fft` directly when asked for its size, and treats its size as a building block for larger sizes: when a
+    /// registered size divides the requested size, the planner splits the FFT so that the registered instance computes one of the factors.
+    /// Register both directions if you plan both. Registered instances are never discarded from the cache, and replace any
+    /// instance of the same size and direction that the cache already has.
+    ///
+    /// FFT instances that were planned before registering won't use the registered instance, so register custom FFTs before planning.
+    pub fn register(&mut self, fft: Arc<dyn Fft<T>>) {
+        self.algorithm_cache.register(&fft);
+        // Designs made before now may be able to use the new building block, so start over
+        self.recipe_cache.clear();
+    }
+
+    // Recipes are cheap, but there's one per cached size, so when instances are discarded from the cache, discard their recipes too
+    fn prune_recipe_cache(&mut self) {
+        let algorithm_cache = &self.algorithm_cache;
+        self.recipe_cache
+            .retain(|len, _| algorithm_cache.contains_len(*len));
     }
 
     // Make a recipe for a length
-    fn design_fft_for_len(&mut self, len: usize) -> Rc<Recipe> {
+    fn design_fft_for_len(&mut self, len: usize) -> Arc<Recipe> {
         if len < 2 {
-            Rc::new(Recipe::Dft(len))
+            Arc::new(Recipe::Dft(len))
         } else if let Some(recipe) = self.recipe_cache.get(&len) {
-            Rc::clone(&recipe)
+            Arc::clone(&recipe)
         } else {
             let factors = PrimeFactors::compute(len);
             let recipe = self.design_fft_with_factors(len, factors);
-            self.recipe_cache.insert(len, Rc::clone(&recipe));
+            self.recipe_cache.insert(len, Arc::clone(&recipe));
             recipe
         }
     }
@@ -286,7 +1215,9 @@ impl<T: FftNum> FftPlannerScalar<T> {
             instance
         } else {
             let fft = self.build_new_fft(recipe, direction);
-            self.algorithm_cache.insert(&fft);
+            if self.algorithm_cache.insert(&fft) {
+                self.prune_recipe_cache();
+            }
             fft
         }
     }
@@ -355,15 +1286,116 @@ impl<T: FftNum> FftPlannerScalar<T> {
         }
     }
 
-    fn design_fft_with_factors(&mut self, len: usize, factors: PrimeFactors) -> Rc<Recipe> {
+    // Create an output-pruned fft from a recipe. Pruned instances depend on the mask, so they aren't cached, but their unpruned parts are
+    fn build_output_pruned_fft(
+        &mut self,
+        recipe: &Recipe,
+        direction: FftDirection,
+        output_mask: &[bool],
+    ) -> Arc<dyn OutputPrunedFft<T>> {
+        if output_mask.iter().all(|&selected| selected) {
+            return Arc::new(FullOutput::new(self.build_fft(recipe, direction)));
+        }
+
+        match recipe {
+            Recipe::Radix4(len) => Arc::new(OutputPrunedRadix4::new(*len, direction, output_mask)),
+            // A good-thomas step computes the same FFT as a mixed radix step with the same inner FFTs, so prune it as one
+            Recipe::MixedRadix {
+                left_fft,
+                right_fft,
+            }
+            | Recipe::GoodThomasAlgorithm {
+                left_fft,
+                right_fft,
+            } => {
+                // Output k of a mixed radix FFT is output k / height of a width FFT, computed from output k % height of every height FFT
+                let width = left_fft.len();
+                let height = right_fft.len();
+                let mut width_mask = vec![false; width];
+                let mut height_mask = vec![false; height];
+                for (k, _) in output_mask
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, &selected)| selected)
+                {
+                    width_mask[k / height] = true;
+                    height_mask[k % height] = true;
+                }
+
+                if width_mask
+                    .iter()
+                    .chain(height_mask.iter())
+                    .all(|&selected| selected)
+                {
+                    Arc::new(FullOutput::new(self.build_fft(recipe, direction)))
+                } else {
+                    let left_fft = self.build_output_pruned_fft(left_fft, direction, &width_mask);
+                    let right_fft =
+                        self.build_output_pruned_fft(right_fft, direction, &height_mask);
+                    Arc::new(OutputPrunedMixedRadix::new(left_fft, right_fft))
+                }
+            }
+            _ => Arc::new(FullOutput::new(self.build_fft(recipe, direction))),
+        }
+    }
+
+    // Create an input-pruned fft from a recipe. Pruned instances depend on nonzero_len, so they aren't cached, but their unpruned parts are
+    fn build_input_pruned_fft(
+        &mut self,
+        recipe: &Recipe,
+        direction: FftDirection,
+        nonzero_len: usize,
+    ) -> Arc<dyn Fft<T>> {
+        if nonzero_len == recipe.len() {
+            return self.build_fft(recipe, direction);
+        }
+
+        match recipe {
+            Recipe::Radix4(len) => {
+                Arc::new(InputPrunedRadix4::new(*len, direction, nonzero_len)) as Arc<dyn Fft<T>>
+            }
+            // A good-thomas step computes the same FFT as a mixed radix step with the same inner FFTs, so prune it as one
+            Recipe::MixedRadix {
+                left_fft,
+                right_fft,
+            }
+            | Recipe::GoodThomasAlgorithm {
+                left_fft,
+                right_fft,
+            } => {
+                // Input n of a mixed radix FFT is input n / width of the height FFT in column n % width,
+                // so each height FFT has at most ceil(nonzero_len / width) non-zero inputs
+                let width = left_fft.len();
+                let height_nonzero_len = div_ceil(nonzero_len, width);
+
+                let left_fft = self.build_fft(left_fft, direction);
+                let right_fft =
+                    self.build_input_pruned_fft(right_fft, direction, height_nonzero_len);
+                Arc::new(InputPrunedMixedRadix::new(left_fft, right_fft, nonzero_len))
+                    as Arc<dyn Fft<T>>
+            }
+            _ => self.build_fft(recipe, direction),
+        }
+    }
+
+    fn design_fft_with_factors(&mut self, len: usize, factors: PrimeFactors) -> Arc<Recipe> {
         if let Some(fft_instance) = self.design_butterfly_algorithm(len) {
             fft_instance
+        } else if let Some(registered_len) = self.largest_registered_factor(len) {
+            // Build around the user's FFT, like the AVX planner builds around cached FFTs
+            let registered_factors = PrimeFactors::compute(registered_len);
+            let other_factors = PrimeFactors::compute(len / registered_len);
+            self.design_mixed_radix(registered_factors, other_factors)
+        } else if self.options.cost_model.is_some() {
+            self.design_cheapest(len, factors)
         } else if factors.is_prime() {
             self.design_prime(len)
-        } else if len.trailing_zeros() <= MAX_RADIX4_BITS && len.trailing_zeros() >= MIN_RADIX4_BITS
+        } else if self.options.is_allowed(PlannerAlgorithm::Radix4)
+            && len.trailing_zeros() <= self.options.max_radix4_bits
+            && len.trailing_zeros() >= self.options.min_radix4_bits
         {
             if len.is_power_of_two() {
-                Rc::new(Recipe::Radix4(len))
+                Arc::new(Recipe::Radix4(len))
             } else {
                 let non_power_of_two = factors
                     .remove_factors(PrimeFactor {
@@ -384,90 +1416,291 @@ impl<T: FftNum> FftPlannerScalar<T> {
         &mut self,
         left_factors: PrimeFactors,
         right_factors: PrimeFactors,
-    ) -> Rc<Recipe> {
+    ) -> Arc<Recipe> {
         let left_len = left_factors.get_product();
         let right_len = right_factors.get_product();
 
+        let mixed_radix_allowed = self.options.is_allowed(PlannerAlgorithm::MixedRadix);
+        let good_thomas_allowed =
+            self.options.is_allowed(PlannerAlgorithm::GoodThomas) && gcd(left_len, right_len) == 1;
+        if !mixed_radix_allowed && !good_thomas_allowed {
+            return Arc::new(Recipe::Dft(left_len * right_len));
+        }
+
         //neither size is a butterfly, so go with the normal algorithm
         let left_fft = self.design_fft_with_factors(left_len, left_factors);
         let right_fft = self.design_fft_with_factors(right_len, right_factors);
 
         //if both left_len and right_len are small, use algorithms optimized for small FFTs
-        if left_len < 31 && right_len < 31 {
+        //they require inner FFTs with little scratch, which registered FFTs don't necessarily satisfy
+        let has_registered = self.algorithm_cache.is_registered(left_len)
+            || self.algorithm_cache.is_registered(right_len);
+
+        // good-thomas avoids the twiddle factors of mixed radix, which makes it faster for smaller FFTs,
+        // but its index remapping is less cache-friendly, so for larger FFTs mixed radix is faster
+        let prefer_good_thomas = self.options.prefer_good_thomas
+            || left_len * right_len <= self.options.max_good_thomas_len;
+        if left_len < 31 && right_len < 31 && !has_registered {
             // for small FFTs, if gcd is 1, good-thomas is faster
-            if gcd(left_len, right_len) == 1 {
-                Rc::new(Recipe::GoodThomasAlgorithmSmall {
+            if good_thomas_allowed {
+                Arc::new(Recipe::GoodThomasAlgorithmSmall {
                     left_fft,
                     right_fft,
                 })
             } else {
-                Rc::new(Recipe::MixedRadixSmall {
+                Arc::new(Recipe::MixedRadixSmall {
                     left_fft,
                     right_fft,
                 })
             }
+        } else if good_thomas_allowed && mixed_radix_allowed && self.options.limits_scratch() {
+            let mixed_radix = Arc::new(Recipe::MixedRadix {
+                left_fft: Arc::clone(&left_fft),
+                right_fft: Arc::clone(&right_fft),
+            });
+            let good_thomas = Arc::new(Recipe::GoodThomasAlgorithm {
+                left_fft,
+                right_fft,
+            });
+            if prefer_good_thomas {
+                self.choose_by_scratch(vec![good_thomas, mixed_radix])
+            } else {
+                self.choose_by_scratch(vec![mixed_radix, good_thomas])
+            }
+        } else if good_thomas_allowed && (prefer_good_thomas || !mixed_radix_allowed) {
+            Arc::new(Recipe::GoodThomasAlgorithm {
+                left_fft,
+                right_fft,
+            })
         } else {
-            Rc::new(Recipe::MixedRadix {
+            Arc::new(Recipe::MixedRadix {
                 left_fft,
                 right_fft,
             })
         }
     }
 
+    // Try every algorithm that can compute this size, and choose the one with the lowest estimated cost. Inner FFTs are designed with
+    // design_fft_for_len, so the recipe cache memoizes the cheapest design of each inner size, and each size is only searched once
+    fn design_cheapest(&mut self, len: usize, factors: PrimeFactors) -> Arc<Recipe> {
+        let mut candidates = Vec::new();
+        if factors.is_prime() {
+            if self.options.is_allowed(PlannerAlgorithm::Raders) {
+                let inner_fft = self.design_fft_for_len(len - 1);
+                candidates.push(Arc::new(Recipe::RadersAlgorithm { inner_fft }));
+            }
+            if self.options.is_allowed(PlannerAlgorithm::Bluesteins) {
+                // Bluestein's algorithm can use any inner size of at least 2 * len - 1. Try the smallest one of the form 2^n * 3^m for each m,
+                // up to the next power of two
+                let min_inner_len = 2 * len - 1;
+                let max_inner_len = min_inner_len.checked_next_power_of_two().unwrap();
+                let mut power_of_three = 1;
+                while power_of_three <= max_inner_len {
+                    let mut inner_len = power_of_three;
+                    while inner_len < min_inner_len {
+                        inner_len *= 2;
+                    }
+                    if inner_len <= max_inner_len {
+                        let inner_fft = self.design_fft_for_len(inner_len);
+                        candidates.push(Arc::new(Recipe::BluesteinsAlgorithm { len, inner_fft }));
+                    }
+                    power_of_three *= 3;
+                }
+            }
+        } else {
+            if self.options.is_allowed(PlannerAlgorithm::Radix4) && len.is_power_of_two() {
+                candidates.push(Arc::new(Recipe::Radix4(len)));
+            }
+
+            let mixed_radix_allowed = self.options.is_allowed(PlannerAlgorithm::MixedRadix);
+            let good_thomas_allowed = self.options.is_allowed(PlannerAlgorithm::GoodThomas);
+            for left_len in factors.divisors() {
+                let right_len = len / left_len;
+                if left_len == 1 {
+                    continue;
+                } else if left_len > right_len {
+                    // every split with a larger left side is the same as one with a smaller left side
+                    break;
+                }
+                let good_thomas_possible = good_thomas_allowed && gcd(left_len, right_len) == 1;
+                if !mixed_radix_allowed && !good_thomas_possible {
+                    continue;
+                }
+
+                let left_fft = self.design_fft_for_len(left_len);
+                let right_fft = self.design_fft_for_len(right_len);
+                let is_small = left_len < 31
+                    && right_len < 31
+                    && !self.algorithm_cache.is_registered(left_len)
+                    && !self.algorithm_cache.is_registered(right_len);
+                if mixed_radix_allowed {
+                    let left_fft = Arc::clone(&left_fft);
+                    let right_fft = Arc::clone(&right_fft);
+                    candidates.push(Arc::new(if is_small {
+                        Recipe::MixedRadixSmall {
+                            left_fft,
+                            right_fft,
+                        }
+                    } else {
+                        Recipe::MixedRadix {
+                            left_fft,
+                            right_fft,
+                        }
+                    }));
+                }
+                if good_thomas_possible {
+                    candidates.push(Arc::new(if is_small {
+                        Recipe::GoodThomasAlgorithmSmall {
+                            left_fft,
+                            right_fft,
+                        }
+                    } else {
+                        Recipe::GoodThomasAlgorithm {
+                            left_fft,
+                            right_fft,
+                        }
+                    }));
+                }
+            }
+        }
+        // Dft can compute any size, so there's always at least one candidate
+        candidates.push(Arc::new(Recipe::Dft(len)));
+
+        // Order the candidates from cheapest to most expensive. Costs are never NaN, because the cost model rejects NaN coefficients
+        let cost_model = self.options.cost_model.as_ref().unwrap();
+        let mut costed_candidates: Vec<(f64, Arc<Recipe>)> = candidates
+            .into_iter()
+            .map(|recipe| (cost_model.recipe_cost(&recipe), recipe))
+            .collect();
+        costed_candidates.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        let candidates: Vec<Arc<Recipe>> = costed_candidates
+            .into_iter()
+            .map(|(_, recipe)| recipe)
+            .collect();
+
+        if self.options.limits_scratch() {
+            self.choose_by_scratch(candidates)
+        } else {
+            candidates.into_iter().next().unwrap()
+        }
+    }
+
+    // Returns the largest registered size that's a proper factor of len, if there is one. If len itself is registered, we still design
+    // a recipe for it, in case it's only registered in one direction, but the registered instance will be used when building it
+    fn largest_registered_factor(&self, len: usize) -> Option<usize> {
+        if self.algorithm_cache.is_registered(len) {
+            return None;
+        }
+        self.algorithm_cache
+            .registered_lens()
+            .filter(|&registered_len| {
+                registered_len > 1 && Integer::is_multiple_of(&len, &registered_len)
+            })
+            .max()
+    }
+
     // Returns Some(instance) if we have a butterfly available for this size. Returns None if there is no butterfly available for this size
-    fn design_butterfly_algorithm(&mut self, len: usize) -> Option<Rc<Recipe>> {
+    fn design_butterfly_algorithm(&mut self, len: usize) -> Option<Arc<Recipe>> {
         match len {
-            2 => Some(Rc::new(Recipe::Butterfly2)),
-            3 => Some(Rc::new(Recipe::Butterfly3)),
-            4 => Some(Rc::new(Recipe::Butterfly4)),
-            5 => Some(Rc::new(Recipe::Butterfly5)),
-            6 => Some(Rc::new(Recipe::Butterfly6)),
-            7 => Some(Rc::new(Recipe::Butterfly7)),
-            8 => Some(Rc::new(Recipe::Butterfly8)),
-            11 => Some(Rc::new(Recipe::Butterfly11)),
-            13 => Some(Rc::new(Recipe::Butterfly13)),
-            16 => Some(Rc::new(Recipe::Butterfly16)),
-            17 => Some(Rc::new(Recipe::Butterfly17)),
-            19 => Some(Rc::new(Recipe::Butterfly19)),
-            23 => Some(Rc::new(Recipe::Butterfly23)),
-            29 => Some(Rc::new(Recipe::Butterfly29)),
-            31 => Some(Rc::new(Recipe::Butterfly31)),
-            32 => Some(Rc::new(Recipe::Butterfly32)),
+            2 => Some(Arc::new(Recipe::Butterfly2)),
+            3 => Some(Arc::new(Recipe::Butterfly3)),
+            4 => Some(Arc::new(Recipe::Butterfly4)),
+            5 => Some(Arc::new(Recipe::Butterfly5)),
+            6 => Some(Arc::new(Recipe::Butterfly6)),
+            7 => Some(Arc::new(Recipe::Butterfly7)),
+            8 => Some(Arc::new(Recipe::Butterfly8)),
+            11 => Some(Arc::new(Recipe::Butterfly11)),
+            13 => Some(Arc::new(Recipe::Butterfly13)),
+            16 => Some(Arc::new(Recipe::Butterfly16)),
+            17 => Some(Arc::new(Recipe::Butterfly17)),
+            19 => Some(Arc::new(Recipe::Butterfly19)),
+            23 => Some(Arc::new(Recipe::Butterfly23)),
+            29 => Some(Arc::new(Recipe::Butterfly29)),
+            31 => Some(Arc::new(Recipe::Butterfly31)),
+            32 => Some(Arc::new(Recipe::Butterfly32)),
             _ => None,
         }
     }
 
-    fn design_prime(&mut self, len: usize) -> Rc<Recipe> {
+    fn design_prime(&mut self, len: usize) -> Arc<Recipe> {
         let inner_fft_len_rader = len - 1;
         let raders_factors = PrimeFactors::compute(inner_fft_len_rader);
         // If any of the prime factors is too large, Rader's gets slow and Bluestein's is the better choice
-        if raders_factors
+        let raders_is_slow = raders_factors
             .get_other_factors()
             .iter()
-            .any(|val| val.value > MAX_RADER_PRIME_FACTOR)
+            .any(|val| val.value > self.options.max_rader_prime_factor);
+        let raders_allowed = self.options.is_allowed(PlannerAlgorithm::Raders);
+        let bluesteins_allowed = self.options.is_allowed(PlannerAlgorithm::Bluesteins);
+
+        if raders_allowed && bluesteins_allowed && self.options.limits_scratch() {
+            let raders = self.design_raders(inner_fft_len_rader, raders_factors);
+            let bluesteins = self.design_bluesteins(len);
+            if raders_is_slow {
+                self.choose_by_scratch(vec![bluesteins, raders])
+            } else {
+                self.choose_by_scratch(vec![raders, bluesteins])
+            }
+        } else if raders_allowed && (!raders_is_slow || !bluesteins_allowed) {
+            self.design_raders(inner_fft_len_rader, raders_factors)
+        } else if bluesteins_allowed {
+            self.design_bluesteins(len)
+        } else {
+            Arc::new(Recipe::Dft(len))
+        }
+    }
+
+    fn design_raders(&mut self, inner_fft_len: usize, inner_factors: PrimeFactors) -> Arc<Recipe> {
+        let inner_fft = self.design_fft_with_factors(inner_fft_len, inner_factors);
+        Arc::new(Recipe::RadersAlgorithm { inner_fft })
+    }
+
+    fn design_bluesteins(&mut self, len: usize) -> Arc<Recipe> {
+        let inner_fft_len_pow2 = (2 * len - 1).checked_next_power_of_two().unwrap();
+        // for long ffts a mixed radix inner fft is faster than a longer radix4
+        let min_inner_len = 2 * len - 1;
+        let mixed_radix_len = 3 * inner_fft_len_pow2 / 4;
+        let inner_fft = if mixed_radix_len >= min_inner_len
+            && len >= self.options.min_bluestein_mixed_radix_len
+            && self.options.is_allowed(PlannerAlgorithm::MixedRadix)
         {
-            let inner_fft_len_pow2 = (2 * len - 1).checked_next_power_of_two().unwrap();
-            // for long ffts a mixed radix inner fft is faster than a longer radix4
-            let min_inner_len = 2 * len - 1;
-            let mixed_radix_len = 3 * inner_fft_len_pow2 / 4;
-            let inner_fft =
-                if mixed_radix_len >= min_inner_len && len >= MIN_BLUESTEIN_MIXED_RADIX_LEN {
-                    let mixed_radix_factors = PrimeFactors::compute(mixed_radix_len);
-                    self.design_fft_with_factors(mixed_radix_len, mixed_radix_factors)
-                } else {
-                    Rc::new(Recipe::Radix4(inner_fft_len_pow2))
-                };
-            Rc::new(Recipe::BluesteinsAlgorithm { len, inner_fft })
+            let mixed_radix_factors = PrimeFactors::compute(mixed_radix_len);
+            self.design_fft_with_factors(mixed_radix_len, mixed_radix_factors)
+        } else if self.options.is_allowed(PlannerAlgorithm::Radix4) {
+            Arc::new(Recipe::Radix4(inner_fft_len_pow2))
         } else {
-            let inner_fft = self.design_fft_with_factors(inner_fft_len_rader, raders_factors);
-            Rc::new(Recipe::RadersAlgorithm { inner_fft })
+            let pow2_factors = PrimeFactors::compute(inner_fft_len_pow2);
+            self.design_fft_with_factors(inner_fft_len_pow2, pow2_factors)
+        };
+        Arc::new(Recipe::BluesteinsAlgorithm { len, inner_fft })
+    }
+
+    // Candidates are ordered from fastest to slowest. Choose the first one that fits within the scratch budget,
+    // or if none of them fit, or if we're minimizing scratch, the one that needs the least scratch
+    fn choose_by_scratch(&self, candidates: Vec<Arc<Recipe>>) -> Arc<Recipe> {
+        if !self.options.minimize_scratch {
+            if let Some(max_len) = self.options.max_inplace_scratch_len {
+                if let Some(recipe) = candidates
+                    .iter()
+                    .find(|recipe| recipe.inplace_scratch_len() <= max_len)
+                {
+                    return Arc::clone(recipe);
+                }
+            }
         }
+        candidates
+            .into_iter()
+            .min_by_key(|recipe| recipe.inplace_scratch_len())
+            .unwrap()
     }
 }
 
 #[cfg(test)]
 mod unit_tests {
     use super::*;
+    use crate::test_utils::{
+        check_fft_algorithm, check_input_pruned_fft_algorithm, check_output_pruned_fft_algorithm,
+    };
 
     fn is_mixedradix(plan: &Recipe) -> bool {
         match plan {
@@ -490,6 +1723,13 @@ mod unit_tests {
         }
     }
 
+    fn is_goodthomas(plan: &Recipe) -> bool {
+        match plan {
+            &Recipe::GoodThomasAlgorithm { .. } => true,
+            _ => false,
+        }
+    }
+
     fn is_raders(plan: &Recipe) -> bool {
         match plan {
             &Recipe::RadersAlgorithm { .. } => true,
@@ -611,6 +1851,28 @@ mod unit_tests {
         }
     }
 
+    #[test]
+    fn test_plan_scalar_goodthomas() {
+        // Coprime factors that are too big for the small algorithms should use good-thomas, as long as the FFT isn't too large
+        let mut planner = FftPlannerScalar::<f64>::new();
+        for &len in &[32 * 3, 64 * 9, 64 * 27, 1024 * 3] {
+            let plan = planner.design_fft_for_len(len);
+            assert!(
+                is_goodthomas(&plan),
+                "Expected GoodThomasAlgorithm, got {:?}",
+                plan
+            );
+            assert_eq!(plan.len(), len, "Recipe reports wrong length");
+
+            let fft = planner.plan_fft_forward(len);
+            check_fft_algorithm(&*fft, len, FftDirection::Forward);
+        }
+        for &len in &[1024 * 7, 64 * 81] {
+            let plan = planner.design_fft_for_len(len);
+            assert!(is_mixedradix(&plan), "Expected MixedRadix, got {:?}", plan);
+        }
+    }
+
     #[test]
     fn test_plan_scalar_bluestein_vs_rader() {
         let difficultprimes: [usize; 11] = [59, 83, 107, 149, 167, 173, 179, 359, 719, 1439, 2879];
@@ -636,6 +1898,272 @@ mod unit_tests {
         }
     }
 
+    #[test]
+    fn test_plan_scalar_options() {
+        // With bluestein's denied, even difficult primes use rader's, and vice versa
+        let options = PlannerOptions::new().deny(PlannerAlgorithm::Bluesteins);
+        let mut planner = FftPlannerScalar::<f64>::with_options(options);
+        let plan = planner.design_fft_for_len(59);
+        assert!(is_raders(&plan), "Expected RadersAlgorithm, got {:?}", plan);
+
+        let options = PlannerOptions::new().deny(PlannerAlgorithm::Raders);
+        let mut planner = FftPlannerScalar::<f64>::with_options(options);
+        let plan = planner.design_fft_for_len(53);
+        assert!(
+            is_bluesteins(&plan),
+            "Expected BluesteinsAlgorithm, got {:?}",
+            plan
+        );
+
+        // Large coprime factors use good-thomas only if it's preferred
+        let options = PlannerOptions::new().prefer_good_thomas(true);
+        let mut planner = FftPlannerScalar::<f64>::with_options(options);
+        let plan = planner.design_fft_for_len(64 * 81);
+        assert!(
+            is_goodthomas(&plan),
+            "Expected GoodThomasAlgorithm, got {:?}",
+            plan
+        );
+        let plan = FftPlannerScalar::<f64>::new().design_fft_for_len(64 * 81);
+        assert!(is_mixedradix(&plan), "Expected MixedRadix, got {:?}", plan);
+
+        // Raising the radix4 threshold should split powers of two with mixed radix instead
+        let options = PlannerOptions::new().min_radix4_bits(12);
+        let mut planner = FftPlannerScalar::<f64>::with_options(options);
+        let plan = planner.design_fft_for_len(1024);
+        assert!(is_mixedradix(&plan), "Expected MixedRadix, got {:?}", plan);
+
+        // Every combination of options should still compute correct FFTs
+        let option_sets = [
+            PlannerOptions::new().deny(PlannerAlgorithm::Bluesteins),
+            PlannerOptions::new().deny(PlannerAlgorithm::Raders),
+            PlannerOptions::new()
+                .prefer_good_thomas(true)
+                .max_radix4_bits(6),
+            PlannerOptions::new().allow_only(&[PlannerAlgorithm::GoodThomas]),
+            PlannerOptions::new().allow_only(&[]),
+        ];
+        for options in option_sets.iter() {
+            let mut planner = FftPlannerScalar::<f64>::with_options(options.clone());
+            for &len in &[59, 64 * 81, 1024, 1201, 1234] {
+                let fft = planner.plan_fft_forward(len);
+                check_fft_algorithm(&*fft, len, FftDirection::Forward);
+            }
+        }
+    }
+
+    #[test]
+    fn test_plan_scalar_recipe_scratch() {
+        // The scratch that a recipe predicts must match the scratch of the FFT built from it
+        let option_sets = [
+            PlannerOptions::new(),
+            PlannerOptions::new().prefer_good_thomas(true),
+            PlannerOptions::new().deny(PlannerAlgorithm::Radix4),
+            PlannerOptions::new().cost_model(CostModel::new()),
+        ];
+        for options in option_sets.iter() {
+            let mut planner = FftPlannerScalar::<f64>::with_options(options.clone());
+            for len in (1..200).chain(
+                [1200, 1201, 1234, 2879, 4096, 5183, 5184, 9973]
+                    .iter()
+                    .cloned(),
+            ) {
+                let recipe = planner.design_fft_for_len(len);
+                let fft = planner.plan_fft_forward(len);
+                assert_eq!(
+                    recipe.inplace_scratch_len(),
+                    fft.get_inplace_scratch_len(),
+                    "Wrong in-place scratch for {:?}",
+                    recipe
+                );
+                assert_eq!(
+                    recipe.outofplace_scratch_len(),
+                    fft.get_outofplace_scratch_len(),
+                    "Wrong out-of-place scratch for {:?}",
+                    recipe
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_plan_scalar_scratch_limits() {
+        let default_scratch = FftPlannerScalar::<f64>::new()
+            .plan_fft_forward(59)
+            .get_inplace_scratch_len();
+
+        // Minimizing scratch should choose rader's over bluestein's, even for difficult primes
+        let mut planner =
+            FftPlannerScalar::<f64>::with_options(PlannerOptions::new().minimize_scratch(true));
+        let plan = planner.design_fft_for_len(59);
+        assert!(is_raders(&plan), "Expected RadersAlgorithm, got {:?}", plan);
+        assert!(plan.inplace_scratch_len() < default_scratch);
+
+        // A generous budget shouldn't change anything, and a tight one should behave like minimizing
+        let options = PlannerOptions::new().max_inplace_scratch_len(default_scratch);
+        let plan = FftPlannerScalar::<f64>::with_options(options).design_fft_for_len(59);
+        assert!(
+            is_bluesteins(&plan),
+            "Expected BluesteinsAlgorithm, got {:?}",
+            plan
+        );
+        let options = PlannerOptions::new().max_inplace_scratch_len(default_scratch - 1);
+        let plan = FftPlannerScalar::<f64>::with_options(options).design_fft_for_len(59);
+        assert!(is_raders(&plan), "Expected RadersAlgorithm, got {:?}", plan);
+
+        // Sizes with difficult primes as factors should need less scratch too, and still be correct
+        for &len in &[59 * 64, 2879, 5183] {
+            let fft = planner.plan_fft_forward(len);
+            let default_fft = FftPlannerScalar::<f64>::new().plan_fft_forward(len);
+            assert!(fft.get_inplace_scratch_len() <= default_fft.get_inplace_scratch_len());
+            check_fft_algorithm(&*fft, len, FftDirection::Forward);
+        }
+    }
+
+    #[test]
+    fn test_plan_scalar_cost_model() {
+        let cost_model = CostModel::new();
+        let mut planner = FftPlannerScalar::<f64>::with_options(
+            PlannerOptions::new().cost_model(cost_model.clone()),
+        );
+        let mut heuristic_planner = FftPlannerScalar::<f64>::new();
+        for len in (1..200).chain([1200, 1201, 2048, 5184, 9973].iter().cloned()) {
+            // The search considers every design that the heuristics can produce, so it should never be more expensive
+            let heuristic_cost = cost_model.recipe_cost(&heuristic_planner.design_fft_for_len(len));
+            let cost = planner.estimated_cost(len);
+            assert!(
+                cost <= heuristic_cost,
+                "Searched cost {} is above heuristic cost {} for len {}",
+                cost,
+                heuristic_cost,
+                len
+            );
+
+            let fft = planner.plan_fft_forward(len);
+            check_fft_algorithm(&*fft, len, FftDirection::Forward);
+        }
+
+        // If the naive DFT is free, it should be chosen for everything except butterflies
+        let options = PlannerOptions::new().cost_model(CostModel::new().dft(0.0));
+        let mut planner = FftPlannerScalar::<f64>::with_options(options);
+        assert_eq!(*planner.design_fft_for_len(1200), Recipe::Dft(1200));
+        assert_eq!(*planner.design_fft_for_len(32), Recipe::Butterfly32);
+
+        // The search should honor the allowed algorithms and the scratch limits
+        let options = PlannerOptions::new()
+            .cost_model(CostModel::new())
+            .allow_only(&[PlannerAlgorithm::GoodThomas]);
+        let mut planner = FftPlannerScalar::<f64>::with_options(options);
+        let plan = planner.design_fft_for_len(35 * 64);
+        assert!(
+            is_goodthomas(&plan),
+            "Expected GoodThomasAlgorithm, got {:?}",
+            plan
+        );
+        assert_eq!(*planner.design_fft_for_len(64), Recipe::Dft(64));
+
+        let options = PlannerOptions::new()
+            .cost_model(CostModel::new())
+            .minimize_scratch(true);
+        let mut planner = FftPlannerScalar::<f64>::with_options(options);
+        let plan = planner.design_fft_for_len(59);
+        assert!(is_raders(&plan), "Expected RadersAlgorithm, got {:?}", plan);
+    }
+
+    #[test]
+    fn test_scalar_estimate() {
+        let mut planner = FftPlannerScalar::<f64>::new();
+        for &len in &[1, 2, 30, 59, 1024, 1200, 1201, 9973] {
+            let estimate = planner.estimate(len);
+            let fft = planner.plan_fft_forward(len);
+            assert_eq!(estimate.len, len);
+            assert_eq!(estimate.inplace_scratch_len, fft.get_inplace_scratch_len());
+            assert_eq!(
+                estimate.outofplace_scratch_len,
+                fft.get_outofplace_scratch_len()
+            );
+            assert_eq!(estimate.cost, planner.estimated_cost(len));
+            assert!(estimate.memory_passes >= 1.0);
+        }
+
+        // Sizes with large prime factors need far more work than nearby smooth sizes
+        let smooth = planner.estimate(1200);
+        let prime = planner.estimate(1201);
+        assert!(prime.multiplications > 2 * smooth.multiplications);
+        assert!(prime.additions > 2 * smooth.additions);
+
+        // The other planners estimate the design of the planner they chose, whether or not it's AVX
+        let options = PlannerOptions::new().cost_model(CostModel::new());
+        let mut planner = FftPlanner::<f64>::with_options(options.clone());
+        let sync_planner = FftPlannerSync::from_planner(FftPlanner::<f64>::with_options(options));
+        for &len in &[1, 30, 59, 512, 1201, 6000, 9973] {
+            let expected = planner.estimate(len);
+            let fft = planner.plan_fft_forward(len);
+            assert_eq!(expected.len, len);
+            assert_eq!(expected.inplace_scratch_len, fft.get_inplace_scratch_len());
+            assert_eq!(
+                expected.outofplace_scratch_len,
+                fft.get_outofplace_scratch_len()
+            );
+            assert_eq!(planner.estimated_cost(len), expected.cost);
+            assert_eq!(sync_planner.estimate(len), expected);
+            assert_eq!(sync_planner.estimated_cost(len), expected.cost);
+        }
+    }
+
+    #[test]
+    fn test_fast_len() {
+        // 1201 is prime, 1210 = 2 * 5 * 11^2, and 1200 = 2^4 * 3 * 5^2
+        let scalar_planner = FftPlannerScalar::<f64>::new();
+        assert_eq!(scalar_planner.next_fast_len(1201), 1210);
+        assert_eq!(scalar_planner.prev_fast_len(1201), 1200);
+        assert_eq!(scalar_planner.next_fast_len(1200), 1200);
+
+        // With AVX, only factors of 2 and 3 count: 1296 = 2^4 * 3^4, and 1152 = 2^7 * 3^2
+        let planner = FftPlanner::<f64>::new();
+        let sync_planner = FftPlannerSync::<f64>::new();
+        let expected = if let Ok(avx_planner) = FftPlannerAvx::<f64>::new() {
+            assert_eq!(avx_planner.next_fast_len(1201), 1296);
+            assert_eq!(avx_planner.prev_fast_len(1201), 1152);
+            (1296, 1152)
+        } else {
+            (1210, 1200)
+        };
+        assert_eq!(
+            (planner.next_fast_len(1201), planner.prev_fast_len(1201)),
+            expected
+        );
+        assert_eq!(
+            (
+                sync_planner.next_fast_len(1201),
+                sync_planner.prev_fast_len(1201)
+            ),
+            expected
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_planner_options_invalid_radix4_bits() {
+        PlannerOptions::new().max_radix4_bits(0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_planner_options_crossed_radix4_bits() {
+        let options = PlannerOptions::new().min_radix4_bits(20);
+        FftPlannerScalar::<f32>::with_options(options);
+    }
+
+    #[test]
+    fn test_plan_zero_len() {
+        // FFTs of length zero return immediately, rather than panicking or looping forever
+        let mut planner = FftPlanner::<f32>::new();
+        let fft = planner.plan_fft_forward(0);
+        fft.process(&mut []);
+        fft.process_split(&mut [], &mut []);
+    }
+
     #[test]
     fn test_scalar_fft_cache() {
         {
@@ -664,12 +2192,202 @@ mod unit_tests {
         }
     }
 
+    #[test]
+    fn test_sync_planner() {
+        fn assert_send_sync<P: Send + Sync>(_: &P) {}
+
+        let planner = Arc::new(FftPlannerSync::<f32>::new());
+        assert_send_sync(&planner);
+
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let planner = Arc::clone(&planner);
+                std::thread::spawn(move || planner.plan_fft_forward(1234))
+            })
+            .collect();
+        let ffts: Vec<_> = threads
+            .into_iter()
+            .map(|thread| thread.join().unwrap())
+            .collect();
+
+        for fft in &ffts {
+            assert!(
+                Arc::ptr_eq(fft, &ffts[0]),
+                "Threads didn't share the same fft"
+            );
+        }
+        assert!(!Arc::ptr_eq(&planner.plan_fft_inverse(1234), &ffts[0]));
+    }
+
+    #[test]
+    fn test_scalar_cache_policy() {
+        let mut planner = FftPlannerScalar::<f64>::new();
+        assert_eq!(planner.cache_policy(), CachePolicy::Unbounded);
+
+        // Butterflies have no inner FFTs, so each of these plans adds exactly one instance to the cache
+        let fft_a = planner.plan_fft_forward(16);
+        let fft_b = planner.plan_fft_forward(32);
+        planner.set_cache_policy(CachePolicy::MaxEntries(2));
+
+        // Using `a` makes `b` the least recently used instance, so planning a third size should discard `b`
+        planner.plan_fft_forward(16);
+        planner.plan_fft_forward(8);
+        assert_eq!(planner.algorithm_cache.entry_count(), 2);
+        assert!(Arc::ptr_eq(&fft_a, &planner.plan_fft_forward(16)));
+        assert!(!Arc::ptr_eq(&fft_b, &planner.plan_fft_forward(32)));
+
+        // Shrinking the limit discards instances immediately
+        planner.set_cache_policy(CachePolicy::MaxEntries(1));
+        assert_eq!(planner.algorithm_cache.entry_count(), 1);
+
+        // Composite FFTs cache their inner FFTs too, and the recipes of discarded instances are discarded with them
+        planner.set_cache_policy(CachePolicy::MaxBytes(1 << 16));
+        for len in 100..200 {
+            planner.plan_fft_forward(len);
+            assert!(planner.cache_memory_bytes() <= 1 << 16);
+        }
+        for len in planner.recipe_cache.keys() {
+            assert!(planner.algorithm_cache.contains_len(*len));
+        }
+
+        planner.evict(199);
+        assert!(!planner.algorithm_cache.contains_len(199));
+        assert!(!planner.recipe_cache.contains_key(&199));
+
+        planner.clear();
+        assert_eq!(planner.algorithm_cache.entry_count(), 0);
+        assert_eq!(planner.cache_memory_bytes(), 0);
+        assert!(planner.recipe_cache.is_empty());
+    }
+
+    #[test]
+    fn test_scalar_heap_bytes() {
+        use num_complex::Complex;
+        let complex_size = std::mem::size_of::<Complex<f64>>();
+
+        // A mixed radix FFT whose width and height FFTs are the same instance should only count that instance once
+        let dft: Arc<dyn Fft<f64>> = Arc::new(Dft::new(8, FftDirection::Forward));
+        let mixed_radix = MixedRadix::new(Arc::clone(&dft), Arc::clone(&dft));
+        assert_eq!(mixed_radix.own_heap_bytes(), 64 * complex_size);
+
+        let report = mixed_radix.heap_bytes_breakdown();
+        assert_eq!(report.len, 64);
+        assert_eq!(report.inner_ffts.len(), 2);
+        assert_eq!(report.inner_ffts[0].own_bytes, 8 * complex_size);
+        assert!(!report.inner_ffts[0].repeated);
+        assert!(report.inner_ffts[1].repeated);
+        assert_eq!(report.total_bytes, 72 * complex_size);
+        assert_eq!(mixed_radix.heap_bytes(), report.total_bytes);
+
+        // A fresh planner caches every instance in the tree exactly once, so its cache should hold exactly what the tree does
+        for &len in &[1200, 1201, 1234, 4096, 9973] {
+            let mut planner = FftPlannerScalar::<f64>::new();
+            let fft = planner.plan_fft_forward(len);
+            assert!(fft.heap_bytes() > 0);
+            assert_eq!(
+                fft.heap_bytes(),
+                planner.cache_memory_bytes(),
+                "Cached bytes don't match the FFT's heap bytes, len = {}",
+                len
+            );
+        }
+    }
+
+    #[test]
+    fn test_scalar_register() {
+        fn contains_len(recipe: &Recipe, len: usize) -> bool {
+            recipe.len() == len
+                || match recipe {
+                    Recipe::MixedRadix {
+                        left_fft,
+                        right_fft,
+                    }
+                    | Recipe::GoodThomasAlgorithm {
+                        left_fft,
+                        right_fft,
+                    }
+                    | Recipe::MixedRadixSmall {
+                        left_fft,
+                        right_fft,
+                    }
+                    | Recipe::GoodThomasAlgorithmSmall {
+                        left_fft,
+                        right_fft,
+                    } => contains_len(left_fft, len) || contains_len(right_fft, len),
+                    Recipe::RadersAlgorithm { inner_fft }
+                    | Recipe::BluesteinsAlgorithm { inner_fft, .. } => contains_len(inner_fft, len),
+                    _ => false,
+                }
+        }
+
+        let mut planner = FftPlannerScalar::<f64>::new();
+        planner.set_cache_policy(CachePolicy::MaxEntries(0));
+        let custom: Arc<dyn Fft<f64>> = Arc::new(Dft::new(10, FftDirection::Forward));
+        planner.register(Arc::clone(&custom));
+
+        // The registered instance should be returned directly, and survive the cache policy and clearing
+        assert!(Arc::ptr_eq(&custom, &planner.plan_fft_forward(10)));
+        planner.clear();
+        planner.evict(10);
+        assert!(Arc::ptr_eq(&custom, &planner.plan_fft_forward(10)));
+
+        // Sizes that the default planner would split differently should be built around the registered size
+        assert!(!contains_len(
+            &FftPlannerScalar::<f64>::new().design_fft_for_len(1230),
+            10
+        ));
+        let recipe = planner.design_fft_for_len(1230);
+        assert!(
+            contains_len(&recipe, 10),
+            "Expected a FFT of size 10 in {:?}",
+            recipe
+        );
+
+        // Both directions should be correct, even though only one is registered
+        for &direction in &[FftDirection::Forward, FftDirection::Inverse] {
+            for &len in &[20, 150, 1230] {
+                let fft = planner.plan_fft(len, direction);
+                check_fft_algorithm(&*fft, len, direction);
+            }
+        }
+    }
+
     #[test]
     fn test_scalar_recipe_cache() {
         // Check that all butterflies are used
         let mut planner = FftPlannerScalar::<f64>::new();
         let fft_a = planner.design_fft_for_len(1234);
         let fft_b = planner.design_fft_for_len(1234);
-        assert!(Rc::ptr_eq(&fft_a, &fft_b), "Existing recipe was not reused");
+        assert!(
+            Arc::ptr_eq(&fft_a, &fft_b),
+            "Existing recipe was not reused"
+        );
+    }
+
+    #[test]
+    fn test_scalar_input_pruned() {
+        let mut planner = FftPlannerScalar::<f64>::new();
+        for &len in &[1, 17, 37, 64, 576, 1024, 1200] {
+            for &nonzero_len in &[0, 1, len / 10 + 1, len] {
+                for &direction in &[FftDirection::Forward, FftDirection::Inverse] {
+                    let fft = planner.plan_fft_input_pruned(len, direction, nonzero_len);
+                    check_input_pruned_fft_algorithm(&*fft, len, nonzero_len, direction, false);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_scalar_output_pruned() {
+        let mut planner = FftPlannerScalar::<f64>::new();
+        for &len in &[1, 17, 37, 64, 576, 1024, 1200] {
+            for &(start, end) in &[(0, 1), (len / 3, len / 3 + len / 10 + 1), (0, len)] {
+                for &direction in &[FftDirection::Forward, FftDirection::Inverse] {
+                    let fft = planner.plan_fft_output_range(len, direction, start..end);
+                    let mask: Vec<bool> = (0..len).map(|i| i >= start && i < end).collect();
+                    check_output_pruned_fft_algorithm(&*fft, &mask, direction);
+                }
+            }
+        }
     }
 }
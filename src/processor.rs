@@ -0,0 +1,153 @@
+use std::sync::Arc;
+
+use num_complex::Complex;
+use num_traits::Zero;
+
+use crate::{Direction, Fft, FftDirection, FftNum, Length};
+
+/// Computes FFTs using a scratch buffer that it owns, so that computing a FFT never allocates.
+///
+/// [`Fft::process`](crate::Fft::process) allocates a new scratch buffer on every call, and
+/// [`Fft::process_with_scratch`](crate::Fft::process_with_scratch) requires the caller to keep a right-sized scratch buffer
+/// around. `FftProcessor` wraps a FFT together with its scratch buffer, so that it can be held by a real-time thread, such as
+/// an audio callback, and used without any allocation.
+///
+/// The scratch buffer for in-place FFTs is allocated when the processor is created. The out-of-place scratch buffer is
+/// usually the same size or smaller, but if it's larger, the scratch buffer is grown the first time `process_outofplace` is called.
+/// To avoid that allocation, call `reserve_outofplace_scratch` before handing the processor to a real-time thread.
+///
+/// ~~~
+/// // Compute FFTs of size 1234 in a loop, without allocating
+/// use rustfft::{FftPlanner, FftProcessor, num_complex::Complex};
+///
+/// let mut planner = FftPlanner::new();
+/// let mut processor = FftProcessor::new(planner.plan_fft_forward(1234));
+///
+/// let mut buffer = vec![Complex{ re: 0.0f32, im: 0.0f32 }; 1234];
+/// for _ in 0..10 {
+///     processor.process(&mut buffer);
+/// }
+/// ~~~
+#[derive(Clone)]
+pub struct FftProcessor<T: FftNum> {
+    fft: Arc<dyn Fft<T>>,
+    scratch: Vec<Complex<T>>,
+}
+
+impl<T: FftNum> FftProcessor<T> {
+    /// Creates a processor which computes FFTs using `fft`, and allocates the scratch buffer required for in-place FFTs
+    pub fn new(fft: Arc<dyn Fft<T>>) -> Self {
+        let scratch = vec![Complex::zero(); fft.get_inplace_scratch_len()];
+        Self { fft, scratch }
+    }
+
+    /// Grows the scratch buffer, if necessary, so that `process_outofplace` never allocates
+    pub fn reserve_outofplace_scratch(&mut self) {
+        let required_len = self.fft.get_outofplace_scratch_len();
+        if self.scratch.len() < required_len {
+            self.scratch.resize(required_len, Complex::zero());
+        }
+    }
+
+    /// Returns the FFT that this processor computes
+    pub fn fft(&self) -> &Arc<dyn Fft<T>> {
+        &self.fft
+    }
+
+    /// Consumes this processor, returning the FFT that it computes
+    pub fn into_fft(self) -> Arc<dyn Fft<T>> {
+        self.fft
+    }
+
+    /// Divides `buffer` into chunks of size `self.len()`, and computes a FFT on each chunk, in-place.
+    ///
+    /// Never allocates.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if:
+    /// - `buffer.len() % self.len() > 0`
+    /// - `buffer.len() < self.len()`
+    pub fn process(&mut self, buffer: &mut [Complex<T>]) {
+        self.fft.process_with_scratch(buffer, &mut self.scratch);
+    }
+
+    /// Divides `input` and `output` into chunks of size `self.len()`, and computes a FFT on each chunk.
+    ///
+    /// This method uses the `input` buffer as scratch space, so the contents of `input` should be considered garbage after calling.
+    ///
+    /// Allocates only if the out-of-place scratch buffer is larger than the in-place scratch buffer, and only on the first call.
+    /// To avoid that allocation, call `reserve_outofplace_scratch` first.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if:
+    /// - `output.len() != input.len()`
+    /// - `input.len() % self.len() > 0`
+    /// - `input.len() < self.len()`
+    pub fn process_outofplace(&mut self, input: &mut [Complex<T>], output: &mut [Complex<T>]) {
+        self.reserve_outofplace_scratch();
+        self.fft
+            .process_outofplace_with_scratch(input, output, &mut self.scratch);
+    }
+}
+impl<T: FftNum> Length for FftProcessor<T> {
+    fn len(&self) -> usize {
+        self.fft.len()
+    }
+}
+impl<T: FftNum> Direction for FftProcessor<T> {
+    fn fft_direction(&self) -> FftDirection {
+        self.fft.fft_direction()
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::algorithm::Dft;
+    use crate::test_utils::{compare_vectors, random_signal};
+    use crate::FftPlanner;
+
+    #[test]
+    fn test_fft_processor() {
+        let mut planner = FftPlanner::new();
+        for &len in &[1, 2, 12, 64, 97, 1000, 1201] {
+            for &direction in &[FftDirection::Forward, FftDirection::Inverse] {
+                let mut processor = FftProcessor::new(planner.plan_fft(len, direction));
+                assert_eq!(processor.len(), len);
+                assert_eq!(processor.fft_direction(), direction);
+
+                // process a couple chunks at once, to make sure batches work
+                let input: Vec<Complex<f64>> = random_signal(len * 2);
+                let mut expected = input.clone();
+                Dft::new(len, direction).process(&mut expected);
+
+                let mut buffer = input.clone();
+                processor.process(&mut buffer);
+                assert!(
+                    compare_vectors(&expected, &buffer),
+                    "process() failed, len = {}, direction = {}",
+                    len,
+                    direction
+                );
+
+                let mut input_copy = input.clone();
+                let mut output = vec![Complex::zero(); len * 2];
+                processor.process_outofplace(&mut input_copy, &mut output);
+                assert!(
+                    compare_vectors(&expected, &output),
+                    "process_outofplace() failed, len = {}, direction = {}",
+                    len,
+                    direction
+                );
+
+                // Once both modes have been used, the scratch buffer should never need to grow again
+                let scratch_capacity = processor.scratch.capacity();
+                processor.process(&mut buffer);
+                processor.process_outofplace(&mut input_copy, &mut output);
+                assert_eq!(processor.scratch.capacity(), scratch_capacity);
+            }
+        }
+    }
+}
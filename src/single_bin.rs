@@ -0,0 +1,364 @@
+use num_complex::Complex;
+use num_traits::Zero;
+
+use crate::{common::FftNum, twiddles, FftDirection};
+use crate::{Direction, Length};
+
+/// Computes a handful of bins of a size-N DFT using the [Goertzel algorithm](https://en.wikipedia.org/wiki/Goertzel_algorithm)
+///
+/// Each bin costs O(N) to compute, so when only a few bins are needed, this is cheaper than computing a full FFT.
+/// The bins use the same conventions as [`Fft`](crate::Fft): bin `k` of a `Goertzel` instance matches element `k` of the output
+/// of a FFT with the same length and direction.
+///
+/// ~~~
+/// // Detect the energy at bins 5 and 12 of a size-205 forward DFT
+/// use rustfft::single_bin::Goertzel;
+/// use rustfft::FftDirection;
+/// use rustfft::num_complex::Complex;
+///
+/// let input = vec![Complex{ re: 0.0f32, im: 0.0f32 }; 205];
+/// let mut output = vec![Complex{ re: 0.0f32, im: 0.0f32 }; 2];
+///
+/// let goertzel = Goertzel::new(205, &[5, 12], FftDirection::Forward);
+/// goertzel.process(&input, &mut output);
+/// ~~~
+pub struct Goertzel<T> {
+    bins: Box<[usize]>,
+
+    // For each bin, `2 * cos(2 * pi * k / len)` and the conjugate of the bin's twiddle factor
+    coefficients: Box<[T]>,
+    twiddles: Box<[Complex<T>]>,
+
+    len: usize,
+    direction: FftDirection,
+}
+
+impl<T: FftNum> Goertzel<T> {
+    /// Creates an instance which computes the given bins of a DFT of size `len`
+    ///
+    /// # Panics
+    /// Panics if any element of `bins` is `>= len`.
+    pub fn new(len: usize, bins: &[usize], direction: FftDirection) -> Self {
+        let two = T::from_u8(2).unwrap();
+        let twiddles: Vec<Complex<T>> = bins
+            .iter()
+            .map(|&bin| {
+                assert!(
+                    bin < len,
+                    "Goertzel bins must be smaller than the DFT length. Expected bin < {}, got {}",
+                    len,
+                    bin
+                );
+                twiddles::compute_twiddle::<T>(bin, len, direction).conj()
+            })
+            .collect();
+        let coefficients = twiddles.iter().map(|twiddle| twiddle.re * two).collect();
+
+        Self {
+            bins: bins.to_vec().into_boxed_slice(),
+
+            coefficients,
+            twiddles: twiddles.into_boxed_slice(),
+
+            len,
+            direction,
+        }
+    }
+
+    /// Returns the DFT bins that this instance computes, in the order they're written by `process`
+    pub fn bins(&self) -> &[usize] {
+        &self.bins
+    }
+
+    /// Computes the DFT of `input`, and writes the requested bins to `output`
+    ///
+    /// # Panics
+    /// Panics if `input.len() != self.len()`, or if `output.len() != self.bins().len()`.
+    pub fn process(&self, input: &[Complex<T>], output: &mut [Complex<T>]) {
+        assert_eq!(
+            input.len(),
+            self.len,
+            "Goertzel input has the wrong length. Expected {}, got {}",
+            self.len,
+            input.len()
+        );
+        assert_eq!(
+            output.len(),
+            self.bins.len(),
+            "Goertzel output must contain one element per bin. Expected {}, got {}",
+            self.bins.len(),
+            output.len()
+        );
+
+        for ((out, &coefficient), &twiddle) in output
+            .iter_mut()
+            .zip(self.coefficients.iter())
+            .zip(self.twiddles.iter())
+        {
+            // Run the real-coefficient resonator over the input...
+            let mut prev1 = Complex::zero();
+            let mut prev2 = Complex::zero();
+            for &sample in input {
+                let current = sample + prev1.scale(coefficient) - prev2;
+                prev2 = prev1;
+                prev1 = current;
+            }
+
+            // ...then one final complex step rotates the resonator state into the DFT bin
+            *out = prev1 * twiddle - prev2;
+        }
+    }
+}
+impl<T> Length for Goertzel<T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+impl<T> Direction for Goertzel<T> {
+    fn fft_direction(&self) -> FftDirection {
+        self.direction
+    }
+}
+
+/// Tracks a handful of bins of a size-N DFT over a sliding window, updated one sample at a time in O(1) per bin
+///
+/// After each call to [`push`](SlidingDft::push), the tracked bins equal the corresponding bins of a FFT of the `len` most recent
+/// samples, oldest sample first. Before `len` samples have been pushed, the window is padded with leading zeros.
+///
+/// The classic sliding DFT multiplies its state by a twiddle factor on every sample, so rounding errors in that twiddle factor
+/// compound until the output drifts or diverges. This implementation instead uses the modulated sliding DFT: Each incoming
+/// sample is multiplied by an exact precomputed twiddle factor before it's accumulated, and the state is only rotated when
+/// the output is read. Rounding errors never get multiplied into the state, so they accumulate additively instead of compounding.
+///
+/// ~~~
+/// // Track bin 3 of a size-64 forward DFT
+/// use rustfft::single_bin::SlidingDft;
+/// use rustfft::FftDirection;
+/// use rustfft::num_complex::Complex;
+///
+/// let mut sliding_dft = SlidingDft::new(64, &[3], FftDirection::Forward);
+/// let mut output = vec![Complex{ re: 0.0f32, im: 0.0f32 }; 1];
+///
+/// for i in 0..1000 {
+///     sliding_dft.push(Complex{ re: i as f32, im: 0.0 });
+///     sliding_dft.spectrum(&mut output);
+/// }
+/// ~~~
+pub struct SlidingDft<T> {
+    bins: Box<[usize]>,
+
+    // twiddles[i] is the twiddle factor for bin 1 raised to the power of i. Bin k raised to the power of n is twiddles[(k * n) % len]
+    twiddles: Box<[Complex<T>]>,
+
+    // One accumulator per bin, along with that bin's current index into `twiddles`
+    accumulators: Box<[Complex<T>]>,
+    phases: Box<[usize]>,
+
+    history: Box<[Complex<T>]>,
+    history_index: usize,
+
+    direction: FftDirection,
+}
+
+impl<T: FftNum> SlidingDft<T> {
+    /// Creates an instance which tracks the given bins of a DFT of size `len`. The initial window contains all zeros.
+    ///
+    /// # Panics
+    /// Panics if `len == 0`, or if any element of `bins` is `>= len`.
+    pub fn new(len: usize, bins: &[usize], direction: FftDirection) -> Self {
+        assert!(len > 0, "SlidingDft requires a non-zero window length");
+        for &bin in bins {
+            assert!(
+                bin < len,
+                "SlidingDft bins must be smaller than the DFT length. Expected bin < {}, got {}",
+                len,
+                bin
+            );
+        }
+
+        let twiddles = (0..len)
+            .map(|i| twiddles::compute_twiddle(i, len, direction))
+            .collect();
+
+        Self {
+            bins: bins.to_vec().into_boxed_slice(),
+
+            twiddles,
+
+            accumulators: vec![Complex::zero(); bins.len()].into_boxed_slice(),
+            phases: vec![0; bins.len()].into_boxed_slice(),
+
+            history: vec![Complex::zero(); len].into_boxed_slice(),
+            history_index: 0,
+
+            direction,
+        }
+    }
+
+    /// Returns the DFT bins that this instance tracks, in the order they're written by `spectrum`
+    pub fn bins(&self) -> &[usize] {
+        &self.bins
+    }
+
+    /// Slides the window forward by one sample, discarding the oldest sample and appending `sample`
+    pub fn push(&mut self, sample: Complex<T>) {
+        let len = self.history.len();
+        let delta = sample - self.history[self.history_index];
+        self.history[self.history_index] = sample;
+        self.history_index += 1;
+        if self.history_index == len {
+            self.history_index = 0;
+        }
+
+        for ((accumulator, phase), &bin) in self
+            .accumulators
+            .iter_mut()
+            .zip(self.phases.iter_mut())
+            .zip(self.bins.iter())
+        {
+            *accumulator = *accumulator + delta * self.twiddles[*phase];
+            *phase += bin;
+            if *phase >= len {
+                *phase -= len;
+            }
+        }
+    }
+
+    /// Writes the current value of each tracked bin to `output`
+    ///
+    /// # Panics
+    /// Panics if `output.len() != self.bins().len()`.
+    pub fn spectrum(&self, output: &mut [Complex<T>]) {
+        assert_eq!(
+            output.len(),
+            self.bins.len(),
+            "SlidingDft output must contain one element per bin. Expected {}, got {}",
+            self.bins.len(),
+            output.len()
+        );
+
+        // Each accumulator is referenced to the absolute sample count. Rotate it so that the oldest sample in the window has phase 0.
+        // `phase` is already one sample ahead of the newest sample, which is exactly the oldest sample's position mod len
+        for ((out, accumulator), &phase) in output
+            .iter_mut()
+            .zip(self.accumulators.iter())
+            .zip(self.phases.iter())
+        {
+            *out = *accumulator * self.twiddles[phase].conj();
+        }
+    }
+
+    /// Clears the window back to all zeros
+    pub fn reset(&mut self) {
+        for element in self.accumulators.iter_mut().chain(self.history.iter_mut()) {
+            *element = Complex::zero();
+        }
+        for phase in self.phases.iter_mut() {
+            *phase = 0;
+        }
+        self.history_index = 0;
+    }
+}
+impl<T> Length for SlidingDft<T> {
+    fn len(&self) -> usize {
+        self.history.len()
+    }
+}
+impl<T> Direction for SlidingDft<T> {
+    fn fft_direction(&self) -> FftDirection {
+        self.direction
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::algorithm::Dft;
+    use crate::test_utils::{compare_vectors, random_signal};
+    use crate::Fft;
+
+    #[test]
+    fn test_goertzel() {
+        for len in 1..20 {
+            for &direction in &[FftDirection::Forward, FftDirection::Inverse] {
+                let input: Vec<Complex<f64>> = random_signal(len);
+                let mut expected = input.clone();
+                Dft::new(len, direction).process(&mut expected);
+
+                let bins: Vec<usize> = (0..len).rev().collect();
+                let goertzel = Goertzel::new(len, &bins, direction);
+                let mut actual = vec![Complex::zero(); bins.len()];
+                goertzel.process(&input, &mut actual);
+
+                let expected: Vec<Complex<f64>> = bins.iter().map(|&bin| expected[bin]).collect();
+                assert!(
+                    compare_vectors(&expected, &actual),
+                    "len = {}, direction = {}",
+                    len,
+                    direction
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_sliding_dft() {
+        let len = 12;
+        let bins = [0, 1, 5, 11];
+        let signal: Vec<Complex<f64>> = random_signal(len * 5);
+
+        for &direction in &[FftDirection::Forward, FftDirection::Inverse] {
+            let dft = Dft::new(len, direction);
+            let mut sliding_dft = SlidingDft::new(len, &bins, direction);
+
+            // Prepend zeros to the signal, to account for the initial window
+            let mut padded = vec![Complex::zero(); len];
+            padded.extend_from_slice(&signal);
+
+            for (i, &sample) in signal.iter().enumerate() {
+                sliding_dft.push(sample);
+
+                let mut expected = padded[i + 1..i + 1 + len].to_vec();
+                dft.process(&mut expected);
+                let expected: Vec<Complex<f64>> = bins.iter().map(|&bin| expected[bin]).collect();
+
+                let mut actual = vec![Complex::zero(); bins.len()];
+                sliding_dft.spectrum(&mut actual);
+
+                assert!(
+                    compare_vectors(&expected, &actual),
+                    "i = {}, direction = {}",
+                    i,
+                    direction
+                );
+            }
+
+            sliding_dft.reset();
+            let mut actual = vec![Complex::zero(); bins.len()];
+            sliding_dft.spectrum(&mut actual);
+            assert!(actual.iter().all(|x| x.is_zero()));
+        }
+    }
+
+    #[test]
+    fn test_sliding_dft_stability() {
+        // After a million updates, the output should still match a freshly computed FFT of the window
+        let len = 100;
+        let bins = [7];
+        let signal: Vec<Complex<f32>> = random_signal(1000);
+
+        let mut sliding_dft = SlidingDft::new(len, &bins, FftDirection::Forward);
+        for _ in 0..1000 {
+            for &sample in &signal {
+                sliding_dft.push(sample);
+            }
+        }
+
+        let mut expected = signal[signal.len() - len..].to_vec();
+        Dft::new(len, FftDirection::Forward).process(&mut expected);
+
+        let mut actual = vec![Complex::zero(); 1];
+        sliding_dft.spectrum(&mut actual);
+        assert!((actual[0] - expected[7]).norm() < 0.1);
+    }
+}
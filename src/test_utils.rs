@@ -5,7 +5,7 @@ use rand::distributions::{uniform::SampleUniform, Distribution, Uniform};
 use rand::{rngs::StdRng, SeedableRng};
 
 use crate::{algorithm::Dft, Direction, FftNum, Length};
-use crate::{Fft, FftDirection};
+use crate::{Fft, FftDirection, OutputPrunedFft};
 
 /// The seed for the random number generator used to generate
 /// random signals. It's defined here so that we have deterministic
@@ -156,6 +156,213 @@ pub fn check_fft_algorithm<T: FftNum + Float + SampleUniform>(
             );
         }
     }
+
+    // test process_split_with_scratch(), on two chunks. The second chunk is the first one doubled, so its expected output is doubled too
+    {
+        let two = T::from_f32(2.0).unwrap();
+        let split_input: Vec<Complex<T>> = reference_input
+            .iter()
+            .cloned()
+            .chain(reference_input.iter().map(|element| element * two))
+            .collect();
+        let split_expected: Vec<Complex<T>> = expected_output
+            .iter()
+            .cloned()
+            .chain(expected_output.iter().map(|element| element * two))
+            .collect();
+
+        let mut real: Vec<T> = split_input.iter().map(|element| element.re).collect();
+        let mut imag: Vec<T> = split_input.iter().map(|element| element.im).collect();
+        let mut scratch = vec![dirty_scratch_value; fft.get_split_scratch_len()];
+
+        fft.process_split_with_scratch(&mut real, &mut imag, &mut scratch);
+
+        let output: Vec<Complex<T>> = real
+            .iter()
+            .zip(imag.iter())
+            .map(|(re, im)| Complex::new(*re, *im))
+            .collect();
+        assert!(
+            compare_vectors(&split_expected, &output),
+            "process_split_with_scratch() failed, length = {}, direction = {}",
+            len,
+            direction
+        );
+    }
+
+    // test process_interleaved_with_scratch()
+    {
+        let mut buffer: Vec<T> = reference_input
+            .iter()
+            .flat_map(|element| vec![element.re, element.im])
+            .collect();
+        let mut scratch = vec![dirty_scratch_value; fft.get_inplace_scratch_len()];
+
+        fft.process_interleaved_with_scratch(&mut buffer, &mut scratch);
+
+        let output: Vec<Complex<T>> = buffer
+            .chunks(2)
+            .map(|pair| Complex::new(pair[0], pair[1]))
+            .collect();
+        assert!(
+            compare_vectors(&expected_output, &output),
+            "process_interleaved_with_scratch() failed, length = {}, direction = {}",
+            len,
+            direction
+        );
+    }
+}
+
+// Calls process_split_with_scratch with an imaginary buffer that's one element longer than the real buffer. Should panic
+pub fn split_mismatched_lengths<T: FftNum>(fft: &dyn Fft<T>) {
+    let mut real = vec![T::zero(); fft.len()];
+    let mut imag = vec![T::zero(); fft.len() + 1];
+    let mut scratch = vec![Zero::zero(); fft.get_split_scratch_len()];
+    fft.process_split_with_scratch(&mut real, &mut imag, &mut scratch);
+}
+
+// Calls process_split_with_scratch with buffers that hold one FFT and a partial chunk. Should panic
+pub fn split_partial_chunk<T: FftNum>(fft: &dyn Fft<T>) {
+    let mut real = vec![T::zero(); fft.len() + 1];
+    let mut imag = vec![T::zero(); fft.len() + 1];
+    let mut scratch = vec![Zero::zero(); fft.get_split_scratch_len()];
+    fft.process_split_with_scratch(&mut real, &mut imag, &mut scratch);
+}
+
+// Calls process_split_with_scratch with one element less scratch than get_split_scratch_len() requires. Should panic
+pub fn split_small_scratch<T: FftNum>(fft: &dyn Fft<T>) {
+    assert!(fft.get_split_scratch_len() > 0);
+    let mut real = vec![T::zero(); fft.len()];
+    let mut imag = vec![T::zero(); fft.len()];
+    let mut scratch = vec![Zero::zero(); fft.get_split_scratch_len() - 1];
+    fft.process_split_with_scratch(&mut real, &mut imag, &mut scratch);
+}
+
+// Checks that an output-pruned FFT computes every output in `output_mask`, and that each output it claims to compute matches a Dft
+pub fn check_output_pruned_fft_algorithm<T: FftNum + Float + SampleUniform>(
+    fft: &dyn OutputPrunedFft<T>,
+    output_mask: &[bool],
+    direction: FftDirection,
+) {
+    let len = output_mask.len();
+    assert_eq!(
+        fft.len(),
+        len,
+        "Algorithm reported incorrect size. Expected {}, got {}",
+        len,
+        fft.len()
+    );
+    assert_eq!(
+        fft.fft_direction(),
+        direction,
+        "Algorithm reported incorrect FFT direction"
+    );
+    for (i, &selected) in output_mask.iter().enumerate() {
+        assert!(
+            !selected || fft.computes_output(i),
+            "Algorithm doesn't compute selected output {}, length = {}",
+            i,
+            len
+        );
+    }
+
+    // process a couple chunks at once, to make sure the algorithm handles batches
+    let n = 2;
+    let dft = Dft::new(len, direction);
+    let reference_input = random_signal(len * n);
+    let mut expected_output = reference_input.clone();
+    dft.process(&mut expected_output);
+
+    let dirty_scratch_value = Complex::one() * T::from_i32(100).unwrap();
+    let mut scratch = vec![dirty_scratch_value; fft.get_inplace_scratch_len()];
+    let mut buffer = reference_input.clone();
+    fft.process_with_scratch(&mut buffer, &mut scratch);
+
+    let computed = |data: &[Complex<T>]| -> Vec<Complex<T>> {
+        data.iter()
+            .enumerate()
+            .filter(|(i, _)| fft.computes_output(i % len))
+            .map(|(_, value)| *value)
+            .collect()
+    };
+    let expected_output = computed(&expected_output);
+    assert!(
+        expected_output.is_empty() || compare_vectors(&expected_output, &computed(&buffer)),
+        "process_with_scratch() failed, length = {}, direction = {}",
+        len,
+        direction
+    );
+}
+
+// Checks that an input-pruned FFT computes the same output as a Dft of the zero-padded input.
+// If `ignores_padding` is true, also checks that it ignores the inputs after the first `nonzero_len`
+pub fn check_input_pruned_fft_algorithm<T: FftNum + Float + SampleUniform>(
+    fft: &dyn Fft<T>,
+    len: usize,
+    nonzero_len: usize,
+    direction: FftDirection,
+    ignores_padding: bool,
+) {
+    assert_eq!(
+        fft.len(),
+        len,
+        "Algorithm reported incorrect size. Expected {}, got {}",
+        len,
+        fft.len()
+    );
+    assert_eq!(
+        fft.fft_direction(),
+        direction,
+        "Algorithm reported incorrect FFT direction"
+    );
+
+    // process a couple chunks at once, to make sure the algorithm handles batches
+    let n = 2;
+    let dft = Dft::new(len, direction);
+    let mut reference_input = random_signal(len * n);
+    let mut expected_output = reference_input.clone();
+    for chunk in expected_output.chunks_mut(len) {
+        for element in chunk[nonzero_len..].iter_mut() {
+            *element = Zero::zero();
+        }
+    }
+    dft.process(&mut expected_output);
+
+    // If the padding should be ignored, fill it with garbage
+    let garbage_value = Complex::one() * T::from_i32(100).unwrap();
+    let padding_value = if ignores_padding {
+        garbage_value
+    } else {
+        Zero::zero()
+    };
+    for chunk in reference_input.chunks_mut(len) {
+        for element in chunk[nonzero_len..].iter_mut() {
+            *element = padding_value;
+        }
+    }
+
+    let mut buffer = reference_input.clone();
+    let mut scratch = vec![garbage_value; fft.get_inplace_scratch_len()];
+    fft.process_with_scratch(&mut buffer, &mut scratch);
+    assert!(
+        compare_vectors(&expected_output, &buffer),
+        "process_with_scratch() failed, length = {}, nonzero_len = {}, direction = {}",
+        len,
+        nonzero_len,
+        direction
+    );
+
+    let mut input = reference_input.clone();
+    let mut output = vec![garbage_value; len * n];
+    let mut scratch = vec![garbage_value; fft.get_outofplace_scratch_len()];
+    fft.process_outofplace_with_scratch(&mut input, &mut output, &mut scratch);
+    assert!(
+        compare_vectors(&expected_output, &output),
+        "process_outofplace_with_scratch() failed, length = {}, nonzero_len = {}, direction = {}",
+        len,
+        nonzero_len,
+        direction
+    );
 }
 
 // A fake FFT algorithm that requests much more scratch than it needs. You can use this as an inner FFT to other algorithms to test their scratch-supplying logic
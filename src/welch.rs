@@ -0,0 +1,590 @@
+use std::cmp::{max, min};
+use std::sync::Arc;
+
+use num_complex::Complex;
+use num_traits::Zero;
+
+use crate::{common::FftNum, Fft};
+
+// Segments are transformed in batches of about this many elements, so that the scratch space doesn't grow with the signal
+const BATCH_ELEMENTS: usize = 1 << 14;
+
+/// Window functions that can be used to taper each segment before it's transformed.
+///
+/// The coefficients are "periodic" (sometimes called "DFT-even"), which is the conventional choice for spectral analysis:
+/// A window of length `n` is the first `n` samples of a symmetric window of length `n + 1`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Window {
+    /// Every coefficient is 1, so segments are transformed as-is. This gives the narrowest main lobe, but the highest sidelobes,
+    /// so power leaks far from its frequency unless the frequency is centered on a bin.
+    Rectangular,
+    /// `0.5 - 0.5 * cos(2 * pi * i / n)`. Tapers to zero at the edges, trading a main lobe twice as wide as the rectangular window's
+    /// for sidelobes that fall off quickly. A good default.
+    Hann,
+    /// `0.54 - 0.46 * cos(2 * pi * i / n)`. Like the Hann window, but doesn't taper all the way to zero, which lowers the nearest sidelobes
+    /// at the cost of sidelobes that fall off slowly.
+    Hamming,
+    /// `0.42 - 0.5 * cos(2 * pi * i / n) + 0.08 * cos(4 * pi * i / n)`. Has a main lobe three times as wide as the rectangular window's,
+    /// with much lower sidelobes, for measuring weak frequencies next to strong ones.
+    Blackman,
+}
+impl Window {
+    /// Returns `len` coefficients of this window function
+    pub fn coefficients<T: FftNum>(&self, len: usize) -> Vec<T> {
+        let constant = 2f64 * std::f64::consts::PI / len as f64;
+        (0..len)
+            .map(|i| {
+                let angle = constant * i as f64;
+                let coefficient = match self {
+                    Self::Rectangular => 1f64,
+                    Self::Hann => 0.5 - 0.5 * angle.cos(),
+                    Self::Hamming => 0.54 - 0.46 * angle.cos(),
+                    Self::Blackman => 0.42 - 0.5 * angle.cos() + 0.08 * (2f64 * angle).cos(),
+                };
+                T::from_f64(coefficient).unwrap()
+            })
+            .collect()
+    }
+}
+
+/// Trend removal applied to each segment before windowing
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Detrend {
+    /// Leave each segment as-is
+    None,
+    /// Subtract the mean of each segment
+    Constant,
+    /// Subtract the least-squares line through each segment. Real and imaginary parts are fit independently.
+    Linear,
+}
+
+/// Normalization applied to the averaged periodogram
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum PsdScaling {
+    /// Power spectral density, in units of `signal^2 / Hz`. Summing every bin and multiplying by the bin width
+    /// `sample_rate / len` gives the mean power of the signal.
+    Density { sample_rate: f64 },
+    /// Power spectrum, in units of `signal^2`. A sinusoid centered on a bin reports its power in that bin.
+    Spectrum,
+}
+
+/// Which half (or halves) of the spectrum to output
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum PsdSides {
+    /// Output every bin, in the same order as the FFT output. Use this for complex signals.
+    TwoSided,
+    /// Output bins `0..=len/2`, folding the power of the negative frequencies into the positive ones.
+    /// Only meaningful for signals whose imaginary component is zero.
+    OneSided,
+}
+
+/// Estimates the power spectral density of a signal using [Welch's method](https://en.wikipedia.org/wiki/Welch%27s_method)
+///
+/// The signal is divided into overlapping segments of length `fft.len()`. Each segment is detrended, multiplied by a window,
+/// and transformed, and the squared magnitudes of the results are averaged. Segments are transformed in batches, with one call
+/// to the inner FFT per batch, so the per-call overhead of the FFT is shared by many segments while the scratch space stays
+/// the same size for any signal length.
+///
+/// ~~~
+/// // Estimate the PSD of a signal sampled at 1kHz, using Hann-windowed segments of size 256 with 50% overlap
+/// use rustfft::welch::{Detrend, PsdScaling, PsdSides, Welch, Window};
+/// use rustfft::FftPlanner;
+/// use rustfft::num_complex::Complex;
+///
+/// let signal = vec![Complex{ re: 0.0f32, im: 0.0f32 }; 4096];
+///
+/// let mut planner = FftPlanner::new();
+/// let fft = planner.plan_fft_forward(256);
+///
+/// let welch = Welch::new(
+///     fft,
+///     &Window::Hann.coefficients(256),
+///     128,
+///     Detrend::Constant,
+///     PsdScaling::Density { sample_rate: 1000.0 },
+///     PsdSides::OneSided,
+/// );
+///
+/// let mut psd = vec![0.0f32; welch.output_len()];
+/// welch.process(&signal, &mut psd);
+/// ~~~
+pub struct Welch<T> {
+    fft: Arc<dyn Fft<T>>,
+    window: Box<[T]>,
+
+    overlap: usize,
+    detrend: Detrend,
+    sides: PsdSides,
+
+    scale: T,
+}
+
+impl<T: FftNum> Welch<T> {
+    /// Creates a Welch estimator that splits signals into segments of size `fft.len()`, where consecutive segments
+    /// share `overlap` samples.
+    ///
+    /// Both forward and inverse FFTs produce the same power spectrum, but the bin order is mirrored for inverse FFTs.
+    ///
+    /// # Panics
+    /// Panics if `window.len() != fft.len()`, if `fft.len() == 0`, or if `overlap >= fft.len()`.
+    pub fn new(
+        fft: Arc<dyn Fft<T>>,
+        window: &[T],
+        overlap: usize,
+        detrend: Detrend,
+        scaling: PsdScaling,
+        sides: PsdSides,
+    ) -> Self {
+        let len = fft.len();
+        assert!(
+            len > 0,
+            "Welch's method requires a non-empty segment length"
+        );
+        assert_eq!(
+            window.len(),
+            len,
+            "Window length must match the FFT length. Expected {}, got {}",
+            len,
+            window.len()
+        );
+        assert!(
+            overlap < len,
+            "Segment overlap must be smaller than the segment length. Expected overlap < {}, got {}",
+            len,
+            overlap
+        );
+
+        // Both scalings divide by a measure of the window's energy, so that the result doesn't depend on the window choice
+        let scale = match scaling {
+            PsdScaling::Density { sample_rate } => {
+                let sum_squares = window.iter().fold(T::zero(), |acc, w| acc + *w * *w);
+                T::one() / (T::from_f64(sample_rate).unwrap() * sum_squares)
+            }
+            PsdScaling::Spectrum => {
+                let sum = window.iter().fold(T::zero(), |acc, w| acc + *w);
+                T::one() / (sum * sum)
+            }
+        };
+
+        Self {
+            fft,
+            window: window.to_vec().into_boxed_slice(),
+
+            overlap,
+            detrend,
+            sides,
+
+            scale,
+        }
+    }
+
+    /// Returns the number of bins written by `process`: `segment_len()` for a two-sided estimate, or `segment_len() / 2 + 1` for a one-sided estimate
+    pub fn output_len(&self) -> usize {
+        match self.sides {
+            PsdSides::TwoSided => self.segment_len(),
+            PsdSides::OneSided => self.segment_len() / 2 + 1,
+        }
+    }
+
+    /// Returns the segment length, which is also the length of the inner FFT
+    pub fn segment_len(&self) -> usize {
+        self.fft.len()
+    }
+
+    /// Returns the number of segments that will be averaged for a signal of length `signal_len`.
+    /// Samples at the end of the signal that don't fill a complete segment are ignored.
+    pub fn segment_count(&self, signal_len: usize) -> usize {
+        if signal_len < self.segment_len() {
+            0
+        } else {
+            (signal_len - self.segment_len()) / (self.segment_len() - self.overlap) + 1
+        }
+    }
+
+    /// Returns the size of the scratch buffer required by `process_with_scratch`. It doesn't depend on the signal length.
+    pub fn get_scratch_len(&self) -> usize {
+        self.batch_segments() * self.segment_len() + self.fft.get_inplace_scratch_len()
+    }
+
+    /// Computes the averaged power spectrum of `signal` and stores it in `output`.
+    ///
+    /// Convenience method that allocates a `Vec` with the required scratch space and calls `self.process_with_scratch`.
+    ///
+    /// # Panics
+    /// Panics if `signal.len() < self.segment_len()`, or if `output.len() != self.output_len()`.
+    pub fn process(&self, signal: &[Complex<T>], output: &mut [T]) {
+        let mut scratch = vec![Complex::zero(); self.get_scratch_len()];
+        self.process_with_scratch(signal, output, &mut scratch);
+    }
+
+    /// Computes the averaged power spectrum of `signal` and stores it in `output`.
+    ///
+    /// Uses the `scratch` buffer as scratch space, so the contents of `scratch` should be considered garbage
+    /// after calling.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if:
+    /// - `signal.len() < self.segment_len()`
+    /// - `output.len() != self.output_len()`
+    /// - `scratch.len() < self.get_scratch_len()`
+    pub fn process_with_scratch(
+        &self,
+        signal: &[Complex<T>],
+        output: &mut [T],
+        scratch: &mut [Complex<T>],
+    ) {
+        let len = self.segment_len();
+        assert!(
+            signal.len() >= len,
+            "Signal must contain at least one full segment. Expected len >= {}, got len = {}",
+            len,
+            signal.len()
+        );
+        assert_eq!(
+            output.len(),
+            self.output_len(),
+            "Output buffer has the wrong length. Expected {}, got {}",
+            self.output_len(),
+            output.len()
+        );
+        assert!(
+            scratch.len() >= self.get_scratch_len(),
+            "Not enough scratch space was provided. Expected scratch len >= {}, got scratch len = {}",
+            self.get_scratch_len(),
+            scratch.len()
+        );
+
+        let batch_segments = self.batch_segments();
+        let (batch_buffer, fft_scratch) = scratch.split_at_mut(batch_segments * len);
+        let fft_scratch = &mut fft_scratch[..self.fft.get_inplace_scratch_len()];
+
+        // Sum the periodograms of each segment into the output. Bins past the end of a one-sided output are never used
+        for total in output.iter_mut() {
+            *total = T::zero();
+        }
+        let segment_count = self.segment_count(signal.len());
+        let step = len - self.overlap;
+        for first_segment in (0..segment_count).step_by(batch_segments) {
+            // Copy a batch of segments into one contiguous buffer, so that we can transform them all with a single FFT call
+            let batch_len = min(batch_segments, segment_count - first_segment) * len;
+            let batch = &mut batch_buffer[..batch_len];
+            for (i, segment) in batch.chunks_exact_mut(len).enumerate() {
+                let start = (first_segment + i) * step;
+                segment.copy_from_slice(&signal[start..start + len]);
+                self.detrend_segment(segment);
+                for (element, window) in segment.iter_mut().zip(self.window.iter()) {
+                    *element = element.scale(*window);
+                }
+            }
+
+            self.fft.process_with_scratch(batch, fft_scratch);
+
+            for segment in batch.chunks_exact(len) {
+                for (total, element) in output.iter_mut().zip(segment.iter()) {
+                    *total = *total + element.norm_sqr();
+                }
+            }
+        }
+
+        // Average the periodograms
+        let scale = self.scale / T::from_usize(segment_count).unwrap();
+        match self.sides {
+            PsdSides::TwoSided => {
+                for total in output.iter_mut() {
+                    *total = *total * scale;
+                }
+            }
+            PsdSides::OneSided => {
+                // Every bin other than DC and Nyquist has a negative-frequency twin. Fold its power in by doubling
+                let two = T::from_u8(2).unwrap();
+                for (i, total) in output.iter_mut().enumerate() {
+                    *total = if i == 0 || i * 2 == len {
+                        *total * scale
+                    } else {
+                        *total * scale * two
+                    };
+                }
+            }
+        }
+    }
+
+    fn batch_segments(&self) -> usize {
+        max(1, BATCH_ELEMENTS / self.segment_len())
+    }
+
+    fn detrend_segment(&self, segment: &mut [Complex<T>]) {
+        match self.detrend {
+            Detrend::None => {}
+            Detrend::Constant => {
+                let sum = segment
+                    .iter()
+                    .fold(Complex::zero(), |acc: Complex<T>, x| acc + x);
+                let mean = sum.unscale(T::from_usize(segment.len()).unwrap());
+                for element in segment.iter_mut() {
+                    *element = *element - mean;
+                }
+            }
+            Detrend::Linear => {
+                // Fit `a + b * (i - center)`. Centering the index decouples the two unknowns, so each has a closed form
+                let n = segment.len();
+                let center = T::from_f64((n as f64 - 1f64) / 2f64).unwrap();
+                let mut sum = Complex::zero();
+                let mut weighted_sum = Complex::zero();
+                let mut sum_squares = T::zero();
+                for (i, element) in segment.iter().enumerate() {
+                    let x = T::from_usize(i).unwrap() - center;
+                    sum = sum + element;
+                    weighted_sum = weighted_sum + element.scale(x);
+                    sum_squares = sum_squares + x * x;
+                }
+                let intercept = sum.unscale(T::from_usize(n).unwrap());
+                let slope = if sum_squares.is_zero() {
+                    Complex::zero()
+                } else {
+                    weighted_sum.unscale(sum_squares)
+                };
+                for (i, element) in segment.iter_mut().enumerate() {
+                    let x = T::from_usize(i).unwrap() - center;
+                    *element = *element - intercept - slope.scale(x);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::algorithm::Dft;
+    use crate::test_utils::random_signal;
+    use crate::FftDirection;
+
+    fn sinusoid(len: usize, amplitude: f64, cycles_per_sample: f64) -> Vec<Complex<f64>> {
+        (0..len)
+            .map(|i| {
+                let angle = 2f64 * std::f64::consts::PI * cycles_per_sample * i as f64;
+                Complex::new(amplitude * angle.cos(), 0f64)
+            })
+            .collect()
+    }
+
+    // Reference implementation: transform each segment separately with a naive DFT
+    fn naive_welch(
+        signal: &[Complex<f64>],
+        window: &[f64],
+        overlap: usize,
+        scale: f64,
+    ) -> Vec<f64> {
+        let len = window.len();
+        let dft = Dft::new(len, FftDirection::Forward);
+        let mut result = vec![0f64; len];
+        let mut start = 0;
+        let mut count = 0;
+        while start + len <= signal.len() {
+            let mut segment: Vec<Complex<f64>> = signal[start..start + len]
+                .iter()
+                .zip(window.iter())
+                .map(|(x, w)| x * w)
+                .collect();
+            dft.process(&mut segment);
+            for (r, x) in result.iter_mut().zip(segment.iter()) {
+                *r += x.norm_sqr();
+            }
+            start += len - overlap;
+            count += 1;
+        }
+        result.iter().map(|r| r * scale / count as f64).collect()
+    }
+
+    #[test]
+    fn test_welch_matches_naive() {
+        for &(len, overlap, signal_len) in &[(16, 8, 100), (15, 0, 45), (32, 31, 40), (7, 3, 7)] {
+            let signal: Vec<Complex<f64>> = random_signal(signal_len);
+            let window = Window::Hann.coefficients::<f64>(len);
+            let fft = Arc::new(Dft::new(len, FftDirection::Forward)) as Arc<dyn Fft<f64>>;
+
+            let welch = Welch::new(
+                fft,
+                &window,
+                overlap,
+                Detrend::None,
+                PsdScaling::Spectrum,
+                PsdSides::TwoSided,
+            );
+            let mut actual = vec![0f64; welch.output_len()];
+            welch.process(&signal, &mut actual);
+
+            let window_sum: f64 = window.iter().sum();
+            let expected = naive_welch(&signal, &window, overlap, 1f64 / (window_sum * window_sum));
+            for (a, e) in actual.iter().zip(expected.iter()) {
+                assert!((a - e).abs() < 1e-9 * e.abs().max(1f64), "len = {}", len);
+            }
+        }
+    }
+
+    #[test]
+    fn test_welch_multiple_batches() {
+        // Enough segments to fill several batches, with a partial batch at the end
+        let (len, overlap) = (16, 8);
+        let signal: Vec<Complex<f64>> = random_signal(8 * 2500 + 8);
+        let window = Window::Hamming.coefficients::<f64>(len);
+        let fft = Arc::new(Dft::new(len, FftDirection::Forward)) as Arc<dyn Fft<f64>>;
+        let welch = Welch::new(
+            fft,
+            &window,
+            overlap,
+            Detrend::None,
+            PsdScaling::Spectrum,
+            PsdSides::TwoSided,
+        );
+        assert_eq!(welch.segment_count(signal.len()), 2500);
+        assert!(welch.get_scratch_len() < 2500 * len);
+
+        // The scratch buffer can be reused between calls, regardless of what it contains
+        let mut scratch = vec![Complex::new(std::f64::NAN, std::f64::NAN); welch.get_scratch_len()];
+        let mut actual = vec![0f64; welch.output_len()];
+        welch.process_with_scratch(&signal, &mut actual, &mut scratch);
+        let mut repeated = vec![std::f64::NAN; welch.output_len()];
+        welch.process_with_scratch(&signal, &mut repeated, &mut scratch);
+        assert_eq!(actual, repeated);
+
+        let window_sum: f64 = window.iter().sum();
+        let expected = naive_welch(&signal, &window, overlap, 1f64 / (window_sum * window_sum));
+        for (a, e) in actual.iter().zip(expected.iter()) {
+            assert!((a - e).abs() < 1e-9 * e.abs().max(1f64));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Not enough scratch space was provided")]
+    fn test_welch_small_scratch() {
+        let fft = Arc::new(Dft::new(16, FftDirection::Forward)) as Arc<dyn Fft<f64>>;
+        let welch = Welch::new(
+            fft,
+            &Window::Hann.coefficients(16),
+            8,
+            Detrend::None,
+            PsdScaling::Spectrum,
+            PsdSides::OneSided,
+        );
+        let signal = vec![Complex::zero(); 64];
+        let mut output = vec![0f64; welch.output_len()];
+        let mut scratch = vec![Complex::zero(); welch.get_scratch_len() - 1];
+        welch.process_with_scratch(&signal, &mut output, &mut scratch);
+    }
+
+    #[test]
+    fn test_welch_spectrum_scaling() {
+        // A real sinusoid of amplitude A centered on a bin has power A^2 / 2, which a one-sided power spectrum should report in that bin
+        let len = 64;
+        let amplitude = 3f64;
+        let signal = sinusoid(len * 8, amplitude, 5f64 / len as f64);
+        let fft = Arc::new(Dft::new(len, FftDirection::Forward)) as Arc<dyn Fft<f64>>;
+
+        let welch = Welch::new(
+            fft,
+            &Window::Rectangular.coefficients(len),
+            len / 2,
+            Detrend::Constant,
+            PsdScaling::Spectrum,
+            PsdSides::OneSided,
+        );
+        let mut psd = vec![0f64; welch.output_len()];
+        welch.process(&signal, &mut psd);
+
+        assert_eq!(psd.len(), len / 2 + 1);
+        assert!((psd[5] - amplitude * amplitude / 2f64).abs() < 1e-9);
+        let leakage: f64 = psd
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != 5)
+            .map(|(_, p)| p)
+            .sum();
+        assert!(leakage < 1e-9);
+    }
+
+    #[test]
+    fn test_welch_density_scaling() {
+        // Integrating a density over frequency should give the mean power of the signal, regardless of the window
+        let sample_rate = 48000f64;
+        for &len in &[64, 65] {
+            for &window in &[
+                Window::Rectangular,
+                Window::Hann,
+                Window::Hamming,
+                Window::Blackman,
+            ] {
+                let signal = sinusoid(len * 16, 2f64, 9f64 / len as f64);
+                let mean_power: f64 =
+                    signal.iter().map(|x| x.norm_sqr()).sum::<f64>() / signal.len() as f64;
+
+                let fft = Arc::new(Dft::new(len, FftDirection::Forward)) as Arc<dyn Fft<f64>>;
+                let welch = Welch::new(
+                    fft,
+                    &window.coefficients(len),
+                    len / 2,
+                    Detrend::None,
+                    PsdScaling::Density { sample_rate },
+                    PsdSides::OneSided,
+                );
+                let mut psd = vec![0f64; welch.output_len()];
+                welch.process(&signal, &mut psd);
+
+                let total: f64 = psd.iter().sum::<f64>() * sample_rate / len as f64;
+                assert!(
+                    (total - mean_power).abs() < 1e-3 * mean_power,
+                    "len = {}, window = {:?}, expected {}, got {}",
+                    len,
+                    window,
+                    mean_power,
+                    total
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_welch_detrend() {
+        // A pure offset + ramp should vanish entirely after linear detrending, and the offset alone after constant detrending
+        let len = 32;
+        let ramp: Vec<Complex<f64>> = (0..len * 4)
+            .map(|i| Complex::new(4f64 + 0.25 * i as f64, -1f64 + 0.5 * i as f64))
+            .collect();
+        let offset = vec![Complex::new(7f64, -2f64); len * 4];
+
+        for &(signal, detrend) in &[(&ramp, Detrend::Linear), (&offset, Detrend::Constant)] {
+            let fft = Arc::new(Dft::new(len, FftDirection::Forward)) as Arc<dyn Fft<f64>>;
+            let welch = Welch::new(
+                fft,
+                &Window::Hann.coefficients(len),
+                0,
+                detrend,
+                PsdScaling::Spectrum,
+                PsdSides::TwoSided,
+            );
+            let mut psd = vec![0f64; welch.output_len()];
+            welch.process(signal, &mut psd);
+            assert!(psd.iter().all(|p| p.abs() < 1e-18), "{:?}", detrend);
+        }
+    }
+
+    #[test]
+    fn test_welch_segment_count() {
+        let fft = Arc::new(Dft::new(10, FftDirection::Forward)) as Arc<dyn Fft<f32>>;
+        let welch = Welch::new(
+            fft,
+            &Window::Hann.coefficients(10),
+            4,
+            Detrend::None,
+            PsdScaling::Spectrum,
+            PsdSides::OneSided,
+        );
+        assert_eq!(welch.segment_count(9), 0);
+        assert_eq!(welch.segment_count(10), 1);
+        assert_eq!(welch.segment_count(15), 1);
+        assert_eq!(welch.segment_count(16), 2);
+        assert_eq!(welch.segment_count(100), 16);
+        assert_eq!(welch.output_len(), 6);
+    }
+}